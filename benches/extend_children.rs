@@ -0,0 +1,45 @@
+//! Compares building wide trees with repeated `push_child` calls against `extend_children`.
+//!
+//! Run with `cargo bench --bench extend_children`.
+
+use std::{hint::black_box, time::Instant};
+
+use tree_iter::tree::Node;
+
+/// Number of children added to each node.
+const WIDTH: usize = 10_000;
+/// Number of nodes built per measurement.
+const ROUNDS: usize = 200;
+
+fn push_children() -> Node<usize> {
+    let mut node = Node::new(0);
+    for i in 0..WIDTH {
+        node.push_child(Node::new(i));
+    }
+    node
+}
+
+fn extend_children() -> Node<usize> {
+    let mut node = Node::new(0);
+    node.extend_children((0..WIDTH).map(Node::new));
+    node
+}
+
+fn measure(name: &str, build: fn() -> Node<usize>) {
+    // Warm up the allocator before timing
+    black_box(build());
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        black_box(build());
+    }
+    let elapsed = start.elapsed();
+    println!(
+        "{name:>16}: {:?} per node of {WIDTH} children",
+        elapsed / ROUNDS as u32
+    );
+}
+
+fn main() {
+    measure("push_child", push_children);
+    measure("extend_children", extend_children);
+}
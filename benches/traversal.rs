@@ -0,0 +1,30 @@
+//! Measures traversal throughput of `Node` trees with the public benchmark harness.
+//!
+//! Run with `cargo bench --bench traversal`.
+
+use tree_iter::bench::{CountingAllocator, measure_all};
+use tree_iter::tree::Node;
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Builds a complete tree with the given branching factor and depth.
+fn complete_tree(branching: usize, depth: usize) -> Node<usize> {
+    let mut tree = Node::new(0);
+    for _ in 0..depth {
+        tree = Node::with_children(0, (0..branching).map(|_| tree.clone()));
+    }
+    tree
+}
+
+fn main() {
+    for (name, tree) in [
+        ("wide", complete_tree(100, 3)),
+        ("binary", complete_tree(2, 18)),
+    ] {
+        println!("{name}:");
+        for report in measure_all(&tree, 20) {
+            println!("  {report}");
+        }
+    }
+}
@@ -0,0 +1,236 @@
+use std::{collections::HashMap, fmt, marker::PhantomData};
+
+use serde::{
+    de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor},
+    ser::{Serialize, SerializeSeq, SerializeStruct, Serializer},
+};
+
+use crate::{iter::TreeNode, tree::Node};
+
+/// Serializes a tree as a flat list of `{id, parent_id, value}` records.
+///
+/// Records are written in depth-first (pre-order) order, ids are the positions in that
+/// order, and the root has a `parent_id` of `null`. The tree is walked iteratively.
+///
+/// Use it through `#[serde(with = "tree_iter::adjacency")]` on a `Node<T>` field:
+///
+/// ```rust
+/// use serde::{Deserialize, Serialize};
+/// use tree_iter::tree;
+/// use tree_iter::tree::Node;
+///
+/// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// struct Document {
+///     #[serde(with = "tree_iter::adjacency")]
+///     outline: Node<String>,
+/// }
+///
+/// let doc = Document {
+///     outline: tree! { "Intro".to_string() => ["Scope".to_string()] },
+/// };
+/// let json = serde_json::to_string(&doc).unwrap();
+/// assert_eq!(
+///     json,
+///     r#"{"outline":[{"id":0,"parent_id":null,"value":"Intro"},{"id":1,"parent_id":0,"value":"Scope"}]}"#
+/// );
+/// assert_eq!(serde_json::from_str::<Document>(&json).unwrap(), doc);
+/// ```
+pub fn serialize<T: Serialize, S: Serializer>(
+    root: &Node<T>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    // Length-prefixed formats need the number of records up front
+    let mut records = serializer.serialize_seq(Some(root.count()))?;
+    let mut next_id = 0;
+    let mut stack = vec![(root, None)];
+    while let Some((node, parent_id)) = stack.pop() {
+        let id = next_id;
+        next_id += 1;
+        records.serialize_element(&Record {
+            id,
+            parent_id,
+            value: &node.value,
+        })?;
+        stack.extend(node.children.iter().rev().map(|child| (child, Some(id))));
+    }
+    records.end()
+}
+
+/// Deserializes a tree from a flat list of `{id, parent_id, value}` records.
+///
+/// Ids can be any unique non-negative integers, and records can come in any order; the
+/// children of a node keep the order in which their records appear. Exactly one record,
+/// the root, must have a missing or `null` `parent_id`, and every other record must be
+/// connected to it. The tree is assembled iteratively, so it may be of any depth.
+pub fn deserialize<'de, T: Deserialize<'de>, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Node<T>, D::Error> {
+    let records = Vec::<Record<T>>::deserialize(deserializer)?;
+    assemble(records).map_err(de::Error::custom)
+}
+
+/// A node of the flat representation.
+struct Record<V> {
+    id: usize,
+    parent_id: Option<usize>,
+    value: V,
+}
+
+impl<V: Serialize> Serialize for Record<&V> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut record = serializer.serialize_struct("Record", 3)?;
+        record.serialize_field("id", &self.id)?;
+        record.serialize_field("parent_id", &self.parent_id)?;
+        record.serialize_field("value", self.value)?;
+        record.end()
+    }
+}
+
+/// The fields of a serialized [`Record`].
+enum Field {
+    Id,
+    ParentId,
+    Value,
+    Ignored,
+}
+
+impl<'de> Deserialize<'de> for Field {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FieldVisitor;
+
+        impl Visitor<'_> for FieldVisitor {
+            type Value = Field;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("`id`, `parent_id` or `value`")
+            }
+
+            fn visit_str<E: de::Error>(self, field: &str) -> Result<Field, E> {
+                Ok(match field {
+                    "id" => Field::Id,
+                    "parent_id" => Field::ParentId,
+                    "value" => Field::Value,
+                    _ => Field::Ignored,
+                })
+            }
+        }
+
+        deserializer.deserialize_identifier(FieldVisitor)
+    }
+}
+
+/// Visits a serialized [`Record`], given either as a map or as an `[id, parent_id, value]` sequence.
+struct RecordVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for RecordVisitor<T> {
+    type Value = Record<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a tree record")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Record<T>, A::Error> {
+        let id = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let parent_id = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        let value = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(2, &self))?;
+        Ok(Record {
+            id,
+            parent_id,
+            value,
+        })
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Record<T>, A::Error> {
+        let mut id = None;
+        let mut parent_id = None;
+        let mut value = None;
+        while let Some(field) = map.next_key()? {
+            match field {
+                Field::Id if id.is_some() => return Err(de::Error::duplicate_field("id")),
+                Field::Id => id = Some(map.next_value()?),
+                Field::ParentId if parent_id.is_some() => {
+                    return Err(de::Error::duplicate_field("parent_id"));
+                }
+                Field::ParentId => parent_id = Some(map.next_value()?),
+                Field::Value if value.is_some() => {
+                    return Err(de::Error::duplicate_field("value"));
+                }
+                Field::Value => value = Some(map.next_value()?),
+                Field::Ignored => {
+                    map.next_value::<de::IgnoredAny>()?;
+                }
+            }
+        }
+        Ok(Record {
+            id: id.ok_or_else(|| de::Error::missing_field("id"))?,
+            parent_id: parent_id.flatten(),
+            value: value.ok_or_else(|| de::Error::missing_field("value"))?,
+        })
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Record<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_struct(
+            "Record",
+            &["id", "parent_id", "value"],
+            RecordVisitor(PhantomData),
+        )
+    }
+}
+
+/// Builds the tree described by `records`, or describes why they do not form one.
+fn assemble<T>(records: Vec<Record<T>>) -> Result<Node<T>, String> {
+    let mut index = HashMap::with_capacity(records.len());
+    for (i, record) in records.iter().enumerate() {
+        if index.insert(record.id, i).is_some() {
+            return Err(format!("duplicate id {}", record.id));
+        }
+    }
+
+    let mut root = None;
+    let mut children = vec![Vec::new(); records.len()];
+    for (i, record) in records.iter().enumerate() {
+        match record.parent_id {
+            None if root.is_some() => return Err(format!("more than one root: {}", record.id)),
+            None => root = Some(i),
+            Some(parent_id) => match index.get(&parent_id) {
+                Some(&parent) => children[parent].push(i),
+                None => return Err(format!("unknown parent_id {parent_id}")),
+            },
+        }
+    }
+    let root = root.ok_or("no root record")?;
+
+    // Order the records so that every node comes before its descendants
+    let mut order = Vec::with_capacity(records.len());
+    let mut stack = vec![root];
+    while let Some(i) = stack.pop() {
+        order.push(i);
+        stack.extend(&children[i]);
+    }
+    if order.len() != records.len() {
+        return Err("records not connected to the root".to_string());
+    }
+
+    // Build the nodes bottom-up, so each node's children are ready when it is built
+    let mut values: Vec<Option<T>> = records.into_iter().map(|r| Some(r.value)).collect();
+    let mut built: Vec<Option<Node<T>>> = (0..values.len()).map(|_| None).collect();
+    for &i in order.iter().rev() {
+        let node = Node {
+            value: values[i].take().unwrap(),
+            children: children[i]
+                .iter()
+                .map(|&c| built[c].take().unwrap())
+                .collect(),
+        };
+        built[i] = Some(node);
+    }
+    Ok(built[root].take().unwrap())
+}
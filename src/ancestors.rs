@@ -0,0 +1,121 @@
+use crate::{flat::Flat, iter::TreeNode, preorder::PreorderIds};
+
+/// An index answering level ancestor queries in logarithmic time, with binary lifting.
+///
+/// For every node, the index stores its ancestors `1, 2, 4, 8, ...` levels up. Any
+/// ancestor is then reached in at most `log2(depth)` jumps, one for each bit of the
+/// distance. Building takes `O(n log h)` time and space, for a tree of height `h`. For a
+/// single query, [`Node::kth_ancestor`](crate::tree::Node::kth_ancestor) needs no
+/// preprocessing.
+///
+/// Nodes are identified by their [`PreorderIds`], returned by [`AncestorTable::ids`].
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::ancestors::AncestorTable;
+/// use tree_iter::tree;
+///
+/// let tree = tree! { 1 => [2 => [4, 5 => [6]], 3] };
+/// let table = AncestorTable::build(&tree);
+///
+/// let ids = table.ids();
+/// let id = ids.id(&[0, 1, 0]).unwrap();
+/// assert_eq!(table.kth_ancestor(id, 2).map(|id| ids.path(id)), Some(vec![0]));
+/// assert_eq!(table.ancestor_at_depth(id, 0), Some(0));
+/// assert_eq!(table.kth_ancestor(id, 4), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct AncestorTable {
+    /// For each `k`, the ancestor `2^k` levels above each node, or `None` if it is too shallow.
+    jumps: Vec<Vec<Option<usize>>>,
+    /// The numbering of the nodes.
+    ids: PreorderIds,
+}
+
+impl AncestorTable {
+    /// Builds the index for the tree rooted at `root`.
+    pub fn build<N: TreeNode>(root: &N) -> Self {
+        let flat = Flat::new(root);
+        let ids = PreorderIds::from_flat(&flat);
+        let height = flat.depth.iter().copied().max().unwrap_or(0);
+        let mut jumps = vec![flat.parent];
+        while 1 << jumps.len() <= height {
+            let previous = jumps.last().unwrap();
+            let level = previous
+                .iter()
+                .map(|&ancestor| ancestor.and_then(|ancestor| previous[ancestor]))
+                .collect();
+            jumps.push(level);
+        }
+
+        Self { jumps, ids }
+    }
+
+    /// Returns the numbering of the nodes of the tree.
+    pub fn ids(&self) -> &PreorderIds {
+        &self.ids
+    }
+
+    /// Returns the ancestor `k` levels above the node with the given id, or `None` if the
+    /// node is less than `k` levels deep.
+    ///
+    /// The ancestor with `k = 0` is the node itself.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is not less than [`PreorderIds::len`].
+    pub fn kth_ancestor(&self, id: usize, k: usize) -> Option<usize> {
+        if k > self.ids.depth(id) {
+            return None;
+        }
+        let mut node = id;
+        for (level, jumps) in self.jumps.iter().enumerate() {
+            if k >> level == 0 {
+                break;
+            }
+            if k >> level & 1 == 1 {
+                node = jumps[node]?;
+            }
+        }
+        Some(node)
+    }
+
+    /// Returns the ancestor of the node with the given id at `depth`, or `None` if the node
+    /// is shallower than that.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is not less than [`PreorderIds::len`].
+    pub fn ancestor_at_depth(&self, id: usize, depth: usize) -> Option<usize> {
+        self.kth_ancestor(id, self.ids.depth(id).checked_sub(depth)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::Node;
+
+    #[test]
+    fn test_matches_paths() {
+        let mut deep = Node::new(0);
+        for i in 1..200 {
+            deep = Node::with_children(i, [Node::new(-i), deep, Node::new(-i)]);
+        }
+        let table = AncestorTable::build(&deep);
+        let ids = table.ids();
+        for id in (0..ids.len()).step_by(7) {
+            let path = ids.path(id);
+            for k in 0..=path.len() {
+                let ancestor = table.kth_ancestor(id, k).unwrap();
+                assert_eq!(ids.path(ancestor), path[..path.len() - k]);
+                assert_eq!(
+                    deep.kth_ancestor(&path, k).map(|node| node.value),
+                    deep.get(&path[..path.len() - k]).map(|node| node.value)
+                );
+            }
+            assert_eq!(table.kth_ancestor(id, path.len() + 1), None);
+        }
+    }
+}
@@ -0,0 +1,212 @@
+use crate::iter::TreeNode;
+
+/// What the runner does after an [`Automaton`] enters a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Transition<S> {
+    /// Visit the node's children in the given state, then exit the node.
+    Descend(S),
+    /// Skip the node's subtree, without exiting the node.
+    Skip,
+}
+
+/// A state machine driven by a depth-first walk of a tree.
+///
+/// The runner keeps one state for each node that is open, from the root down. Entering a
+/// node takes the state of its parent and either gives the state of the node, in which
+/// its children are entered, or cuts off its subtree. Exiting a node gives back its state
+/// once its subtree is done. Both can emit outputs, which [`run_automaton`] collects.
+///
+/// This is the engine behind matchers and validators that need context from the
+/// ancestors of a node, such as selectors, schemas and scoped lookups.
+///
+/// # Type Parameters
+///
+/// * `N` - The type of node.
+pub trait Automaton<N> {
+    /// The state of the automaton inside a node.
+    type State;
+    /// The outputs the automaton emits.
+    type Output;
+
+    /// Returns the state in which the root is entered.
+    fn initial(&mut self) -> Self::State;
+
+    /// Enters a node, in the state of its parent, with the node's child-index path from the
+    /// root.
+    fn enter(
+        &mut self,
+        state: &Self::State,
+        node: &N,
+        path: &[usize],
+        output: &mut Vec<Self::Output>,
+    ) -> Transition<Self::State>;
+
+    /// Exits a node after its subtree, with the state it was entered in.
+    ///
+    /// Does nothing by default.
+    fn exit(
+        &mut self,
+        state: Self::State,
+        node: &N,
+        path: &[usize],
+        output: &mut Vec<Self::Output>,
+    ) {
+        let _ = (state, node, path, output);
+    }
+}
+
+/// A step of running an automaton.
+enum Step<'a, N> {
+    /// Enter a node, with its index among its siblings unless it is the root.
+    Enter(&'a N, Option<usize>),
+    /// Exit the innermost open node.
+    Exit(&'a N),
+}
+
+/// Runs an automaton over a tree in depth-first order, returning everything it emits.
+///
+/// The tree is walked iteratively, so trees of any depth can be run, and the path passed
+/// to the automaton is kept up to date rather than rebuilt for each node.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::automaton::{Automaton, Transition, run_automaton};
+/// use tree_iter::tree;
+/// use tree_iter::tree::Node;
+///
+/// /// Finds `item` nodes directly inside a `list`, never looking inside `hidden` nodes.
+/// struct ListItems;
+///
+/// impl Automaton<Node<&str>> for ListItems {
+///     type State = bool;
+///     type Output = Vec<usize>;
+///
+///     fn initial(&mut self) -> bool {
+///         false
+///     }
+///
+///     fn enter(
+///         &mut self,
+///         in_list: &bool,
+///         node: &Node<&str>,
+///         path: &[usize],
+///         output: &mut Vec<Vec<usize>>,
+///     ) -> Transition<bool> {
+///         match node.value {
+///             "hidden" => return Transition::Skip,
+///             "item" if *in_list => output.push(path.to_vec()),
+///             _ => {}
+///         }
+///         Transition::Descend(node.value == "list")
+///     }
+/// }
+///
+/// let tree = tree! {
+///     "doc" => ["item", "list" => ["item", "hidden" => ["list" => ["item"]], "item" => ["item"]]]
+/// };
+/// assert_eq!(run_automaton(&tree, ListItems), vec![vec![1, 0], vec![1, 2]]);
+/// ```
+pub fn run_automaton<N: TreeNode, A: Automaton<N>>(root: &N, mut automaton: A) -> Vec<A::Output> {
+    let mut output = Vec::new();
+    let mut states = vec![automaton.initial()];
+    let mut path = Vec::new();
+    let mut stack = vec![Step::Enter(root, None)];
+    while let Some(step) = stack.pop() {
+        match step {
+            Step::Enter(node, index) => {
+                path.extend(index);
+                let state = states
+                    .last()
+                    .expect("the parent of an entered node is open");
+                match automaton.enter(state, node, &path, &mut output) {
+                    Transition::Descend(state) => {
+                        states.push(state);
+                        stack.push(Step::Exit(node));
+                        let start = stack.len();
+                        stack.extend(
+                            node.children()
+                                .enumerate()
+                                .map(|(i, child)| Step::Enter(child, Some(i))),
+                        );
+                        stack[start..].reverse();
+                    }
+                    Transition::Skip => {
+                        if index.is_some() {
+                            path.pop();
+                        }
+                    }
+                }
+            }
+            Step::Exit(node) => {
+                let state = states.pop().expect("an exited node is open");
+                automaton.exit(state, node, &path, &mut output);
+                path.pop();
+            }
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::Node;
+
+    /// Emits the depth of every node on exit, and cuts every subtree below `limit`.
+    struct Depths {
+        limit: usize,
+    }
+
+    impl Automaton<Node<i32>> for Depths {
+        type State = usize;
+        type Output = (i32, usize);
+
+        fn initial(&mut self) -> usize {
+            0
+        }
+
+        fn enter(
+            &mut self,
+            depth: &usize,
+            node: &Node<i32>,
+            path: &[usize],
+            _: &mut Vec<(i32, usize)>,
+        ) -> Transition<usize> {
+            assert_eq!(path.len(), *depth);
+            if node.value < 0 || *depth > self.limit {
+                Transition::Skip
+            } else {
+                Transition::Descend(depth + 1)
+            }
+        }
+
+        fn exit(
+            &mut self,
+            depth: usize,
+            node: &Node<i32>,
+            path: &[usize],
+            output: &mut Vec<(i32, usize)>,
+        ) {
+            assert_eq!(path.len() + 1, depth);
+            output.push((node.value, depth - 1));
+        }
+    }
+
+    #[test]
+    fn test_exit_order_and_cuts() {
+        let tree = crate::tree! { 1 => [2 => [3, -4 => [5]], 6] };
+        assert_eq!(
+            run_automaton(&tree, Depths { limit: 10 }),
+            vec![(3, 2), (2, 1), (6, 1), (1, 0)]
+        );
+
+        let mut deep = Node::new(0);
+        for i in 1..100_000 {
+            deep = Node::with_children(i, [deep]);
+        }
+        let output = run_automaton(&deep, Depths { limit: 50_000 });
+        assert_eq!(output.len(), 50_001);
+        assert_eq!(output[0], (99_999 - 50_000, 50_000));
+    }
+}
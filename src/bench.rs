@@ -0,0 +1,289 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    any::type_name,
+    fmt,
+    hint::black_box,
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    iter::{TreeIter, TreeNode},
+    traversal_order::{BreadthFirst, DepthFirst, TraversalOrder},
+};
+
+/// Allocation counts kept by an allocator.
+struct Counters {
+    /// The number of allocations made.
+    allocations: AtomicU64,
+    /// Whether the allocator has served any request, i.e. is installed.
+    installed: AtomicBool,
+}
+
+impl Counters {
+    /// Creates counters with nothing counted.
+    const fn new() -> Self {
+        Self {
+            allocations: AtomicU64::new(0),
+            installed: AtomicBool::new(false),
+        }
+    }
+
+    /// Records a request to the allocator, counting it if it allocates memory.
+    fn record(&self, allocates: bool) {
+        if allocates {
+            self.allocations.fetch_add(1, Ordering::Relaxed);
+        }
+        self.installed.store(true, Ordering::Relaxed);
+    }
+}
+
+/// The counts of [`CountingAllocator`].
+static COUNTERS: Counters = Counters::new();
+
+/// A global allocator that counts allocations, for use with the benchmark harness.
+///
+/// It forwards to the system allocator. Install it in the benchmark binary to have
+/// [`BenchReport::allocations`] filled in:
+///
+/// ```rust
+/// use tree_iter::bench::CountingAllocator;
+///
+/// #[global_allocator]
+/// static ALLOCATOR: CountingAllocator = CountingAllocator;
+/// # fn main() {}
+/// ```
+///
+/// The count is process-wide, so allocations made by other threads during a measurement
+/// are included.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CountingAllocator;
+
+// SAFETY: every call is forwarded to the system allocator unchanged.
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        // SAFETY: the caller upholds the contract of `GlobalAlloc::alloc`.
+        unsafe { Counted(&COUNTERS).alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        // SAFETY: the caller upholds the contract of `GlobalAlloc::dealloc`.
+        unsafe { Counted(&COUNTERS).dealloc(ptr, layout) }
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        // SAFETY: the caller upholds the contract of `GlobalAlloc::alloc_zeroed`.
+        unsafe { Counted(&COUNTERS).alloc_zeroed(layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        // SAFETY: the caller upholds the contract of `GlobalAlloc::realloc`.
+        unsafe { Counted(&COUNTERS).realloc(ptr, layout, new_size) }
+    }
+}
+
+/// The system allocator, recording every request in the given counters.
+struct Counted<'c>(&'c Counters);
+
+// SAFETY: every call is forwarded to the system allocator unchanged.
+unsafe impl GlobalAlloc for Counted<'_> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        self.0.record(true);
+        // SAFETY: the caller upholds the contract of `GlobalAlloc::alloc`.
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.0.record(false);
+        // SAFETY: the caller upholds the contract of `GlobalAlloc::dealloc`.
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        self.0.record(true);
+        // SAFETY: the caller upholds the contract of `GlobalAlloc::alloc_zeroed`.
+        unsafe { System.alloc_zeroed(layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        self.0.record(true);
+        // SAFETY: the caller upholds the contract of `GlobalAlloc::realloc`.
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+/// The result of measuring the traversal of a tree in one order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchReport {
+    /// The name of the traversal order.
+    pub order: &'static str,
+    /// The number of nodes visited by one traversal.
+    pub nodes: usize,
+    /// The number of timed traversals.
+    pub iterations: u32,
+    /// The total time spent in the timed traversals.
+    pub elapsed: Duration,
+    /// The number of allocations per traversal, or `None` if [`CountingAllocator`] is not installed.
+    pub allocations: Option<f64>,
+}
+
+impl BenchReport {
+    /// Returns the number of nodes visited per second.
+    pub fn nodes_per_sec(&self) -> f64 {
+        let visited = self.nodes as f64 * f64::from(self.iterations);
+        visited / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+impl fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} nodes, {:.0} nodes/sec",
+            self.order,
+            self.nodes,
+            self.nodes_per_sec()
+        )?;
+        if let Some(allocations) = self.allocations {
+            write!(f, ", {allocations:.1} allocations/traversal")?;
+        }
+        Ok(())
+    }
+}
+
+/// Measures the traversal throughput of a tree in the order `O`.
+///
+/// The tree is traversed once to warm up, then `iterations` more times under the timer.
+///
+/// # Parameters
+///
+/// * `root` - The root node of the tree.
+/// * `iterations` - The number of timed traversals (at least one).
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::bench::measure;
+/// use tree_iter::prelude::*;
+/// use tree_iter::tree;
+///
+/// let tree = tree! { 1 => [2 => [4, 5], 3] };
+/// let report = measure::<BreadthFirst, _>(&tree, 100);
+/// assert_eq!(report.order, "BreadthFirst");
+/// assert_eq!(report.nodes, 5);
+/// println!("{report}");
+/// ```
+pub fn measure<O, N>(root: &N, iterations: u32) -> BenchReport
+where
+    O: TraversalOrder,
+    N: TreeNode,
+    for<'a> TreeIter<'a, N, O>: Iterator<Item = &'a N>,
+{
+    let iterations = iterations.max(1);
+    let nodes = TreeIter::<N, O>::new([root]).map(black_box).count();
+
+    let allocations_before = COUNTERS.allocations.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for _ in 0..iterations {
+        for node in TreeIter::<N, O>::new([root]) {
+            black_box(node);
+        }
+    }
+    let elapsed = start.elapsed();
+    let allocations = COUNTERS.allocations.load(Ordering::Relaxed) - allocations_before;
+
+    BenchReport {
+        order: type_name::<O>().rsplit("::").next().unwrap_or_default(),
+        nodes,
+        iterations,
+        elapsed,
+        allocations: COUNTERS
+            .installed
+            .load(Ordering::Relaxed)
+            .then(|| allocations as f64 / f64::from(iterations)),
+    }
+}
+
+/// Measures the traversal throughput of a tree in every traversal order.
+///
+/// See [`measure`] for details.
+pub fn measure_all<N: TreeNode>(root: &N, iterations: u32) -> Vec<BenchReport> {
+    vec![
+        measure::<DepthFirst, N>(root, iterations),
+        measure::<BreadthFirst, N>(root, iterations),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reports() {
+        let tree = crate::tree! { 1 => [2 => [4, 5], 3] };
+        let reports = measure_all(&tree, 0);
+        let orders: Vec<&str> = reports.iter().map(|report| report.order).collect();
+        assert_eq!(orders, vec!["DepthFirst", "BreadthFirst"]);
+        for report in &reports {
+            assert_eq!(report.nodes, 5);
+            assert_eq!(report.iterations, 1);
+            assert!(report.nodes_per_sec() > 0.0);
+        }
+
+        let report = BenchReport {
+            order: "DepthFirst",
+            nodes: 5,
+            iterations: 2,
+            elapsed: Duration::from_secs(1),
+            allocations: Some(1.0),
+        };
+        assert_eq!(report.nodes_per_sec(), 10.0);
+        assert_eq!(
+            report.to_string(),
+            "DepthFirst: 5 nodes, 10 nodes/sec, 1.0 allocations/traversal"
+        );
+    }
+
+    #[test]
+    fn test_every_entry_point_marks_installed() {
+        let layout = Layout::from_size_align(16, 8).unwrap();
+        let grown = Layout::from_size_align(32, 8).unwrap();
+        let counted = |counters: &Counters| {
+            (
+                counters.allocations.load(Ordering::Relaxed),
+                counters.installed.load(Ordering::Relaxed),
+            )
+        };
+
+        // Each entry point is checked on counters of its own, away from the global ones.
+        // A buffer that only ever grows is seen through `realloc` alone.
+        // SAFETY: the layouts have non-zero sizes, and every pointer is passed back to the
+        // allocator it came from with the layout it was allocated with.
+        unsafe {
+            let counters = Counters::new();
+            let ptr = System.alloc(layout);
+            assert!(!ptr.is_null());
+            let ptr = Counted(&counters).realloc(ptr, layout, 32);
+            assert!(!ptr.is_null());
+            assert_eq!(counted(&counters), (1, true));
+            System.dealloc(ptr, grown);
+
+            let counters = Counters::new();
+            let ptr = System.alloc(layout);
+            Counted(&counters).dealloc(ptr, layout);
+            assert_eq!(counted(&counters), (0, true));
+
+            let counters = Counters::new();
+            let ptr = Counted(&counters).alloc(layout);
+            assert!(!ptr.is_null());
+            assert_eq!(counted(&counters), (1, true));
+            System.dealloc(ptr, layout);
+
+            let counters = Counters::new();
+            let ptr = Counted(&counters).alloc_zeroed(layout);
+            assert!(!ptr.is_null());
+            assert_eq!(counted(&counters), (1, true));
+            System.dealloc(ptr, layout);
+        }
+    }
+}
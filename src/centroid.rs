@@ -0,0 +1,192 @@
+use std::mem;
+
+use crate::{flat::Flat, iter::TreeNode, traversal_order::DepthFirst, tree::Node};
+
+/// Returns the id of a centroid of the tree, as numbered by [`PreorderIds`].
+///
+/// A centroid is a node whose removal splits the tree, taken as undirected, into parts of
+/// at most half its nodes each. Every tree has one or two centroids; when there are two,
+/// they are adjacent and the one nearer the root is returned.
+///
+/// [`PreorderIds`]: crate::preorder::PreorderIds
+///
+/// # Parameters
+///
+/// * `root` - The root node of the tree.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::centroid::find_centroid;
+/// use tree_iter::tree;
+///
+/// // Ids are positions in depth-first order: 1, 2, 3, 4, 5, 6, 7
+/// let tree = tree! { 1 => [2 => [3 => [4, 5], 6 => [7]]] };
+/// assert_eq!(find_centroid(&tree), 1);
+/// ```
+pub fn find_centroid<N: TreeNode>(root: &N) -> usize {
+    let flat = Flat::new(root);
+    let size = |id: usize| flat.subtree_end[id] - id;
+
+    // Move towards the child holding more than half of the nodes, while there is one
+    let mut id = 0;
+    loop {
+        let mut child = id + 1;
+        while child < flat.subtree_end[id] && size(child) <= flat.len() / 2 {
+            child = flat.subtree_end[child];
+        }
+        if child == flat.subtree_end[id] {
+            return id;
+        }
+        id = child;
+    }
+}
+
+/// Returns the centroid decomposition of a tree.
+///
+/// The root of the decomposition is a centroid of the whole tree, as found by
+/// [`find_centroid`]. Removing it splits the tree into parts, taken as undirected, whose
+/// own centroids are its children, and so on down to single nodes. Each node of the tree is
+/// the value of exactly one node of the decomposition, as its id in depth-first order;
+/// [`PreorderIds::path`] gives its child-index path when needed.
+///
+/// Every part is at most half the size of the part it was split from, so the decomposition
+/// is at most `log2(n) + 1` levels deep. This is the starting point of divide-and-conquer
+/// algorithms over paths in a tree. It is computed iteratively in `O(n log n)` time.
+///
+/// [`PreorderIds::path`]: crate::preorder::PreorderIds::path
+///
+/// # Parameters
+///
+/// * `root` - The root node of the tree.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::centroid::centroid_decomposition;
+/// use tree_iter::prelude::*;
+/// use tree_iter::preorder::PreorderIds;
+/// use tree_iter::tree;
+///
+/// // A path of seven nodes is split in the middle, then each half in its middle
+/// let tree = tree! { 1 => [2 => [3 => [4 => [5 => [6 => [7]]]]]] };
+/// let decomposition = centroid_decomposition(&tree);
+/// assert_eq!(decomposition.value, 3);
+/// assert_eq!(decomposition.children[0].value, 5);
+/// assert_eq!(decomposition.children[1].value, 1);
+/// assert_eq!(decomposition.height(), 2);
+///
+/// let ids = PreorderIds::new(&tree);
+/// assert_eq!(ids.path(decomposition.value), vec![0, 0, 0]);
+/// ```
+pub fn centroid_decomposition<N: TreeNode>(root: &N) -> Node<usize> {
+    let flat = Flat::new(root);
+    // The neighbors of each node: its children, then its parent
+    let mut neighbors = vec![Vec::new(); flat.len()];
+    for (id, parent) in flat.parent.iter().enumerate() {
+        if let Some(parent) = *parent {
+            neighbors[parent].push(id);
+        }
+    }
+    for (id, parent) in flat.parent.iter().enumerate() {
+        neighbors[id].extend(*parent);
+    }
+
+    let mut removed = vec![false; flat.len()];
+    let mut size = vec![0; flat.len()];
+    let mut towards = vec![None; flat.len()];
+    // Each centroid, with the indices of the centroids of the parts it splits into
+    let mut centroids: Vec<(usize, Vec<usize>)> = Vec::new();
+    // Each part still to split, as one of its nodes and the index of the centroid above it
+    let mut pending: Vec<(usize, Option<usize>)> = vec![(0, None)];
+    let mut part = Vec::new();
+    while let Some((start, above)) = pending.pop() {
+        // Collect the part, each node with the neighbor it was reached from
+        part.clear();
+        part.push(start);
+        towards[start] = None;
+        let mut i = 0;
+        while let Some(&id) = part.get(i) {
+            i += 1;
+            for &next in &neighbors[id] {
+                if !removed[next] && towards[id] != Some(next) {
+                    towards[next] = Some(id);
+                    part.push(next);
+                }
+            }
+        }
+        for &id in part.iter().rev() {
+            size[id] = 1 + neighbors[id]
+                .iter()
+                .filter(|&&next| !removed[next] && towards[next] == Some(id))
+                .map(|&next| size[next])
+                .sum::<usize>();
+        }
+
+        let mut centroid = start;
+        while let Some(&next) = neighbors[centroid].iter().find(|&&next| {
+            !removed[next] && towards[next] == Some(centroid) && size[next] > part.len() / 2
+        }) {
+            centroid = next;
+        }
+
+        removed[centroid] = true;
+        let index = centroids.len();
+        centroids.push((centroid, Vec::new()));
+        if let Some(above) = above {
+            centroids[above].1.push(index);
+        }
+        pending.extend(
+            neighbors[centroid]
+                .iter()
+                .rev()
+                .filter(|&&next| !removed[next])
+                .map(|&next| (next, Some(index))),
+        );
+    }
+
+    Node::unfold::<DepthFirst, _>(0, |index| {
+        let (id, parts) = &mut centroids[index];
+        (*id, mem::take(parts))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parts_halve() {
+        let tree = Node::unfold::<DepthFirst, _>(1u32, |n| {
+            let children = match n % 3 {
+                _ if n > 200 => vec![],
+                0 => vec![],
+                1 => vec![2 * n],
+                _ => vec![2 * n, 2 * n + 1, 3 * n],
+            };
+            (n, children)
+        });
+        let decomposition = centroid_decomposition(&tree);
+        assert_eq!(decomposition.count(), tree.count());
+        assert_eq!(decomposition.value, find_centroid(&tree));
+
+        let mut stack = vec![&decomposition];
+        while let Some(node) = stack.pop() {
+            for child in &node.children {
+                assert!(child.count() <= node.count() / 2);
+            }
+            stack.extend(&node.children);
+        }
+
+        let mut ids: Vec<usize> = decomposition.iter_dfs().map(|node| node.value).collect();
+        ids.sort_unstable();
+        assert!(ids.into_iter().eq(0..tree.count()));
+
+        let mut deep = Node::new(0);
+        for i in 1..(1 << 11) - 1 {
+            deep = Node::with_children(i, [deep]);
+        }
+        assert_eq!(centroid_decomposition(&deep).height(), 10);
+        assert_eq!(find_centroid(&deep), (1 << 10) - 1);
+    }
+}
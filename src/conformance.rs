@@ -0,0 +1,396 @@
+use std::{error::Error, fmt, ptr};
+
+use crate::{
+    iter::TreeNode,
+    iter_mut::TreeNodeMut,
+    traversal_order::{BreadthFirst, DepthFirst, TraversalOrder},
+};
+
+/// A way in which a tree implementation fails to traverse consistently.
+///
+/// # Type Parameters
+///
+/// * `K` - The type of node ids.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ConformanceError<K> {
+    /// `children()` and `children_mut()` of a node give different children.
+    ChildrenMismatch {
+        /// The node whose children differ.
+        node: K,
+        /// The children given by `children()`.
+        children: Vec<K>,
+        /// The children given by `children_mut()`.
+        children_mut: Vec<K>,
+    },
+    /// Iterating the children of a node backwards does not give them in reverse.
+    ReversedMismatch {
+        /// The node whose children differ.
+        node: K,
+        /// Whether the children come from `children_mut()` rather than `children()`.
+        mutable: bool,
+        /// The children, iterated forwards.
+        forward: Vec<K>,
+        /// The children, iterated backwards and then reversed.
+        backward: Vec<K>,
+    },
+    /// A traversal does not visit the nodes in the expected order.
+    OrderMismatch {
+        /// The name of the traversal order.
+        order: &'static str,
+        /// Whether the traversal is the mutable one.
+        mutable: bool,
+        /// The order given by [`TraversalOrder::expected_sequence`].
+        expected: Vec<K>,
+        /// The order of the traversal.
+        actual: Vec<K>,
+    },
+}
+
+impl<K: fmt::Debug> fmt::Display for ConformanceError<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ChildrenMismatch {
+                node,
+                children,
+                children_mut,
+            } => write!(
+                f,
+                "children of node {node:?} differ: {children:?} from children(), {children_mut:?} from children_mut()"
+            ),
+            Self::ReversedMismatch {
+                node,
+                mutable,
+                forward,
+                backward,
+            } => write!(
+                f,
+                "{} of node {node:?} differ by direction: {forward:?} forwards, {backward:?} backwards",
+                if *mutable {
+                    "children_mut()"
+                } else {
+                    "children()"
+                }
+            ),
+            Self::OrderMismatch {
+                order,
+                mutable,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{}{order} traversal visits {actual:?}, expected {expected:?}",
+                if *mutable { "mutable " } else { "" }
+            ),
+        }
+    }
+}
+
+impl<K: fmt::Debug> Error for ConformanceError<K> {}
+
+/// Checks that a custom tree implementation traverses consistently.
+///
+/// This checks, in turn, that:
+///
+/// * `children()` and `children_mut()` give the same children, in the same order, for every node;
+/// * iterating the children of a node backwards gives them in reverse, as the depth-first
+///   iterators rely on;
+/// * the immutable and mutable traversals, in both orders, visit the nodes as given by
+///   [`TraversalOrder::expected_sequence`].
+///
+/// Nodes are compared by the ids given by `id`, which should tell apart all nodes of the tree.
+/// The tree is not modified.
+///
+/// # Parameters
+///
+/// * `root` - The root of the tree to check.
+/// * `id` - Gives the id of a node.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::conformance::{ConformanceError, check_conformance};
+/// use tree_iter::prelude::*;
+///
+/// struct Dir {
+///     name: &'static str,
+///     entries: Vec<Dir>,
+/// }
+///
+/// impl TreeNode for Dir {
+///     fn children(&self) -> impl DoubleEndedIterator<Item = &Self> {
+///         self.entries.iter()
+///     }
+/// }
+///
+/// impl TreeNodeMut for Dir {
+///     fn children_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut Self> {
+///         // Out of step with children()
+///         self.entries.iter_mut().rev()
+///     }
+/// }
+///
+/// let leaf = |name| Dir { name, entries: vec![] };
+/// let mut root = Dir { name: "/", entries: vec![leaf("bin"), leaf("etc")] };
+/// let error = check_conformance(&mut root, |dir| dir.name).unwrap_err();
+/// assert!(matches!(error, ConformanceError::ChildrenMismatch { node: "/", .. }));
+/// ```
+pub fn check_conformance<N, K>(
+    root: &mut N,
+    mut id: impl FnMut(&N) -> K,
+) -> Result<(), ConformanceError<K>>
+where
+    N: TreeNode + TreeNodeMut,
+    K: PartialEq,
+{
+    let mut stack = vec![&mut *root];
+    while let Some(node) = stack.pop() {
+        let forward = node.children().map(&mut id).collect();
+        let backward = node.children().rev().map(&mut id).collect();
+        let children = check_reversed(forward, backward, false, || id(node))?;
+
+        let forward = node.children_mut().map(|child| id(child)).collect();
+        let backward = node.children_mut().rev().map(|child| id(child)).collect();
+        let children_mut = check_reversed(forward, backward, true, || id(node))?;
+
+        if children != children_mut {
+            return Err(ConformanceError::ChildrenMismatch {
+                node: id(node),
+                children,
+                children_mut,
+            });
+        }
+        stack.extend(node.children_mut());
+    }
+
+    let expected = DepthFirst::expected_sequence(root, &mut id);
+    let actual = root.iter::<DepthFirst>().map(&mut id).collect();
+    check_order("depth-first", false, expected, actual)?;
+    let expected = DepthFirst::expected_sequence(root, &mut id);
+    let mut actual = Vec::new();
+    let mut iter = root.iter_mut::<DepthFirst>();
+    while let Some(node) = iter.next() {
+        actual.push(id(&node));
+    }
+    check_order("depth-first", true, expected, actual)?;
+
+    let expected = BreadthFirst::expected_sequence(root, &mut id);
+    let actual = root.iter::<BreadthFirst>().map(&mut id).collect();
+    check_order("breadth-first", false, expected, actual)?;
+    let expected = BreadthFirst::expected_sequence(root, &mut id);
+    let mut actual = Vec::new();
+    let mut iter = root.iter_mut::<BreadthFirst>();
+    while let Some(node) = iter.next() {
+        actual.push(id(&node));
+    }
+    check_order("breadth-first", true, expected, actual)
+}
+
+/// A divergence between the walks of a tree through `children()` and `children_mut()`,
+/// as found by [`verify_consistency`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Inconsistency {
+    /// The walks visit different numbers of nodes.
+    Count {
+        /// The number of nodes reached through `children()`.
+        children: usize,
+        /// The number of nodes reached through `children_mut()`.
+        children_mut: usize,
+    },
+    /// The walks visit the same number of nodes, but first differ at `position`, counted
+    /// from zero in depth-first order.
+    Order {
+        /// The position of the first differing node.
+        position: usize,
+    },
+}
+
+impl fmt::Display for Inconsistency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Count {
+                children,
+                children_mut,
+            } => write!(
+                f,
+                "children() reaches {children} nodes but children_mut() reaches {children_mut}"
+            ),
+            Self::Order { position } => write!(
+                f,
+                "children() and children_mut() diverge at node {position} in depth-first order"
+            ),
+        }
+    }
+}
+
+impl Error for Inconsistency {}
+
+/// Checks that `children()` and `children_mut()` describe the same tree.
+///
+/// The tree is walked depth-first once through each method, and the nodes reached are
+/// compared by address, so no ids are needed. Only the forward iterators are used; see
+/// [`check_conformance`] for a more thorough check. The tree is not modified.
+///
+/// Zero-sized nodes may share an address, so divergences among them can go unnoticed.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::conformance::{Inconsistency, verify_consistency};
+/// use tree_iter::prelude::*;
+///
+/// struct Menu {
+///     items: Vec<Menu>,
+///     hidden: Vec<Menu>,
+/// }
+///
+/// impl TreeNode for Menu {
+///     fn children(&self) -> impl DoubleEndedIterator<Item = &Self> {
+///         self.items.iter()
+///     }
+/// }
+///
+/// impl TreeNodeMut for Menu {
+///     fn children_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut Self> {
+///         // Forgot that hidden items are not children
+///         self.items.iter_mut().chain(self.hidden.iter_mut())
+///     }
+/// }
+///
+/// let leaf = || Menu { items: vec![], hidden: vec![] };
+/// let mut menu = Menu { items: vec![leaf()], hidden: vec![leaf()] };
+/// assert_eq!(
+///     verify_consistency(&mut menu),
+///     Err(Inconsistency::Count { children: 2, children_mut: 3 })
+/// );
+/// ```
+pub fn verify_consistency<N: TreeNode + TreeNodeMut>(root: &mut N) -> Result<(), Inconsistency> {
+    let immutable = DepthFirst::expected_sequence(root, |node| ptr::from_ref(node));
+
+    let mut mutable = vec![ptr::from_ref(&*root)];
+    let mut stack = vec![root.children_mut()];
+    while let Some(children) = stack.last_mut() {
+        match children.next() {
+            Some(child) => {
+                mutable.push(ptr::from_ref(&*child));
+                stack.push(child.children_mut());
+            }
+            None => {
+                stack.pop();
+            }
+        }
+    }
+
+    if immutable.len() != mutable.len() {
+        return Err(Inconsistency::Count {
+            children: immutable.len(),
+            children_mut: mutable.len(),
+        });
+    }
+    match immutable.iter().zip(&mutable).position(|(a, b)| a != b) {
+        Some(position) => Err(Inconsistency::Order { position }),
+        None => Ok(()),
+    }
+}
+
+/// Checks that the children of a node, iterated `backward` and reversed, match `forward`,
+/// returning them.
+fn check_reversed<K: PartialEq>(
+    forward: Vec<K>,
+    mut backward: Vec<K>,
+    mutable: bool,
+    node: impl FnOnce() -> K,
+) -> Result<Vec<K>, ConformanceError<K>> {
+    backward.reverse();
+    if forward == backward {
+        return Ok(forward);
+    }
+    Err(ConformanceError::ReversedMismatch {
+        node: node(),
+        mutable,
+        forward,
+        backward,
+    })
+}
+
+/// Checks that a traversal visited the nodes in the `expected` order.
+fn check_order<K: PartialEq>(
+    order: &'static str,
+    mutable: bool,
+    expected: Vec<K>,
+    actual: Vec<K>,
+) -> Result<(), ConformanceError<K>> {
+    if expected == actual {
+        return Ok(());
+    }
+    Err(ConformanceError::OrderMismatch {
+        order,
+        mutable,
+        expected,
+        actual,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::Node;
+
+    /// A node whose children, iterated backwards, leave out the last child.
+    struct Lossy(Vec<Lossy>, u8);
+
+    impl TreeNode for Lossy {
+        fn children(&self) -> impl DoubleEndedIterator<Item = &Self> {
+            Skip(self.0.iter(), false)
+        }
+    }
+
+    impl TreeNodeMut for Lossy {
+        fn children_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut Self> {
+            self.0.iter_mut()
+        }
+    }
+
+    /// Drops the last item when iterated backwards.
+    struct Skip<I>(I, bool);
+
+    impl<I: DoubleEndedIterator> Iterator for Skip<I> {
+        type Item = I::Item;
+
+        fn next(&mut self) -> Option<I::Item> {
+            self.0.next()
+        }
+    }
+
+    impl<I: DoubleEndedIterator> DoubleEndedIterator for Skip<I> {
+        fn next_back(&mut self) -> Option<I::Item> {
+            if !self.1 {
+                self.1 = true;
+                self.0.next_back();
+            }
+            self.0.next_back()
+        }
+    }
+
+    #[test]
+    fn test_check_conformance() {
+        let mut tree = crate::tree! { 1 => [2 => [4, 5], 3 => [6]] };
+        assert_eq!(
+            check_conformance(&mut tree, |node: &Node<i32>| node.value),
+            Ok(())
+        );
+
+        assert_eq!(verify_consistency(&mut tree), Ok(()));
+
+        let mut lossy = Lossy(vec![Lossy(vec![], 2), Lossy(vec![], 3)], 1);
+        assert_eq!(
+            check_conformance(&mut lossy, |node| node.1),
+            Err(ConformanceError::ReversedMismatch {
+                node: 1,
+                mutable: false,
+                forward: vec![2, 3],
+                backward: vec![2],
+            })
+        );
+        assert_eq!(verify_consistency(&mut lossy), Ok(()));
+    }
+}
@@ -0,0 +1,988 @@
+use std::{
+    error::Error,
+    fmt::{self, Write},
+    ptr,
+};
+
+use crate::{iter::TreeNode, tree::Node};
+
+/// Gives the key of a node.
+type KeyFn<'a, N, K> = Box<dyn FnMut(&N) -> K + 'a>;
+/// Gives the label of a node.
+type LabelFn<'a, N> = Box<dyn FnMut(&N) -> String + 'a>;
+/// Decides whether two matched nodes are unchanged.
+type EqFn<'a, N> = Box<dyn FnMut(&N, &N) -> bool + 'a>;
+/// Decides whether two subtrees are known to be identical.
+type PruneFn<'a, N> = Box<dyn FnMut(&N, &N) -> bool + 'a>;
+
+/// The largest table of common subsequence lengths built to match children; larger sets of
+/// children are matched by position.
+const MAX_TABLE_CELLS: usize = 1 << 22;
+
+/// How a row of a diff differs between the two trees.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Change {
+    /// The node is in both trees and unchanged.
+    Same,
+    /// The node is in both trees, with a changed value.
+    Changed,
+    /// The node is only in the left tree.
+    Removed,
+    /// The node is only in the right tree.
+    Added,
+}
+
+impl Change {
+    /// Returns the marker written at the start of a row.
+    fn marker(self) -> char {
+        match self {
+            Self::Same => ' ',
+            Self::Changed => '~',
+            Self::Removed => '-',
+            Self::Added => '+',
+        }
+    }
+}
+
+/// Renders a side-by-side diff of two trees, with `key` matching the children of matched
+/// nodes and `label` giving the label of each node.
+///
+/// See [`DiffFormatter`] for more options.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::diff::to_side_by_side;
+/// use tree_iter::tree;
+///
+/// let staging = tree! { ("app", "") => [("port", "80"), ("debug", "true")] };
+/// let production = tree! { ("app", "") => [("port", "443"), ("cache", "on")] };
+/// let diff = to_side_by_side(&staging, &production, |node| node.value.0, |node| {
+///     format!("{} {}", node.value.0, node.value.1).trim().to_string()
+/// });
+/// assert_eq!(
+///     diff.lines().collect::<Vec<_>>(),
+///     vec![
+///         "  app            │ app",
+///         "~   port 80      │   port 443",
+///         "-   debug true   │",
+///         "+                │   cache on",
+///     ]
+/// );
+/// ```
+pub fn to_side_by_side<N: TreeNode, K: PartialEq>(
+    left: &N,
+    right: &N,
+    key: impl FnMut(&N) -> K,
+    label: impl FnMut(&N) -> String,
+) -> String {
+    DiffFormatter::new(key, label).render(left, right)
+}
+
+/// Configurable side-by-side diff of two trees of any [`TreeNode`].
+///
+/// The roots are matched with each other, and the children of two matched nodes are
+/// matched by their keys, keeping their order, as in a line diff. Matched nodes are
+/// compared, by their labels unless [`DiffFormatter::eq`] says otherwise, and unmatched
+/// nodes are shown as removed or added along with their whole subtrees.
+///
+/// Each row shows a node of the left tree and the node of the right tree it matches, each
+/// indented by two spaces per level, after a marker: a space if they are the same, `~` if
+/// they are changed, `-` if only the left node exists and `+` if only the right one does.
+/// Rows are separated by `\n`, with no newline after the last one, and trailing spaces are
+/// left out. The trees are walked iteratively, so trees of any depth can be compared.
+///
+/// Matching the children of two nodes takes time and memory proportional to the product of
+/// their numbers of children, not counting the matching runs of children at the start and
+/// the end, which are matched in linear time. When that product exceeds about four million,
+/// the children in between are instead matched by position, pairing the children at the
+/// same index when their keys are equal, so that two very wide nodes that differ throughout
+/// are still diffed in linear time, though with more changes than needed.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::diff::{Change, DiffFormatter};
+/// use tree_iter::tree;
+/// use tree_iter::tree::Node;
+///
+/// let before = tree! { 10 => [21, 32] };
+/// let after = tree! { 10 => [23, 40] };
+/// let mut formatter = DiffFormatter::new(|node: &Node<i32>| node.value / 10, |node| node.value.to_string())
+///     .eq(|left, right| left.value / 2 == right.value / 2)
+///     .width(6);
+/// assert_eq!(formatter.render(&before, &after), "  10     │ 10\n~   21   │   23\n-   32   │\n+        │   40");
+///
+/// let changes: Vec<Change> = formatter.changes(&before, &after);
+/// assert_eq!(changes, vec![Change::Same, Change::Changed, Change::Removed, Change::Added]);
+/// ```
+pub struct DiffFormatter<'a, N, K> {
+    /// Gives the key of a node.
+    key: KeyFn<'a, N, K>,
+    /// Gives the label of a node.
+    label: LabelFn<'a, N>,
+    /// Decides whether two matched nodes are unchanged, if not by their labels.
+    eq: Option<EqFn<'a, N>>,
+    /// The width of the left column, or `None` to fit the widest left label.
+    width: Option<usize>,
+}
+
+/// A row of a diff, before it is written.
+struct Row {
+    /// How the row differs between the trees.
+    change: Change,
+    /// The depth of the nodes.
+    depth: usize,
+    /// The label of the left node, if there is one.
+    left: Option<String>,
+    /// The label of the right node, if there is one.
+    right: Option<String>,
+}
+
+/// A step of diffing two trees.
+enum Step<'n, N> {
+    /// Compare two matched nodes and their children.
+    Both(&'n N, &'n N, usize),
+    /// Show a node only in the left tree, with its subtree.
+    Left(&'n N, usize),
+    /// Show a node only in the right tree, with its subtree.
+    Right(&'n N, usize),
+}
+
+impl<'a, N: TreeNode, K: PartialEq> DiffFormatter<'a, N, K> {
+    /// Creates a formatter with `key` matching the children of matched nodes and `label`
+    /// giving the label of each node.
+    pub fn new(key: impl FnMut(&N) -> K + 'a, label: impl FnMut(&N) -> String + 'a) -> Self {
+        Self {
+            key: Box::new(key),
+            label: Box::new(label),
+            eq: None,
+            width: None,
+        }
+    }
+
+    /// Sets a function deciding whether two matched nodes are unchanged, instead of
+    /// comparing their labels.
+    pub fn eq(mut self, eq: impl FnMut(&N, &N) -> bool + 'a) -> Self {
+        self.eq = Some(Box::new(eq));
+        self
+    }
+
+    /// Sets the width of the left column, not counting the marker. Longer labels push the
+    /// separator of their row to the right.
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// Returns the change shown on each row of the diff, in order.
+    pub fn changes(&mut self, left: &N, right: &N) -> Vec<Change> {
+        self.rows(left, right)
+            .iter()
+            .map(|row| row.change)
+            .collect()
+    }
+
+    /// Renders the diff to a string.
+    pub fn render(&mut self, left: &N, right: &N) -> String {
+        let mut out = String::new();
+        self.write(left, right, &mut out)
+            .expect("writing to a String cannot fail");
+        out
+    }
+
+    /// Writes the diff to `out`.
+    pub fn write(&mut self, left: &N, right: &N, out: &mut impl Write) -> fmt::Result {
+        let rows = self.rows(left, right);
+        let text = |row: &Row, label: &Option<String>| {
+            label
+                .as_ref()
+                .map(|label| format!("{}{label}", "  ".repeat(row.depth)))
+                .unwrap_or_default()
+        };
+        let width = self.width.unwrap_or_else(|| {
+            rows.iter()
+                .map(|row| text(row, &row.left).chars().count())
+                .max()
+                .unwrap_or(0)
+                + 2
+        });
+
+        for (i, row) in rows.iter().enumerate() {
+            if i > 0 {
+                out.write_char('\n')?;
+            }
+            let line = format!(
+                "{} {:width$} │ {}",
+                row.change.marker(),
+                text(row, &row.left),
+                text(row, &row.right)
+            );
+            out.write_str(line.trim_end())?;
+        }
+        Ok(())
+    }
+
+    /// Matches the two trees and returns the rows of the diff.
+    fn rows(&mut self, left: &N, right: &N) -> Vec<Row> {
+        let mut rows = Vec::new();
+        let mut stack = vec![Step::Both(left, right, 0)];
+        while let Some(step) = stack.pop() {
+            let start = stack.len();
+            match step {
+                Step::Both(left, right, depth) => {
+                    let left_label = (self.label)(left);
+                    let right_label = (self.label)(right);
+                    let same = match &mut self.eq {
+                        Some(eq) => eq(left, right),
+                        None => left_label == right_label,
+                    };
+                    rows.push(Row {
+                        change: if same { Change::Same } else { Change::Changed },
+                        depth,
+                        left: Some(left_label),
+                        right: Some(right_label),
+                    });
+                    let steps = self.match_children(left, right, depth + 1);
+                    stack.extend(steps);
+                }
+                Step::Left(node, depth) => {
+                    rows.push(Row {
+                        change: Change::Removed,
+                        depth,
+                        left: Some((self.label)(node)),
+                        right: None,
+                    });
+                    stack.extend(node.children().map(|child| Step::Left(child, depth + 1)));
+                }
+                Step::Right(node, depth) => {
+                    rows.push(Row {
+                        change: Change::Added,
+                        depth,
+                        left: None,
+                        right: Some((self.label)(node)),
+                    });
+                    stack.extend(node.children().map(|child| Step::Right(child, depth + 1)));
+                }
+            }
+            stack[start..].reverse();
+        }
+        rows
+    }
+
+    /// Matches the children of two matched nodes by a longest common subsequence of their
+    /// keys, or by position when there are too many, and returns the steps for them in order.
+    fn match_children<'n>(&mut self, left: &'n N, right: &'n N, depth: usize) -> Vec<Step<'n, N>> {
+        let left: Vec<&N> = left.children().collect();
+        let right: Vec<&N> = right.children().collect();
+        let left_keys: Vec<K> = left.iter().map(|&node| (self.key)(node)).collect();
+        let right_keys: Vec<K> = right.iter().map(|&node| (self.key)(node)).collect();
+
+        // Matching children at the start and the end are matched without the table
+        let prefix = left_keys
+            .iter()
+            .zip(&right_keys)
+            .take_while(|(left, right)| left == right)
+            .count();
+        let suffix = left_keys[prefix..]
+            .iter()
+            .rev()
+            .zip(right_keys[prefix..].iter().rev())
+            .take_while(|(left, right)| left == right)
+            .count();
+        let (left_end, right_end) = (left.len() - suffix, right.len() - suffix);
+
+        let (width, height) = (left_end - prefix, right_end - prefix);
+        let mut steps = Vec::with_capacity(left.len() + right.len());
+        steps.extend((0..prefix).map(|i| Step::Both(left[i], right[i], depth)));
+        let suffix_steps =
+            (0..suffix).map(|k| Step::Both(left[left_end + k], right[right_end + k], depth));
+
+        // Too many children in between for the table: match them by position instead
+        if (width + 1).saturating_mul(height + 1) > MAX_TABLE_CELLS {
+            for k in prefix..left_end.max(right_end) {
+                match (
+                    (k < left_end).then(|| left[k]),
+                    (k < right_end).then(|| right[k]),
+                ) {
+                    (Some(l), Some(r)) if left_keys[k] == right_keys[k] => {
+                        steps.push(Step::Both(l, r, depth));
+                    }
+                    (l, r) => {
+                        steps.extend(l.map(|l| Step::Left(l, depth)));
+                        steps.extend(r.map(|r| Step::Right(r, depth)));
+                    }
+                }
+            }
+            steps.extend(suffix_steps);
+            return steps;
+        }
+
+        // The length of the longest common subsequence of each pair of suffixes of the
+        // children in between
+        let mut lengths = vec![vec![0; height + 1]; width + 1];
+        for i in (0..width).rev() {
+            for j in (0..height).rev() {
+                lengths[i][j] = if left_keys[prefix + i] == right_keys[prefix + j] {
+                    lengths[i + 1][j + 1] + 1
+                } else {
+                    lengths[i + 1][j].max(lengths[i][j + 1])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        while i < width || j < height {
+            let (l, r) = (prefix + i, prefix + j);
+            if i < width && j < height && left_keys[l] == right_keys[r] {
+                steps.push(Step::Both(left[l], right[r], depth));
+                i += 1;
+                j += 1;
+            } else if j == height || (i < width && lengths[i + 1][j] >= lengths[i][j + 1]) {
+                steps.push(Step::Left(left[l], depth));
+                i += 1;
+            } else {
+                steps.push(Step::Right(right[r], depth));
+                j += 1;
+            }
+        }
+        steps.extend(suffix_steps);
+        steps
+    }
+}
+
+impl<N, K> fmt::Debug for DiffFormatter<'_, N, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DiffFormatter")
+            .field("width", &self.width)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A position where two trees differ, found by [`iter_diff`].
+///
+/// # Type Parameters
+///
+/// * `'a` - The lifetime of the trees.
+/// * `N` - The type of node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Difference<'a, N> {
+    /// Both trees have a node at the path, with different values.
+    Changed {
+        /// The child-index path of the nodes.
+        path: Vec<usize>,
+        /// The node in the left tree.
+        left: &'a N,
+        /// The node in the right tree.
+        right: &'a N,
+    },
+    /// Only the left tree has a node at the path, with its whole subtree.
+    Removed {
+        /// The child-index path of the node.
+        path: Vec<usize>,
+        /// The node in the left tree.
+        node: &'a N,
+    },
+    /// Only the right tree has a node at the path, with its whole subtree.
+    Added {
+        /// The child-index path of the node.
+        path: Vec<usize>,
+        /// The node in the right tree.
+        node: &'a N,
+    },
+}
+
+impl<'a, N> Difference<'a, N> {
+    /// Returns the child-index path of the difference.
+    pub fn path(&self) -> &[usize] {
+        match self {
+            Self::Changed { path, .. } | Self::Removed { path, .. } | Self::Added { path, .. } => {
+                path
+            }
+        }
+    }
+}
+
+/// Walks two trees in lockstep, position by position, and yields only the positions where
+/// they differ, in depth-first order.
+///
+/// Nodes at the same child-index path are compared with `eq`. Where they differ, a
+/// [`Difference::Changed`] is yielded, and their children are compared either way. Where
+/// only one tree has a node, the node is yielded as [`Difference::Removed`] or
+/// [`Difference::Added`] and its subtree is not walked. Unlike [`DiffFormatter`], children
+/// are matched by position alone.
+///
+/// Subtrees are skipped without being walked when the two sides are the same node in
+/// memory, as in snapshots that share unchanged subtrees, or when the function set with
+/// [`IterDiff::prune`] reports them identical, for instance by comparing cached subtree
+/// hashes. Comparing successive snapshots then costs time in the size of the changes
+/// rather than of the trees.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::diff::{Difference, iter_diff};
+/// use tree_iter::tree;
+///
+/// let before = tree! { 1 => [2 => [4, 5], 3] };
+/// let after = tree! { 1 => [2 => [4, 6], 3 => [7]] };
+///
+/// let found: Vec<String> = iter_diff(&before, &after, |a, b| a.value == b.value)
+///     .map(|difference| match difference {
+///         Difference::Changed { path, left, right } => format!("{path:?}: {} -> {}", left.value, right.value),
+///         Difference::Removed { path, node } => format!("{path:?}: -{}", node.value),
+///         Difference::Added { path, node } => format!("{path:?}: +{}", node.value),
+///     })
+///     .collect();
+/// assert_eq!(found, vec!["[0, 1]: 5 -> 6", "[1, 0]: +7"]);
+/// ```
+pub fn iter_diff<'a, N: TreeNode, F: FnMut(&N, &N) -> bool>(
+    left: &'a N,
+    right: &'a N,
+    eq: F,
+) -> IterDiff<'a, N, F> {
+    IterDiff {
+        stack: vec![Position::Both(left, right, 0, 0)],
+        path: Vec::new(),
+        eq,
+        prune: None,
+    }
+}
+
+/// A position still to compare in [`IterDiff`], with its depth and its index among its
+/// siblings.
+enum Position<'a, N> {
+    /// Both trees have a node here.
+    Both(&'a N, &'a N, usize, usize),
+    /// Only the left tree has a node here.
+    Left(&'a N, usize, usize),
+    /// Only the right tree has a node here.
+    Right(&'a N, usize, usize),
+}
+
+/// An iterator over the positions where two trees differ, created by [`iter_diff`].
+///
+/// # Type Parameters
+///
+/// * `'a` - The lifetime of the trees and the prune function.
+/// * `N` - The type of node.
+/// * `F` - Decides whether two nodes at the same position are equal.
+pub struct IterDiff<'a, N, F> {
+    /// The positions still to compare, the next one last.
+    stack: Vec<Position<'a, N>>,
+    /// The child-index path of the last compared position.
+    path: Vec<usize>,
+    /// Decides whether two nodes at the same position are equal.
+    eq: F,
+    /// Decides whether two subtrees are known to be identical.
+    prune: Option<PruneFn<'a, N>>,
+}
+
+impl<'a, N: TreeNode, F: FnMut(&N, &N) -> bool> IterDiff<'a, N, F> {
+    /// Skips every pair of subtrees at the same position that `prune` reports identical.
+    ///
+    /// `prune` must only return `true` for subtrees that are equal throughout, such as ones
+    /// with equal cached hashes; their differences are not reported.
+    pub fn prune(mut self, prune: impl FnMut(&N, &N) -> bool + 'a) -> Self {
+        self.prune = Some(Box::new(prune));
+        self
+    }
+}
+
+impl<'a, N: TreeNode, F: FnMut(&N, &N) -> bool> Iterator for IterDiff<'a, N, F> {
+    type Item = Difference<'a, N>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(position) = self.stack.pop() {
+            let (Position::Both(.., depth, index)
+            | Position::Left(_, depth, index)
+            | Position::Right(_, depth, index)) = position;
+            if depth > 0 {
+                self.path.truncate(depth - 1);
+                self.path.push(index);
+            }
+            let (left, right) = match position {
+                Position::Both(left, right, ..) => (left, right),
+                Position::Left(node, ..) => {
+                    return Some(Difference::Removed {
+                        path: self.path.clone(),
+                        node,
+                    });
+                }
+                Position::Right(node, ..) => {
+                    return Some(Difference::Added {
+                        path: self.path.clone(),
+                        node,
+                    });
+                }
+            };
+            if ptr::eq(left, right) || self.prune.as_mut().is_some_and(|prune| prune(left, right)) {
+                continue;
+            }
+
+            let left_children: Vec<&N> = left.children().collect();
+            let right_children: Vec<&N> = right.children().collect();
+            let depth = depth + 1;
+            for i in (0..left_children.len().max(right_children.len())).rev() {
+                self.stack
+                    .push(match (left_children.get(i), right_children.get(i)) {
+                        (Some(left), Some(right)) => Position::Both(left, right, depth, i),
+                        (Some(left), None) => Position::Left(left, depth, i),
+                        (None, Some(right)) => Position::Right(right, depth, i),
+                        (None, None) => unreachable!("the index is below one of the lengths"),
+                    });
+            }
+            if !(self.eq)(left, right) {
+                return Some(Difference::Changed {
+                    path: self.path.clone(),
+                    left,
+                    right,
+                });
+            }
+        }
+        None
+    }
+}
+
+impl<N, F> fmt::Debug for IterDiff<'_, N, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IterDiff")
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Returns whether two trees, possibly of different node types, have the same shape and
+/// pairwise equal nodes by `eq`.
+///
+/// Nodes at the same child-index path are compared with `eq` in depth-first order, and the
+/// comparison stops at the first difference. The trees are walked iteratively, so they may
+/// be of any depth. This is how a custom tree is checked against a [`Node`] fixture.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::diff::eq_by;
+/// use tree_iter::prelude::*;
+/// use tree_iter::tree;
+///
+/// struct Dir {
+///     name: String,
+///     entries: Vec<Dir>,
+/// }
+///
+/// impl TreeNode for Dir {
+///     fn children(&self) -> impl DoubleEndedIterator<Item = &Self> {
+///         self.entries.iter()
+///     }
+/// }
+///
+/// let dir = |name: &str, entries| Dir { name: name.to_string(), entries };
+/// let home = dir("home", vec![dir("alice", vec![dir("notes", vec![])]), dir("bob", vec![])]);
+///
+/// let same = |a: &Dir, b: &tree_iter::tree::Node<&str>| a.name == b.value;
+/// assert!(eq_by(&home, &tree! { "home" => ["alice" => ["notes"], "bob"] }, same));
+/// assert!(!eq_by(&home, &tree! { "home" => ["alice", "bob"] }, same));
+/// ```
+pub fn eq_by<A: TreeNode, B: TreeNode>(a: &A, b: &B, mut eq: impl FnMut(&A, &B) -> bool) -> bool {
+    let mut stack = vec![(a, b)];
+    while let Some((a, b)) = stack.pop() {
+        if !eq(a, b) {
+            return false;
+        }
+        let start = stack.len();
+        let (mut left, mut right) = (a.children(), b.children());
+        loop {
+            match (left.next(), right.next()) {
+                (Some(a), Some(b)) => stack.push((a, b)),
+                (None, None) => break,
+                _ => return false,
+            }
+        }
+        stack[start..].reverse();
+    }
+    true
+}
+
+/// One step of an edit script turning one tree into another, created by [`edit_script`]
+/// and applied with [`Node::apply`].
+///
+/// Each edit records what it replaces as well as what it puts in place, so it can be
+/// checked against the tree it is applied to and undone with [`Edit::inverse`].
+///
+/// # Type Parameters
+///
+/// * `T` - The type of values in the trees.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Edit<T> {
+    /// Replaces the value of the node at the path.
+    Change {
+        /// The child-index path of the node.
+        path: Vec<usize>,
+        /// The value the node must have.
+        from: T,
+        /// The value to give the node.
+        to: T,
+    },
+    /// Removes the subtree at the path, shifting its later siblings to the left.
+    Remove {
+        /// The child-index path of the subtree.
+        path: Vec<usize>,
+        /// The subtree that must be at the path.
+        subtree: Node<T>,
+    },
+    /// Inserts a subtree at the path, shifting the siblings from there to the right.
+    Insert {
+        /// The child-index path the subtree will have.
+        path: Vec<usize>,
+        /// The subtree to insert.
+        subtree: Node<T>,
+    },
+}
+
+impl<T> Edit<T> {
+    /// Returns the child-index path of the edit.
+    pub fn path(&self) -> &[usize] {
+        match self {
+            Self::Change { path, .. } | Self::Remove { path, .. } | Self::Insert { path, .. } => {
+                path
+            }
+        }
+    }
+
+    /// Returns the edit that undoes this one.
+    pub fn inverse(self) -> Self {
+        match self {
+            Self::Change { path, from, to } => Self::Change {
+                path,
+                from: to,
+                to: from,
+            },
+            Self::Remove { path, subtree } => Self::Insert { path, subtree },
+            Self::Insert { path, subtree } => Self::Remove { path, subtree },
+        }
+    }
+}
+
+/// Returns the edits that turn `before` into `after`, in the order [`Node::apply`] applies
+/// them.
+///
+/// Children are matched by position, as in [`iter_diff`]: nodes at the same path with
+/// different values are changed, and extra children on either side are removed or
+/// inserted whole. Each edit's path refers to the tree as the edits before it leave it, so
+/// the removals of a node's trailing children come last child first.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::diff::{Edit, edit_script};
+/// use tree_iter::tree;
+///
+/// let before = tree! { 1 => [2 => [4, 5, 6], 3] };
+/// let after = tree! { 0 => [2 => [4], 3 => [7]] };
+///
+/// let script = edit_script(&before, &after);
+/// assert_eq!(
+///     script,
+///     vec![
+///         Edit::Change { path: vec![], from: 1, to: 0 },
+///         Edit::Remove { path: vec![0, 2], subtree: tree! { 6 } },
+///         Edit::Remove { path: vec![0, 1], subtree: tree! { 5 } },
+///         Edit::Insert { path: vec![1, 0], subtree: tree! { 7 } },
+///     ]
+/// );
+///
+/// let mut patched = before.clone();
+/// patched.apply(&script).unwrap();
+/// assert_eq!(patched, after);
+/// ```
+pub fn edit_script<T: Clone + PartialEq>(before: &Node<T>, after: &Node<T>) -> Vec<Edit<T>> {
+    let mut script = Vec::new();
+    // The position in `script` where the current run of sibling removals starts
+    let mut removals = 0;
+    for difference in iter_diff(before, after, |a, b| a.value == b.value) {
+        let edit = match difference {
+            Difference::Changed { path, left, right } => Edit::Change {
+                path,
+                from: left.value.clone(),
+                to: right.value.clone(),
+            },
+            Difference::Removed { path, node } => Edit::Remove {
+                path,
+                subtree: node.clone_subtree(&[]).expect("the root exists"),
+            },
+            Difference::Added { path, node } => Edit::Insert {
+                path,
+                subtree: node.clone_subtree(&[]).expect("the root exists"),
+            },
+        };
+        let is_sibling_removal = |previous: &Edit<T>| {
+            matches!(previous, Edit::Remove { .. })
+                && previous.path()[..previous.path().len() - 1]
+                    == edit.path()[..edit.path().len() - 1]
+        };
+        let extends_run =
+            matches!(edit, Edit::Remove { .. }) && script.last().is_some_and(is_sibling_removal);
+        if !extends_run {
+            script[removals..].reverse();
+            removals = script.len();
+        }
+        script.push(edit);
+    }
+    script[removals..].reverse();
+    script
+}
+
+/// The reason an [`Edit`] does not apply to a tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Conflict {
+    /// There is no node at the path, or for an insertion, no parent at the path or no
+    /// position for the new child. The root can be neither removed nor inserted.
+    Missing,
+    /// The node at the path does not have the value the change replaces.
+    Value,
+    /// The subtree at the path is not the one the removal expects.
+    Subtree,
+}
+
+/// The error returned when an edit script does not apply to a tree, created by
+/// [`Node::apply`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PatchError {
+    /// The position of the failing edit in the script.
+    pub edit: usize,
+    /// The child-index path of the failing edit.
+    pub path: Vec<usize>,
+    /// Why the edit does not apply.
+    pub conflict: Conflict,
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self.conflict {
+            Conflict::Missing => "the position does not exist",
+            Conflict::Value => "the value differs",
+            Conflict::Subtree => "the subtree differs",
+        };
+        write!(
+            f,
+            "edit {} at {:?} does not apply: {reason}",
+            self.edit, self.path
+        )
+    }
+}
+
+impl Error for PatchError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::Node;
+
+    #[test]
+    fn test_subtrees_and_order() {
+        let left = crate::tree! { "r" => ["a" => ["x", "y"], "b", "c"] };
+        let right = crate::tree! { "r" => ["c", "a" => ["y", "z" => ["w"]], "d"] };
+        let diff = to_side_by_side(
+            &left,
+            &right,
+            |node| node.value,
+            |node| node.value.to_string(),
+        );
+        assert_eq!(
+            diff.lines().collect::<Vec<_>>(),
+            vec![
+                "  r       │ r",
+                "-   a     │",
+                "-     x   │",
+                "-     y   │",
+                "-   b     │",
+                "    c     │   c",
+                "+         │   a",
+                "+         │     y",
+                "+         │     z",
+                "+         │       w",
+                "+         │   d",
+            ]
+        );
+
+        let mut before = Node::new(0);
+        let mut after = Node::new(-1);
+        for i in 1..10_000 {
+            before = Node::with_children(i, [before]);
+            after = Node::with_children(i, [after]);
+        }
+        let changes = DiffFormatter::new(|_: &Node<i32>| (), |node| node.value.to_string())
+            .changes(&before, &after);
+        assert_eq!(changes.len(), 10_000);
+        assert_eq!(
+            changes
+                .iter()
+                .filter(|&&change| change == Change::Same)
+                .count(),
+            9_999
+        );
+        assert_eq!(changes.last(), Some(&Change::Changed));
+    }
+
+    #[test]
+    fn test_iter_diff_pruning() {
+        let shared = crate::tree! { 10 => [11, 12 => [13]] };
+        let mut left = Node::with_children(0, [shared.clone(), Node::new(1), Node::new(2)]);
+        let right = Node::with_children(0, [shared, Node::new(5)]);
+        let mut compared = 0;
+        let paths: Vec<Vec<usize>> = iter_diff(&left, &right, |a, b| {
+            compared += 1;
+            a.value == b.value
+        })
+        .map(|difference| difference.path().to_vec())
+        .collect();
+        assert_eq!(paths, vec![vec![1], vec![2]]);
+        assert_eq!(compared, 6);
+
+        // Only the subtrees the prune function cannot vouch for are walked
+        left.children[1].value = 5;
+        let mut compared = 0;
+        let differences: Vec<Difference<'_, Node<i32>>> = iter_diff(&left, &right, |a, b| {
+            compared += 1;
+            a.value == b.value
+        })
+        .prune(|a, b| a.value == 10 && b.value == 10)
+        .collect();
+        assert_eq!(
+            differences,
+            vec![Difference::Removed {
+                path: vec![2],
+                node: &left.children[2]
+            }]
+        );
+        assert_eq!(compared, 2);
+
+        // The same node on both sides is skipped without any comparison
+        let mut compared = 0;
+        assert_eq!(
+            iter_diff(&left, &left, |_, _| {
+                compared += 1;
+                false
+            })
+            .count(),
+            0
+        );
+        assert_eq!(compared, 0);
+    }
+
+    #[test]
+    fn test_patch_round_trip() {
+        // Trees of varied shapes, numbered so that values and child counts both differ
+        let tree = |seed: u32| {
+            Node::unfold::<crate::traversal_order::DepthFirst, _>(1u32, |n| {
+                let children = if n < 40 {
+                    (0..(n * seed) % 4).map(|i| 2 * n + i).collect()
+                } else {
+                    vec![]
+                };
+                (n * seed % 7, children)
+            })
+        };
+        for (a, b) in [(1, 2), (3, 5), (2, 7), (5, 5)] {
+            let (before, after) = (tree(a), tree(b));
+            let script = edit_script(&before, &after);
+            let mut patched = before.clone();
+            patched.apply(&script).unwrap();
+            assert_eq!(patched, after);
+
+            let inverse: Vec<Edit<u32>> = script.into_iter().rev().map(Edit::inverse).collect();
+            patched.apply(&inverse).unwrap();
+            assert_eq!(patched, before);
+        }
+
+        // A script that fails part-way leaves the tree untouched
+        let mut tree = crate::tree! { 1 => [2, 3] };
+        let script = vec![
+            Edit::Change {
+                path: vec![0],
+                from: 2,
+                to: 4,
+            },
+            Edit::Insert {
+                path: vec![2],
+                subtree: Node::new(5),
+            },
+            Edit::Remove {
+                path: vec![1],
+                subtree: crate::tree! { 3 => [6] },
+            },
+        ];
+        let error = tree.apply(&script).unwrap_err();
+        assert_eq!((error.edit, error.conflict), (2, Conflict::Subtree));
+        assert_eq!(tree, crate::tree! { 1 => [2, 3] });
+        let missing = Edit::Insert {
+            path: vec![3],
+            subtree: Node::new(5),
+        };
+        assert_eq!(
+            tree.apply(&[missing]).unwrap_err().conflict,
+            Conflict::Missing
+        );
+    }
+
+    #[test]
+    fn test_wide_nodes_with_few_edits() {
+        // Far too wide for a table over all the children
+        let before = Node::with_children(0, (0..200_000).map(Node::new));
+        let mut after = before.clone();
+        after.children.remove(100_000);
+        after.children.insert(100_001, Node::new(-1));
+
+        let mut formatter =
+            DiffFormatter::new(|node: &Node<i32>| node.value, |node| node.value.to_string());
+        let changes = formatter.changes(&before, &after);
+        assert_eq!(changes.len(), 200_002);
+        assert_eq!(
+            changes[100_001..100_004],
+            [Change::Removed, Change::Same, Change::Added]
+        );
+        assert_eq!(
+            changes
+                .iter()
+                .filter(|&&change| change == Change::Same)
+                .count(),
+            200_000
+        );
+    }
+
+    #[test]
+    fn test_wide_nodes_changed_throughout() {
+        // Far too many cells for a table, so the children are matched by position
+        let before = Node::with_children(0, (0..100_000).map(Node::new));
+        let after = Node::with_children(0, (0..100_000).rev().map(Node::new));
+
+        let mut formatter =
+            DiffFormatter::new(|node: &Node<i32>| node.value, |node| node.value.to_string());
+        let changes = formatter.changes(&before, &after);
+        assert_eq!(changes.len(), 200_001);
+        assert_eq!(changes[..3], [Change::Same, Change::Removed, Change::Added]);
+        assert_eq!(
+            changes
+                .iter()
+                .filter(|&&change| change == Change::Same)
+                .count(),
+            1
+        );
+
+        // The children that do match in place are kept
+        let mut after = before.clone();
+        after.children.swap(0, 99_999);
+        for i in (1..99_999).step_by(2) {
+            after.children[i].value = -1;
+        }
+        let changes = formatter.changes(&before, &after);
+        assert_eq!(
+            changes
+                .iter()
+                .filter(|&&change| change == Change::Same)
+                .count(),
+            1 + 49_999
+        );
+    }
+}
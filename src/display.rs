@@ -0,0 +1,732 @@
+use std::fmt::{self, Display, Write};
+
+use crate::{iter::TreeNode, tree::Node};
+
+/// Gives the label of a node.
+type LabelFn<'a, N> = Box<dyn FnMut(&N) -> String + 'a>;
+/// Gives the style of a node's label.
+type StyleFn<'a, N> = Box<dyn FnMut(&N, NodeContext) -> Style + 'a>;
+
+/// The characters used to draw the branches of a tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Charset {
+    /// Box-drawing characters: `├──`, `└──` and `│`.
+    #[default]
+    Unicode,
+    /// Plain ASCII: `|--`, `` `-- `` and `|`.
+    Ascii,
+}
+
+impl Charset {
+    /// Returns the characters for a branch to a middle child, a branch to the last child,
+    /// a continuing line, and a horizontal line.
+    fn parts(self) -> (char, char, char, char) {
+        match self {
+            Self::Unicode => ('├', '└', '│', '─'),
+            Self::Ascii => ('|', '`', '|', '-'),
+        }
+    }
+
+    /// Returns the character where a horizontal line splits into several branches.
+    fn split(self) -> char {
+        match self {
+            Self::Unicode => '┬',
+            Self::Ascii => '+',
+        }
+    }
+}
+
+/// The direction in which a [`TreeFormatter`] lays out a tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Layout {
+    /// The root on the first line, with each node on its own line below its parent.
+    #[default]
+    Vertical,
+    /// The root on the left, with each node to the right of its parent and each leaf on
+    /// its own line. The first child of a node is on the same line as the node, and the
+    /// labels at each depth are aligned in a column. This suits wide, shallow trees.
+    ///
+    /// Labels are expected to fit on one line.
+    Horizontal,
+}
+
+/// A terminal color, written as an ANSI escape code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Color {
+    /// Black.
+    Black,
+    /// Red.
+    Red,
+    /// Green.
+    Green,
+    /// Yellow.
+    Yellow,
+    /// Blue.
+    Blue,
+    /// Magenta.
+    Magenta,
+    /// Cyan.
+    Cyan,
+    /// White.
+    White,
+    /// Bright black, or gray.
+    BrightBlack,
+    /// Bright red.
+    BrightRed,
+    /// Bright green.
+    BrightGreen,
+    /// Bright yellow.
+    BrightYellow,
+    /// Bright blue.
+    BrightBlue,
+    /// Bright magenta.
+    BrightMagenta,
+    /// Bright cyan.
+    BrightCyan,
+    /// Bright white.
+    BrightWhite,
+    /// A color of the 256-color palette.
+    Fixed(u8),
+    /// A 24-bit color, from its red, green and blue components.
+    Rgb(u8, u8, u8),
+}
+
+impl Color {
+    /// Writes the parameters of the escape code selecting this color, with `base` being
+    /// 30 for the foreground and 40 for the background.
+    fn write_code(self, base: u8, out: &mut impl Write) -> fmt::Result {
+        let code = match self {
+            Self::Black => base,
+            Self::Red => base + 1,
+            Self::Green => base + 2,
+            Self::Yellow => base + 3,
+            Self::Blue => base + 4,
+            Self::Magenta => base + 5,
+            Self::Cyan => base + 6,
+            Self::White => base + 7,
+            Self::BrightBlack => base + 60,
+            Self::BrightRed => base + 61,
+            Self::BrightGreen => base + 62,
+            Self::BrightYellow => base + 63,
+            Self::BrightBlue => base + 64,
+            Self::BrightMagenta => base + 65,
+            Self::BrightCyan => base + 66,
+            Self::BrightWhite => base + 67,
+            Self::Fixed(index) => return write!(out, "{};5;{index}", base + 8),
+            Self::Rgb(red, green, blue) => {
+                return write!(out, "{};2;{red};{green};{blue}", base + 8);
+            }
+        };
+        write!(out, "{code}")
+    }
+}
+
+/// The terminal style of a label: its colors and text attributes.
+///
+/// The default style is plain text, which is written without escape codes.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::display::{Color, Style};
+///
+/// let style = Style::new().fg(Color::Red).bold();
+/// assert_eq!(style.paint("error"), "\x1b[1;31merror\x1b[0m");
+/// assert_eq!(Style::new().paint("plain"), "plain");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Style {
+    /// The text color.
+    foreground: Option<Color>,
+    /// The background color.
+    background: Option<Color>,
+    /// Whether the text is bold.
+    bold: bool,
+    /// Whether the text is dimmed.
+    dim: bool,
+    /// Whether the text is italic.
+    italic: bool,
+    /// Whether the text is underlined.
+    underline: bool,
+}
+
+impl Style {
+    /// Creates a plain style.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the text color.
+    pub fn fg(mut self, color: Color) -> Self {
+        self.foreground = Some(color);
+        self
+    }
+
+    /// Sets the background color.
+    pub fn bg(mut self, color: Color) -> Self {
+        self.background = Some(color);
+        self
+    }
+
+    /// Makes the text bold.
+    pub fn bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+
+    /// Makes the text dimmed.
+    pub fn dim(mut self) -> Self {
+        self.dim = true;
+        self
+    }
+
+    /// Makes the text italic.
+    pub fn italic(mut self) -> Self {
+        self.italic = true;
+        self
+    }
+
+    /// Makes the text underlined.
+    pub fn underline(mut self) -> Self {
+        self.underline = true;
+        self
+    }
+
+    /// Returns whether the style is plain text.
+    pub fn is_plain(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Returns `text` in this style, between the escape codes that start and reset it.
+    pub fn paint(&self, text: &str) -> String {
+        let mut out = String::new();
+        self.write(text, &mut out)
+            .expect("writing to a String cannot fail");
+        out
+    }
+
+    /// Writes `text` in this style to `out`.
+    fn write(&self, text: &str, out: &mut impl Write) -> fmt::Result {
+        if self.is_plain() || text.is_empty() {
+            return out.write_str(text);
+        }
+        out.write_str("\x1b[")?;
+        let mut codes = Vec::new();
+        for (set, code) in [
+            (self.bold, 1),
+            (self.dim, 2),
+            (self.italic, 3),
+            (self.underline, 4),
+        ] {
+            if set {
+                codes.push(code.to_string());
+            }
+        }
+        for (color, base) in [(self.foreground, 30), (self.background, 40)] {
+            if let Some(color) = color {
+                let mut code = String::new();
+                color.write_code(base, &mut code)?;
+                codes.push(code);
+            }
+        }
+        write!(out, "{}m{text}\x1b[0m", codes.join(";"))
+    }
+}
+
+/// Where a node is in the tree, as passed to the styling function of a [`TreeFormatter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct NodeContext {
+    /// The depth of the node, with the root at depth 0.
+    pub depth: usize,
+    /// The position of the node among its siblings.
+    pub index: usize,
+    /// Whether the node is the last of its siblings, which the root is.
+    pub is_last: bool,
+}
+
+impl NodeContext {
+    /// Returns the context of a child of the node at this context.
+    fn child(self, index: usize, count: usize) -> Self {
+        Self {
+            depth: self.depth + 1,
+            index,
+            is_last: index + 1 == count,
+        }
+    }
+}
+
+/// The context of the root.
+const ROOT_CONTEXT: NodeContext = NodeContext {
+    depth: 0,
+    index: 0,
+    is_last: true,
+};
+
+/// Renders a tree as text with box-drawing branches, with `label` giving the label of
+/// each node.
+///
+/// See [`TreeFormatter`] for more options.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::display::to_pretty;
+/// use tree_iter::tree;
+///
+/// let tree = tree! { "src" => ["tree" => ["mod.rs", "node.rs"], "lib.rs"] };
+/// assert_eq!(
+///     to_pretty(&tree, |node| node.value.to_string()),
+///     "src\n├── tree\n│   ├── mod.rs\n│   └── node.rs\n└── lib.rs"
+/// );
+/// ```
+pub fn to_pretty<N: TreeNode>(root: &N, label: impl FnMut(&N) -> String) -> String {
+    TreeFormatter::new(label).render(root)
+}
+
+/// Configurable text rendering of any [`TreeNode`], with branches drawn between the nodes.
+///
+/// Each node is written on its own line, below its parent and indented by one level per
+/// depth. Lines are separated by `\n`, with no newline after the last one. A label with
+/// several lines is kept aligned, with its later lines indented like the node's children.
+/// The tree is walked iteratively, so trees of any depth can be rendered.
+///
+/// [`Node`] implements [`Display`] with the default settings.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::display::{Charset, TreeFormatter};
+/// use tree_iter::tree;
+///
+/// let tree = tree! { 1 => [2 => [4], 3] };
+/// let text = TreeFormatter::new(|node: &tree_iter::tree::Node<i32>| format!("#{}", node.value))
+///     .charset(Charset::Ascii)
+///     .indent(3)
+///     .render(&tree);
+/// assert_eq!(text, "#1\n|- #2\n|  `- #4\n`- #3");
+///
+/// assert_eq!(tree.to_string(), "1\n├── 2\n│   └── 4\n└── 3");
+/// ```
+pub struct TreeFormatter<'a, N> {
+    /// Gives the label of a node.
+    label: LabelFn<'a, N>,
+    /// The characters used to draw the branches.
+    charset: Charset,
+    /// The width of each level of indentation.
+    indent: usize,
+    /// The direction in which the tree is laid out.
+    layout: Layout,
+    /// Gives the style of a node's label, if labels are styled.
+    style: Option<StyleFn<'a, N>>,
+}
+
+impl<'a, N: TreeNode> TreeFormatter<'a, N> {
+    /// Creates a formatter with Unicode branches four columns wide, with `label` giving the
+    /// label of each node.
+    pub fn new(label: impl FnMut(&N) -> String + 'a) -> Self {
+        Self {
+            label: Box::new(label),
+            charset: Charset::default(),
+            indent: 4,
+            layout: Layout::default(),
+            style: None,
+        }
+    }
+
+    /// Sets the characters used to draw the branches.
+    pub fn charset(mut self, charset: Charset) -> Self {
+        self.charset = charset;
+        self
+    }
+
+    /// Sets the direction in which the tree is laid out.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::display::{Layout, TreeFormatter};
+    /// use tree_iter::tree;
+    /// use tree_iter::tree::Node;
+    ///
+    /// let tree = tree! { "root" => ["a" => ["x", "y"], "bb", "c" => ["z"]] };
+    /// let text = TreeFormatter::new(|node: &Node<&str>| node.value.to_string())
+    ///     .layout(Layout::Horizontal)
+    ///     .render(&tree);
+    /// assert_eq!(
+    ///     text,
+    ///     "root ─┬─ a  ─┬─ x\n      │      └─ y\n      ├─ bb\n      └─ c  ─── z"
+    /// );
+    /// ```
+    pub fn layout(mut self, layout: Layout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Sets the width of each level of indentation, including the branch and the space
+    /// before the label, in the vertical layout.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `indent` is less than 2.
+    pub fn indent(mut self, indent: usize) -> Self {
+        assert!(
+            indent >= 2,
+            "the indentation must be at least 2 columns wide"
+        );
+        self.indent = indent;
+        self
+    }
+
+    /// Sets a function giving the terminal style of each node's label, from the node and
+    /// its place in the tree.
+    ///
+    /// Only the labels are styled, so the branches and the alignment of the columns are
+    /// unchanged. Each line of a label with several lines is styled on its own.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::display::{Color, Style, TreeFormatter};
+    /// use tree_iter::tree;
+    /// use tree_iter::tree::Node;
+    ///
+    /// let tree = tree! { "crate" => ["ok", "failed"] };
+    /// let text = TreeFormatter::new(|node: &Node<&str>| node.value.to_string())
+    ///     .style(|node, context| match node.value {
+    ///         "failed" => Style::new().fg(Color::Red).bold(),
+    ///         _ if context.depth == 0 => Style::new().underline(),
+    ///         _ => Style::new(),
+    ///     })
+    ///     .render(&tree);
+    /// assert_eq!(
+    ///     text,
+    ///     "\x1b[4mcrate\x1b[0m\n├── ok\n└── \x1b[1;31mfailed\x1b[0m"
+    /// );
+    /// ```
+    pub fn style(mut self, style: impl FnMut(&N, NodeContext) -> Style + 'a) -> Self {
+        self.style = Some(Box::new(style));
+        self
+    }
+
+    /// Returns the style of a node's label.
+    fn style_of(&mut self, node: &N, context: NodeContext) -> Style {
+        self.style
+            .as_mut()
+            .map_or_else(Style::new, |style| style(node, context))
+    }
+
+    /// Renders the tree to a string.
+    pub fn render(&mut self, root: &N) -> String {
+        let mut out = String::new();
+        self.write(root, &mut out)
+            .expect("writing to a String cannot fail");
+        out
+    }
+
+    /// Writes the tree to `out`.
+    pub fn write(&mut self, root: &N, out: &mut impl Write) -> fmt::Result {
+        match self.layout {
+            Layout::Vertical => self.write_vertical(root, out),
+            Layout::Horizontal => self.write_horizontal(root, out),
+        }
+    }
+
+    /// Writes the tree to `out` in the vertical layout.
+    fn write_vertical(&mut self, root: &N, out: &mut impl Write) -> fmt::Result {
+        let (middle, last, vertical, horizontal) = self.charset.parts();
+        let line = horizontal.to_string().repeat(self.indent - 2);
+        let blank = " ".repeat(self.indent - 1);
+
+        // Each entry holds a node, the prefix of its first line, and the prefix of the
+        // lines below it, which is also the start of its children's prefixes
+        let mut stack = vec![(root, String::new(), String::new(), ROOT_CONTEXT)];
+        let mut first = true;
+        while let Some((node, head, body, context)) = stack.pop() {
+            if !first {
+                out.write_char('\n')?;
+            }
+            first = false;
+
+            let label = (self.label)(node);
+            let style = self.style_of(node, context);
+            let mut lines = label.split('\n');
+            out.write_str(&head)?;
+            style.write(lines.next().unwrap_or_default(), out)?;
+            let continued = if node.children().next().is_some() {
+                format!("{body}{vertical}{blank}")
+            } else {
+                format!("{body} {blank}")
+            };
+            for text in lines {
+                let prefix = if text.is_empty() {
+                    continued.trim_end()
+                } else {
+                    &continued
+                };
+                write!(out, "\n{prefix}")?;
+                style.write(text, out)?;
+            }
+
+            let count = node.children().count();
+            let mut children = (0..count).rev().zip(node.children().rev());
+            if let Some((index, child)) = children.next() {
+                stack.push((
+                    child,
+                    format!("{body}{last}{line} "),
+                    format!("{body} {blank}"),
+                    context.child(index, count),
+                ));
+            }
+            stack.extend(children.map(|(index, child)| {
+                (
+                    child,
+                    format!("{body}{middle}{line} "),
+                    format!("{body}{vertical}{blank}"),
+                    context.child(index, count),
+                )
+            }));
+        }
+        Ok(())
+    }
+
+    /// Writes the tree to `out` in the horizontal layout.
+    fn write_horizontal(&mut self, root: &N, out: &mut impl Write) -> fmt::Result {
+        let (middle, last, vertical, horizontal) = self.charset.parts();
+        let split = self.charset.split();
+
+        // Label the nodes first, to find the width of the column at each depth
+        let mut labels = Vec::new();
+        let mut widths: Vec<usize> = Vec::new();
+        for (depth, node) in root.traverse().with_depth().build() {
+            let label = (self.label)(node);
+            if depth == widths.len() {
+                widths.push(0);
+            }
+            widths[depth] = widths[depth].max(label.chars().count());
+            labels.push(label);
+        }
+
+        // Whether the line at each depth continues down to a later child
+        let mut open: Vec<bool> = Vec::new();
+        let mut stack = vec![(root, ROOT_CONTEXT)];
+        let mut labels = labels.into_iter();
+        while let Some((node, context)) = stack.pop() {
+            let depth = context.depth;
+            if depth > 0 {
+                open[depth - 1] = !context.is_last;
+            }
+            if context.index > 0 {
+                out.write_char('\n')?;
+                for (&width, &open) in widths.iter().zip(&open).take(depth - 1) {
+                    let line = if open { vertical } else { ' ' };
+                    write!(out, "{:width$}  {line}  ", "")?;
+                }
+                let branch = if context.is_last { last } else { middle };
+                write!(
+                    out,
+                    "{:width$}  {branch}{horizontal} ",
+                    "",
+                    width = widths[depth - 1]
+                )?;
+            }
+            let label = labels.next().unwrap();
+            self.style_of(node, context).write(&label, out)?;
+
+            let children: Vec<&N> = node.children().collect();
+            if !children.is_empty() {
+                let padding = widths[depth] - label.chars().count();
+                let junction = if children.len() > 1 {
+                    split
+                } else {
+                    horizontal
+                };
+                write!(out, "{:padding$} {horizontal}{junction}{horizontal} ", "")?;
+                if open.len() == depth {
+                    open.push(false);
+                }
+            }
+            let count = children.len();
+            stack.extend(
+                children
+                    .into_iter()
+                    .enumerate()
+                    .rev()
+                    .map(|(index, child)| (child, context.child(index, count))),
+            );
+        }
+        Ok(())
+    }
+}
+
+impl<N> fmt::Debug for TreeFormatter<'_, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TreeFormatter")
+            .field("charset", &self.charset)
+            .field("indent", &self.indent)
+            .field("layout", &self.layout)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: Display> Display for Node<T> {
+    /// Writes the tree with Unicode branches, as rendered by [`TreeFormatter`].
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        TreeFormatter::new(|node: &Node<T>| node.value.to_string()).write(self, f)
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Node<T> {
+    /// Writes the tree on one line as `value [child, child [grandchild]]`, or with `{:#?}`,
+    /// as an indented tree with Unicode branches, with the values written with `{:?}`.
+    ///
+    /// The tree is walked iteratively, so trees of any depth can be written.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::tree;
+    ///
+    /// let tree = tree! { "a" => ["b" => ["c"], "d"] };
+    /// assert_eq!(format!("{tree:?}"), r#""a" ["b" ["c"], "d"]"#);
+    /// assert_eq!(
+    ///     format!("{tree:#?}"),
+    ///     "\"a\"\n├── \"b\"\n│   └── \"c\"\n└── \"d\""
+    /// );
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            return TreeFormatter::new(|node: &Node<T>| format!("{:?}", node.value)).write(self, f);
+        }
+
+        /// A step of writing a tree on one line.
+        enum Step<'a, T> {
+            /// Write a node and its children.
+            Node(&'a Node<T>),
+            /// Write punctuation.
+            Text(&'static str),
+        }
+
+        let mut stack = vec![Step::Node(self)];
+        while let Some(step) = stack.pop() {
+            match step {
+                Step::Node(node) => {
+                    fmt::Debug::fmt(&node.value, f)?;
+                    if !node.children.is_empty() {
+                        f.write_str(" [")?;
+                        stack.push(Step::Text("]"));
+                        for (i, child) in node.children.iter().enumerate().rev() {
+                            stack.push(Step::Node(child));
+                            if i > 0 {
+                                stack.push(Step::Text(", "));
+                            }
+                        }
+                    }
+                }
+                Step::Text(text) => f.write_str(text)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multiline_labels() {
+        let tree = crate::tree! { "a\nb" => ["c\n\nd" => ["e"], "f\ng"] };
+        assert_eq!(
+            tree.to_string(),
+            "a\n│   b\n├── c\n│   │\n│   │   d\n│   └── e\n└── f\n        g"
+        );
+    }
+
+    #[test]
+    fn test_horizontal_layout() {
+        let tree = crate::tree! { 1 => [2 => [3 => [4, 5], 6], 7] };
+        let text = TreeFormatter::new(|node: &Node<i32>| node.value.to_string())
+            .charset(Charset::Ascii)
+            .layout(Layout::Horizontal)
+            .render(&tree);
+        assert_eq!(
+            text,
+            "1 -+- 2 -+- 3 -+- 4\n   |     |     `- 5\n   |     `- 6\n   `- 7"
+        );
+    }
+
+    #[test]
+    fn test_debug() {
+        let tree = crate::tree! { 1 => [2 => [3, 4], 5 => [6]] };
+        assert_eq!(format!("{tree:?}"), "1 [2 [3, 4], 5 [6]]");
+        assert_eq!(format!("{:?}", Node::new(Some(1))), "Some(1)");
+
+        let mut deep = Node::new(0);
+        for i in 1..100_000 {
+            deep = Node::with_children(i, [deep]);
+        }
+        let text = format!("{deep:?}");
+        assert!(text.starts_with("99999 [99998 [") && text.contains("1 [0]]]"));
+        assert_eq!(text.matches(']').count(), 99_999);
+    }
+
+    #[test]
+    fn test_styled_layouts() {
+        let tree = crate::tree! { 1 => [2 => [3], 4] };
+        let mut contexts = Vec::new();
+        let text = TreeFormatter::new(|node: &Node<i32>| format!("{}\n.", node.value))
+            .charset(Charset::Ascii)
+            .style(|node, context| {
+                contexts.push((node.value, context.depth, context.index, context.is_last));
+                Style::new().fg(Color::Fixed(node.value as u8))
+            })
+            .render(&tree);
+        assert_eq!(
+            text,
+            "\x1b[38;5;1m1\x1b[0m\n|   \x1b[38;5;1m.\x1b[0m\n\
+             |-- \x1b[38;5;2m2\x1b[0m\n|   |   \x1b[38;5;2m.\x1b[0m\n\
+             |   `-- \x1b[38;5;3m3\x1b[0m\n|           \x1b[38;5;3m.\x1b[0m\n\
+             `-- \x1b[38;5;4m4\x1b[0m\n        \x1b[38;5;4m.\x1b[0m"
+        );
+        assert_eq!(
+            contexts,
+            vec![
+                (1, 0, 0, true),
+                (2, 1, 0, false),
+                (3, 2, 0, true),
+                (4, 1, 1, true)
+            ]
+        );
+
+        let text = TreeFormatter::new(|node: &Node<i32>| "x".repeat(node.value as usize))
+            .charset(Charset::Ascii)
+            .layout(Layout::Horizontal)
+            .style(|_, context| {
+                if context.depth == 1 {
+                    Style::new().bg(Color::Rgb(1, 2, 3)).italic()
+                } else {
+                    Style::new()
+                }
+            })
+            .render(&tree);
+        assert_eq!(
+            text,
+            "x -+- \x1b[3;48;2;1;2;3mxx\x1b[0m   --- xxx\n   `- \x1b[3;48;2;1;2;3mxxxx\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn test_deep_tree() {
+        let mut deep = Node::new(0);
+        for i in 1..10_000 {
+            deep = Node::with_children(i, [deep]);
+        }
+        let text = TreeFormatter::new(|node: &Node<i32>| node.value.to_string())
+            .charset(Charset::Ascii)
+            .indent(2)
+            .render(&deep);
+        assert_eq!(text.lines().count(), 10_000);
+        assert!(text.ends_with(&format!("{}` 0", " ".repeat(2 * 9_998))));
+    }
+}
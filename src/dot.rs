@@ -0,0 +1,263 @@
+use std::fmt::{self, Write};
+
+use crate::iter::TreeNode;
+
+/// Attributes of a node or edge, as `(name, value)` pairs.
+type Attributes = Vec<(String, String)>;
+/// Gives the attributes of a node.
+type NodeAttributesFn<'a, N> = Box<dyn FnMut(&N) -> Attributes + 'a>;
+/// Gives the attributes of the edge from a parent to a child.
+type EdgeAttributesFn<'a, N> = Box<dyn FnMut(&N, &N) -> Attributes + 'a>;
+
+/// Renders a tree as a Graphviz DOT digraph, with `label` giving the label of each node.
+///
+/// See [`DotWriter`] for more options.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::dot::to_dot;
+/// use tree_iter::tree;
+///
+/// let tree = tree! { 1 => [2, 3] };
+/// let dot = to_dot(&tree, |node| node.value.to_string());
+/// assert_eq!(
+///     dot,
+///     "digraph tree {\n    n0 [label=\"1\"];\n    n1 [label=\"2\"];\n    n0 -> n1;\n    n2 [label=\"3\"];\n    n0 -> n2;\n}\n"
+/// );
+/// ```
+pub fn to_dot<N: TreeNode>(root: &N, label: impl FnMut(&N) -> String) -> String {
+    DotWriter::new(label).render(root)
+}
+
+/// Configurable Graphviz DOT output for any [`TreeNode`].
+///
+/// Nodes are named `n0`, `n1`, ... in depth-first order, and each node is followed by the
+/// edge from its parent. All attribute values are quoted and escaped, and so are the graph
+/// name and attribute names that are not plain identifiers or that are DOT keywords, such
+/// as `node`. The tree is walked iteratively, so trees of any depth can be rendered.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::dot::DotWriter;
+/// use tree_iter::tree;
+///
+/// let tree = tree! { "root" => ["ok", "failed"] };
+/// let dot = DotWriter::new(|node: &tree_iter::tree::Node<&str>| node.value.to_string())
+///     .name("run")
+///     .graph_attribute("rankdir", "LR")
+///     .node_attributes(|node| {
+///         if node.value == "failed" {
+///             vec![("color".to_string(), "red".to_string())]
+///         } else {
+///             vec![]
+///         }
+///     })
+///     .edge_attributes(|_, child| vec![("tooltip".to_string(), format!("to {}", child.value))])
+///     .render(&tree);
+/// assert!(dot.starts_with("digraph run {\n    rankdir=\"LR\";\n"));
+/// assert!(dot.contains("n2 [label=\"failed\", color=\"red\"];"));
+/// assert!(dot.contains("n0 -> n2 [tooltip=\"to failed\"];"));
+/// ```
+pub struct DotWriter<'a, N> {
+    /// The name of the digraph.
+    name: String,
+    /// Attributes of the whole graph.
+    graph_attributes: Attributes,
+    /// Gives the label of a node.
+    label: Box<dyn FnMut(&N) -> String + 'a>,
+    /// Gives extra attributes of a node.
+    node_attributes: Option<NodeAttributesFn<'a, N>>,
+    /// Gives the attributes of the edge from a parent to a child.
+    edge_attributes: Option<EdgeAttributesFn<'a, N>>,
+}
+
+impl<'a, N: TreeNode> DotWriter<'a, N> {
+    /// Creates a writer for a digraph named `tree`, with `label` giving the label of each node.
+    pub fn new(label: impl FnMut(&N) -> String + 'a) -> Self {
+        Self {
+            name: "tree".to_string(),
+            graph_attributes: Vec::new(),
+            label: Box::new(label),
+            node_attributes: None,
+            edge_attributes: None,
+        }
+    }
+
+    /// Sets the name of the digraph.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Adds an attribute of the whole graph, such as `rankdir`.
+    pub fn graph_attribute(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.graph_attributes.push((name.into(), value.into()));
+        self
+    }
+
+    /// Sets a function giving extra attributes of each node, written after its label.
+    pub fn node_attributes(mut self, attributes: impl FnMut(&N) -> Attributes + 'a) -> Self {
+        self.node_attributes = Some(Box::new(attributes));
+        self
+    }
+
+    /// Sets a function giving the attributes of each edge, from the parent and the child.
+    pub fn edge_attributes(mut self, attributes: impl FnMut(&N, &N) -> Attributes + 'a) -> Self {
+        self.edge_attributes = Some(Box::new(attributes));
+        self
+    }
+
+    /// Renders the tree to a string.
+    pub fn render(&mut self, root: &N) -> String {
+        let mut out = String::new();
+        self.write(root, &mut out)
+            .expect("writing to a String cannot fail");
+        out
+    }
+
+    /// Writes the tree to `out`.
+    pub fn write(&mut self, root: &N, out: &mut impl Write) -> fmt::Result {
+        writeln!(out, "digraph {} {{", quote_id(&self.name))?;
+        for (name, value) in &self.graph_attributes {
+            writeln!(out, "    {}=\"{}\";", quote_id(name), escape(value))?;
+        }
+
+        let mut next_id = 0;
+        let mut stack = vec![(root, None)];
+        while let Some((node, parent)) = stack.pop() {
+            let id = next_id;
+            next_id += 1;
+
+            write!(out, "    n{id} [label=\"{}\"", escape(&(self.label)(node)))?;
+            if let Some(attributes) = &mut self.node_attributes {
+                write_attributes(out, &attributes(node), true)?;
+            }
+            writeln!(out, "];")?;
+
+            if let Some((parent, parent_id)) = parent {
+                write!(out, "    n{parent_id} -> n{id}")?;
+                if let Some(attributes) = &mut self.edge_attributes {
+                    let attributes = attributes(parent, node);
+                    if !attributes.is_empty() {
+                        write!(out, " [")?;
+                        write_attributes(out, &attributes, false)?;
+                        write!(out, "]")?;
+                    }
+                }
+                writeln!(out, ";")?;
+            }
+
+            stack.extend(node.children().rev().map(|child| (child, Some((node, id)))));
+        }
+        writeln!(out, "}}")
+    }
+}
+
+impl<N> fmt::Debug for DotWriter<'_, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DotWriter")
+            .field("name", &self.name)
+            .field("graph_attributes", &self.graph_attributes)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Writes `name="value"` pairs separated by commas, with a leading comma if `continued`.
+/// Names are quoted as needed.
+fn write_attributes(
+    out: &mut impl Write,
+    attributes: &[(String, String)],
+    continued: bool,
+) -> fmt::Result {
+    for (i, (name, value)) in attributes.iter().enumerate() {
+        if continued || i > 0 {
+            write!(out, ", ")?;
+        }
+        write!(out, "{}=\"{}\"", quote_id(name), escape(value))?;
+    }
+    Ok(())
+}
+
+/// Escapes a string for use inside a quoted DOT string.
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// The keywords of DOT, which are matched regardless of case.
+const KEYWORDS: [&str; 6] = ["graph", "digraph", "node", "edge", "subgraph", "strict"];
+
+/// Returns `name` as a DOT identifier, quoting it unless it is a plain identifier other
+/// than a keyword.
+fn quote_id(name: &str) -> String {
+    let plain = name.chars().next().is_some_and(|c| !c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !KEYWORDS
+            .iter()
+            .any(|keyword| keyword.eq_ignore_ascii_case(name));
+    if plain {
+        name.to_string()
+    } else {
+        format!("\"{}\"", escape(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escaping() {
+        let tree = crate::tree! { "say \"hi\"\\" => ["two\nlines"] };
+        let dot = DotWriter::new(|node: &crate::tree::Node<&str>| node.value.to_string())
+            .name("my graph")
+            .render(&tree);
+        assert_eq!(
+            dot,
+            "digraph \"my graph\" {\n    n0 [label=\"say \\\"hi\\\"\\\\\"];\n    n1 [label=\"two\\nlines\"];\n    n0 -> n1;\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_attribute_names_are_quoted() {
+        let tree = crate::tree! { 1 => [2] };
+        let dot = DotWriter::new(|node: &crate::tree::Node<i32>| node.value.to_string())
+            .graph_attribute("a=b", "1")
+            .node_attributes(|_| vec![("x\"];evil".to_string(), "v".to_string())])
+            .edge_attributes(|_, _| vec![("weight".to_string(), "2".to_string())])
+            .render(&tree);
+        assert_eq!(
+            dot,
+            "digraph tree {\n    \"a=b\"=\"1\";\n    n0 [label=\"1\", \"x\\\"];evil\"=\"v\"];\n    \
+             n1 [label=\"2\", \"x\\\"];evil\"=\"v\"];\n    n0 -> n1 [weight=\"2\"];\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_keywords_are_quoted() {
+        for keyword in ["graph", "Node", "EDGE", "subGraph", "strict", "digraph"] {
+            assert_eq!(quote_id(keyword), format!("\"{keyword}\""));
+        }
+        assert_eq!(quote_id("nodes"), "nodes");
+
+        let tree = crate::tree! { 1 };
+        let dot = DotWriter::new(|node: &crate::tree::Node<i32>| node.value.to_string())
+            .name("graph")
+            .node_attributes(|_| vec![("node".to_string(), "x".to_string())])
+            .render(&tree);
+        assert_eq!(
+            dot,
+            "digraph \"graph\" {\n    n0 [label=\"1\", \"node\"=\"x\"];\n}\n"
+        );
+    }
+}
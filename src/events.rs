@@ -0,0 +1,286 @@
+use std::{error::Error, fmt};
+
+use crate::{iter::TreeNode, tree::Node};
+
+/// An event in the streaming form of a tree.
+///
+/// A tree is streamed as an `Open` event for each node, in depth-first order, and a
+/// `Close` event after the events of its subtree, like the start and end tags of XML.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Event<T> {
+    /// Starts a node with the given value, as a child of the innermost open node.
+    Open(T),
+    /// Ends the innermost open node.
+    Close,
+}
+
+/// Builds a [`Node`] tree from a stream of [`Event`]s.
+///
+/// The builder keeps only the nodes that are still open, so events can be fed to it as
+/// they are produced by a parser, and trees of any depth can be built.
+///
+/// # Type Parameters
+///
+/// * `T` - The type of value stored in each node.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::events::{Event, TreeBuilder};
+/// use tree_iter::tree;
+///
+/// let mut builder = TreeBuilder::new();
+/// builder.open("html");
+/// builder.open("head");
+/// builder.close().unwrap();
+/// builder.push(Event::Open("body")).unwrap();
+/// builder.push(Event::Close).unwrap();
+/// builder.close().unwrap();
+/// assert_eq!(builder.finish(), Ok(tree! { "html" => ["head", "body"] }));
+/// ```
+#[derive(Debug, Clone)]
+pub struct TreeBuilder<T> {
+    /// The nodes that are open, from the root down, with the children finished so far.
+    open: Vec<(T, Vec<Node<T>>)>,
+    /// The root, once it is closed.
+    root: Option<Node<T>>,
+}
+
+impl<T> TreeBuilder<T> {
+    /// Creates a builder that has not received any events.
+    pub fn new() -> Self {
+        Self {
+            open: Vec::new(),
+            root: None,
+        }
+    }
+
+    /// Returns the number of nodes that are open.
+    pub fn depth(&self) -> usize {
+        self.open.len()
+    }
+
+    /// Handles an event.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, and ignores the event, if it would start a second root or close
+    /// a node when none is open.
+    pub fn push(&mut self, event: Event<T>) -> Result<(), EventError> {
+        match event {
+            Event::Open(value) => {
+                if self.root.is_some() {
+                    return Err(EventError::MultipleRoots);
+                }
+                self.open(value);
+                Ok(())
+            }
+            Event::Close => self.close(),
+        }
+    }
+
+    /// Starts a node with the given value, as a child of the innermost open node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the root has already been closed; use [`TreeBuilder::push`] to handle
+    /// this as an error.
+    pub fn open(&mut self, value: T) {
+        assert!(self.root.is_none(), "the root has already been closed");
+        self.open.push((value, Vec::new()));
+    }
+
+    /// Ends the innermost open node.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no node is open.
+    pub fn close(&mut self) -> Result<(), EventError> {
+        let node = Node::from(self.open.pop().ok_or(EventError::UnmatchedClose)?);
+        match self.open.last_mut() {
+            Some((_, children)) => children.push(node),
+            None => self.root = Some(node),
+        }
+        Ok(())
+    }
+
+    /// Returns the finished tree.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no node was opened, or if some nodes are still open.
+    pub fn finish(self) -> Result<Node<T>, EventError> {
+        if !self.open.is_empty() {
+            return Err(EventError::Unclosed(self.open.len()));
+        }
+        self.root.ok_or(EventError::Empty)
+    }
+}
+
+impl<T> Default for TreeBuilder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Builds a tree from a whole stream of events.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::events::{Event, EventError, build};
+/// use tree_iter::tree;
+///
+/// let events = [Event::Open(1), Event::Open(2), Event::Close, Event::Close];
+/// assert_eq!(build(events), Ok(tree! { 1 => [2] }));
+/// assert_eq!(build([Event::Open(1)]), Err(EventError::Unclosed(1)));
+/// ```
+pub fn build<T>(events: impl IntoIterator<Item = Event<T>>) -> Result<Node<T>, EventError> {
+    let mut builder = TreeBuilder::new();
+    for event in events {
+        builder.push(event)?;
+    }
+    builder.finish()
+}
+
+/// The error returned when a stream of [`Event`]s does not describe a single tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventError {
+    /// The stream has no events.
+    Empty,
+    /// A `Close` event has no matching `Open`.
+    UnmatchedClose,
+    /// An `Open` event follows the `Close` of the root.
+    MultipleRoots,
+    /// The stream ended with the given number of nodes still open.
+    Unclosed(usize),
+}
+
+impl fmt::Display for EventError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "no nodes were opened"),
+            Self::UnmatchedClose => write!(f, "close event without an open node"),
+            Self::MultipleRoots => write!(f, "open event after the root was closed"),
+            Self::Unclosed(count) => write!(f, "{count} nodes were left open"),
+        }
+    }
+}
+
+impl Error for EventError {}
+
+/// A step of emitting the events of a tree.
+#[derive(Debug)]
+enum Step<'a, N> {
+    /// Open a node.
+    Open(&'a N),
+    /// Close the innermost open node.
+    Close,
+}
+
+/// An iterator over the [`Event`]s of any [`TreeNode`], created by [`events`].
+///
+/// # Type Parameters
+///
+/// * `'a` - The lifetime of the nodes being traversed.
+/// * `N` - The type of node.
+/// * `F` - Gives the value of the `Open` event of a node.
+pub struct Events<'a, N, F> {
+    /// The steps still to take, the next one last.
+    stack: Vec<Step<'a, N>>,
+    /// Gives the value of the `Open` event of a node.
+    value: F,
+}
+
+/// Streams a tree as [`Event`]s, with `value` giving the value of each node.
+///
+/// The tree is walked iteratively, so trees of any depth can be streamed. Together with
+/// [`build`], this copies any tree into a [`Node`] tree, or into any other format that can
+/// be built from events.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::events::{Event, build, events};
+/// use tree_iter::tree;
+///
+/// let tree = tree! { 1 => [2, 3] };
+/// let stream: Vec<Event<i32>> = events(&tree, |node| node.value).collect();
+/// assert_eq!(
+///     stream,
+///     vec![Event::Open(1), Event::Open(2), Event::Close, Event::Open(3), Event::Close, Event::Close]
+/// );
+/// assert_eq!(build(stream), Ok(tree));
+/// ```
+pub fn events<N: TreeNode, V, F: FnMut(&N) -> V>(root: &N, value: F) -> Events<'_, N, F> {
+    Events {
+        stack: vec![Step::Open(root)],
+        value,
+    }
+}
+
+impl<'a, N: TreeNode, V, F: FnMut(&N) -> V> Iterator for Events<'a, N, F> {
+    type Item = Event<V>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.stack.pop()? {
+            Step::Open(node) => {
+                self.stack.push(Step::Close);
+                self.stack.extend(node.children().rev().map(Step::Open));
+                Some(Event::Open((self.value)(node)))
+            }
+            Step::Close => Some(Event::Close),
+        }
+    }
+}
+
+impl<N: fmt::Debug, F> fmt::Debug for Events<'_, N, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Events")
+            .field("stack", &self.stack)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_and_errors() {
+        let mut deep = Node::new(0);
+        for i in 1..100_000 {
+            deep = Node::with_children(i, [deep]);
+        }
+        let copy = build(events(&deep, |node| node.value)).unwrap();
+        assert_eq!(copy.height(), 99_999);
+
+        assert_eq!(build::<i32>([]), Err(EventError::Empty));
+        assert_eq!(
+            build::<i32>([Event::Close]),
+            Err(EventError::UnmatchedClose)
+        );
+        assert_eq!(
+            build([Event::Open(1), Event::Close, Event::Open(2)]),
+            Err(EventError::MultipleRoots)
+        );
+    }
+
+    #[test]
+    fn test_round_trip_shapes() {
+        for size in 1..200u32 {
+            // Node `n` has `(n + 2) % 4` children, cut off after `size` nodes in total
+            let mut next = 1;
+            let tree = Node::unfold::<crate::traversal_order::BreadthFirst, _>(0u32, |n| {
+                let count = ((n + 2) % 4).min(size - next);
+                let children: Vec<u32> = (next..next + count).collect();
+                next += count;
+                (n, children)
+            });
+            assert_eq!(tree.count(), size as usize);
+            let stream: Vec<Event<u32>> = events(&tree, |node| node.value).collect();
+            assert_eq!(stream.len(), 2 * size as usize);
+            assert_eq!(Node::from_events(stream), Ok(tree));
+        }
+    }
+}
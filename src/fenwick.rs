@@ -0,0 +1,173 @@
+use std::ops::{Add, Sub};
+
+use crate::{flat::Flat, iter::TreeNode, preorder::PreorderIds};
+
+/// Weights of the nodes of a tree, with updates and subtree sums in logarithmic time.
+///
+/// Numbered in depth-first order, the subtree of every node is an interval of node ids,
+/// starting at the node itself. The weights are kept in that order in a Fenwick tree, which
+/// updates a weight and sums any interval in `O(log n)` time, so the sum of a subtree never
+/// requires walking it. The structure of the tree is captured when the index is built;
+/// only the weights change afterwards.
+///
+/// Nodes are identified by their [`PreorderIds`], returned by [`SubtreeSums::ids`].
+///
+/// # Type Parameters
+///
+/// * `T` - The type of the weights, with `T::default()` as zero.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::fenwick::SubtreeSums;
+/// use tree_iter::tree;
+///
+/// let tree = tree! { 1 => [2 => [4, 5], 3] };
+/// let mut sums = SubtreeSums::new(&tree, |node| node.value);
+/// assert_eq!(sums.total(), 15);
+///
+/// let id = sums.ids().id(&[0]).unwrap();
+/// assert_eq!(sums.ids().range(id), 1..4);
+/// assert_eq!(sums.subtree_sum(id), 11);
+///
+/// sums.add(sums.ids().id(&[0, 1]).unwrap(), 10);
+/// assert_eq!(sums.subtree_sum(id), 21);
+/// sums.set(id, 0);
+/// assert_eq!(sums.subtree_sum(id), 19);
+/// assert_eq!(sums.total(), 23);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SubtreeSums<T> {
+    /// The weight of each node.
+    values: Vec<T>,
+    /// The Fenwick tree, where entry `i` holds the sum of the `i & i.wrapping_neg()` weights
+    /// ending at node `i - 1`.
+    sums: Vec<T>,
+    /// The numbering of the nodes.
+    ids: PreorderIds,
+}
+
+impl<T> SubtreeSums<T>
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + Default,
+{
+    /// Builds the index for the tree rooted at `root`, with `weight` giving the initial
+    /// weight of each node, in `O(n)` time.
+    pub fn new<N: TreeNode>(root: &N, mut weight: impl FnMut(&N) -> T) -> Self {
+        let flat = Flat::new(root);
+        let values: Vec<T> = flat.nodes.iter().map(|&node| weight(node)).collect();
+        let mut sums = vec![T::default(); values.len() + 1];
+        sums[1..].copy_from_slice(&values);
+        for i in 1..sums.len() {
+            let next = i + (i & i.wrapping_neg());
+            if next < sums.len() {
+                sums[next] = sums[next] + sums[i];
+            }
+        }
+        Self {
+            values,
+            sums,
+            ids: PreorderIds::from_flat(&flat),
+        }
+    }
+
+    /// Returns the numbering of the nodes of the tree.
+    pub fn ids(&self) -> &PreorderIds {
+        &self.ids
+    }
+
+    /// Returns the weight of the node with the given id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is not less than [`PreorderIds::len`].
+    pub fn get(&self, id: usize) -> T {
+        self.values[id]
+    }
+
+    /// Sets the weight of the node with the given id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is not less than [`PreorderIds::len`].
+    pub fn set(&mut self, id: usize, value: T) {
+        let delta = value - self.values[id];
+        self.add(id, delta);
+    }
+
+    /// Adds `delta` to the weight of the node with the given id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is not less than [`PreorderIds::len`].
+    pub fn add(&mut self, id: usize, delta: T) {
+        self.values[id] = self.values[id] + delta;
+        let mut i = id + 1;
+        while i < self.sums.len() {
+            self.sums[i] = self.sums[i] + delta;
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    /// Returns the sum of the weights of the subtree of the node with the given id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is not less than [`PreorderIds::len`].
+    pub fn subtree_sum(&self, id: usize) -> T {
+        let range = self.ids.range(id);
+        self.prefix_sum(range.end) - self.prefix_sum(range.start)
+    }
+
+    /// Returns the sum of the weights of all nodes.
+    pub fn total(&self) -> T {
+        self.prefix_sum(self.values.len())
+    }
+
+    /// Returns the sum of the weights of the first `count` nodes.
+    fn prefix_sum(&self, count: usize) -> T {
+        let mut sum = T::default();
+        let mut i = count;
+        while i > 0 {
+            sum = sum + self.sums[i];
+            i -= i & i.wrapping_neg();
+        }
+        sum
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{iter_mut::TreeNodeMut, traversal_order::DepthFirst, tree::Node};
+
+    #[test]
+    fn test_matches_walking() {
+        let mut tree = Node::unfold::<DepthFirst, _>(1i64, |n| {
+            let children = if n < 100 {
+                vec![2 * n, 2 * n + 1, 3 * n]
+            } else {
+                vec![]
+            };
+            (n, children)
+        });
+        let mut sums = SubtreeSums::new(&tree, |node| node.value);
+        let mut iter = tree.iter_mut::<DepthFirst>();
+        let mut id = 0;
+        while let Some(mut node) = iter.next() {
+            if id % 3 == 0 {
+                node.value = -(id as i64);
+                sums.set(id, node.value);
+            }
+            id += 1;
+        }
+
+        let ids = sums.ids();
+        for id in 0..ids.len() {
+            let subtree = tree.get(&ids.path(id)).unwrap();
+            assert_eq!(sums.subtree_sum(id), subtree.sum());
+            assert_eq!(ids.range(id).len(), subtree.count());
+        }
+        assert_eq!(sums.total(), tree.sum());
+    }
+}
@@ -0,0 +1,72 @@
+use crate::iter::TreeNode;
+
+/// A tree flattened into pre-order arrays.
+///
+/// Nodes are identified by their pre-order index, so a node's subtree occupies the
+/// contiguous range `id..subtree_end[id]` and every parent comes before its children.
+/// This is the shared representation behind the algorithms that need random access
+/// to parents and subtrees.
+#[derive(Debug)]
+pub(crate) struct Flat<'a, N> {
+    /// The nodes in pre-order.
+    pub(crate) nodes: Vec<&'a N>,
+    /// The parent of each node, or `None` for the root.
+    pub(crate) parent: Vec<Option<usize>>,
+    /// The depth of each node, with the root at depth 0.
+    pub(crate) depth: Vec<usize>,
+    /// The position of each node among its siblings.
+    pub(crate) sibling_index: Vec<usize>,
+    /// The end of each node's subtree range (exclusive).
+    pub(crate) subtree_end: Vec<usize>,
+}
+
+impl<'a, N: TreeNode> Flat<'a, N> {
+    /// Flattens the tree rooted at `root`.
+    pub(crate) fn new(root: &'a N) -> Self {
+        let mut flat = Self {
+            nodes: Vec::new(),
+            parent: Vec::new(),
+            depth: Vec::new(),
+            sibling_index: Vec::new(),
+            subtree_end: Vec::new(),
+        };
+        let mut stack = vec![(root, None, 0, 0)];
+        while let Some((node, parent, depth, sibling_index)) = stack.pop() {
+            let id = flat.nodes.len();
+            flat.nodes.push(node);
+            flat.parent.push(parent);
+            flat.depth.push(depth);
+            flat.sibling_index.push(sibling_index);
+            let children: Vec<_> = node.children().enumerate().collect();
+            for (i, child) in children.into_iter().rev() {
+                stack.push((child, Some(id), depth + 1, i));
+            }
+        }
+
+        let mut size = vec![1; flat.nodes.len()];
+        for id in (1..flat.nodes.len()).rev() {
+            if let Some(parent) = flat.parent[id] {
+                size[parent] += size[id];
+            }
+        }
+        flat.subtree_end = size.iter().enumerate().map(|(id, s)| id + s).collect();
+        flat
+    }
+}
+
+impl<N> Flat<'_, N> {
+    /// Returns the number of nodes.
+    pub(crate) fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns the child-index path from the root to `id`.
+    pub(crate) fn path(&self, id: usize) -> Vec<usize> {
+        let mut path: Vec<usize> = std::iter::successors(Some(id), |&node| self.parent[node])
+            .take_while(|&node| self.parent[node].is_some())
+            .map(|node| self.sibling_index[node])
+            .collect();
+        path.reverse();
+        path
+    }
+}
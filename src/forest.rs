@@ -0,0 +1,197 @@
+use std::{num::NonZeroUsize, slice, thread};
+
+use crate::{iter::TreeIter, iter_mut::TreeIterMut, traversal_order::TraversalOrder, tree::Node};
+
+/// An ordered collection of trees.
+///
+/// The trees of a forest are separate values, so their subtrees never overlap. This makes
+/// the forest the safe entry point for mutating trees in chunks: every root subtree can be
+/// handed out mutably at the same time, in sequence or to different threads.
+///
+/// # Type Parameters
+///
+/// * `T` - The type of value stored in each node.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::forest::Forest;
+/// use tree_iter::prelude::*;
+/// use tree_iter::tree;
+///
+/// let mut forest: Forest<i32> = [tree! { 1 => [2] }, tree! { 3 }].into_iter().collect();
+/// for tree in forest.iter_mut_subtrees() {
+///     tree.value *= 10;
+/// }
+///
+/// let values: Vec<i32> = forest.iter::<DepthFirst>().map(|node| node.value).collect();
+/// assert_eq!(values, vec![10, 2, 30]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default, Hash)]
+pub struct Forest<T> {
+    /// The root nodes of the trees, in order.
+    pub roots: Vec<Node<T>>,
+}
+
+impl<T> Forest<T> {
+    /// Creates an empty forest.
+    pub fn new() -> Self {
+        Self { roots: Vec::new() }
+    }
+
+    /// Returns the number of trees in the forest.
+    pub fn len(&self) -> usize {
+        self.roots.len()
+    }
+
+    /// Returns `true` if the forest has no trees.
+    pub fn is_empty(&self) -> bool {
+        self.roots.is_empty()
+    }
+
+    /// Appends a tree to the forest.
+    pub fn push(&mut self, root: Node<T>) {
+        self.roots.push(root);
+    }
+
+    /// Creates an iterator that traverses all trees in the forest, one after another for
+    /// depth-first order and level by level across all trees for breadth-first order.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `O` - The traversal order strategy to use (e.g., `DepthFirst` or `BreadthFirst`).
+    pub fn iter<O: TraversalOrder>(&self) -> TreeIter<'_, Node<T>, O> {
+        TreeIter::new(&self.roots)
+    }
+
+    /// Creates a mutable iterator that traverses all trees in the forest.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `O` - The traversal order strategy to use (e.g., `DepthFirst` or `BreadthFirst`).
+    pub fn iter_mut<O: TraversalOrder>(&mut self) -> TreeIterMut<'_, Node<T>, O> {
+        TreeIterMut::new(&mut self.roots)
+    }
+
+    /// Returns an iterator over mutable references to the root of each tree.
+    ///
+    /// Each reference gives access to a whole tree, and no two of them share a node, so
+    /// all of them can be held at once.
+    pub fn iter_mut_subtrees(&mut self) -> slice::IterMut<'_, Node<T>> {
+        self.roots.iter_mut()
+    }
+
+    /// Calls `f` on every tree of the forest, spreading the trees over scoped threads.
+    ///
+    /// The trees are split into contiguous chunks, one per available core, and each chunk
+    /// is processed on its own thread. Since the trees are disjoint, no synchronization is
+    /// needed for the nodes themselves. The call returns when all trees are processed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `f` panics on any of the threads.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::forest::Forest;
+    /// use tree_iter::tree::Node;
+    ///
+    /// let mut forest: Forest<u64> = (0..100).map(Node::new).collect();
+    /// forest.par_for_each_subtree(|tree| {
+    ///     tree.push_child(Node::new(tree.value * tree.value));
+    /// });
+    /// assert_eq!(forest.roots[9].children[0].value, 81);
+    /// ```
+    pub fn par_for_each_subtree(&mut self, f: impl Fn(&mut Node<T>) + Sync)
+    where
+        T: Send,
+    {
+        let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        let chunk_size = self.roots.len().div_ceil(threads).max(1);
+        let f = &f;
+        thread::scope(|scope| {
+            for chunk in self.roots.chunks_mut(chunk_size) {
+                scope.spawn(move || chunk.iter_mut().for_each(f));
+            }
+        });
+    }
+}
+
+impl<T> From<Vec<Node<T>>> for Forest<T> {
+    fn from(roots: Vec<Node<T>>) -> Self {
+        Self { roots }
+    }
+}
+
+impl<T> FromIterator<Node<T>> for Forest<T> {
+    fn from_iter<I: IntoIterator<Item = Node<T>>>(roots: I) -> Self {
+        Self {
+            roots: roots.into_iter().collect(),
+        }
+    }
+}
+
+impl<T> Extend<Node<T>> for Forest<T> {
+    fn extend<I: IntoIterator<Item = Node<T>>>(&mut self, roots: I) {
+        self.roots.extend(roots);
+    }
+}
+
+impl<T> IntoIterator for Forest<T> {
+    type Item = Node<T>;
+    type IntoIter = std::vec::IntoIter<Node<T>>;
+
+    /// Returns the trees of the forest.
+    fn into_iter(self) -> Self::IntoIter {
+        self.roots.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_traversal_across_trees() {
+        let mut forest: Forest<i32> = Forest::new();
+        assert!(forest.is_empty());
+        forest.push(crate::tree! { 1 => [2 => [3]] });
+        forest.extend([crate::tree! { 4 => [5] }]);
+        assert_eq!(forest.len(), 2);
+
+        let values: Vec<i32> = forest.iter::<DepthFirst>().map(|node| node.value).collect();
+        assert_eq!(values, vec![1, 2, 3, 4, 5]);
+        let values: Vec<i32> = forest
+            .iter::<BreadthFirst>()
+            .map(|node| node.value)
+            .collect();
+        assert_eq!(values, vec![1, 4, 2, 5, 3]);
+
+        let mut iter = forest.iter_mut::<BreadthFirst>();
+        while let Some(mut node) = iter.next() {
+            node.value = -node.value;
+        }
+        let roots: Vec<i32> = forest.into_iter().map(|root| root.value).collect();
+        assert_eq!(roots, vec![-1, -4]);
+    }
+
+    #[test]
+    fn test_par_for_each_subtree() {
+        // Many more trees than threads, each processed exactly once
+        let mut forest: Forest<usize> = (0..1000).map(Node::new).collect();
+        forest.par_for_each_subtree(|tree| tree.value += 1);
+        assert!(
+            forest
+                .roots
+                .iter()
+                .enumerate()
+                .all(|(i, tree)| tree.value == i + 1)
+        );
+
+        let mut empty: Forest<usize> = Vec::new().into();
+        empty.par_for_each_subtree(|_| unreachable!());
+        assert!(empty.is_empty());
+    }
+}
@@ -0,0 +1,305 @@
+use crate::iter::TreeNode;
+
+/// The result of searching a game tree.
+///
+/// # Type Parameters
+///
+/// * `S` - The type of scores.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Evaluation<S> {
+    /// The score of the root, from the point of view of the player to move there.
+    pub score: S,
+    /// The principal variation: the child-index path of the best play from the root, as
+    /// deep as the search went. Empty if the root was evaluated directly.
+    pub line: Vec<usize>,
+    /// The number of positions passed to the evaluation function.
+    pub evaluated: usize,
+}
+
+/// Searches a game tree to `depth` plies with minimax, where the root's player maximizes
+/// the score and the players alternate by depth.
+///
+/// Positions at the depth limit and positions without children are scored by `eval`,
+/// from the root player's point of view. Every position within the depth is visited; see
+/// [`alpha_beta`] for the same result with fewer evaluations. The search keeps its own
+/// stack, so any depth can be searched.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::game::minimax;
+/// use tree_iter::tree;
+///
+/// // The maximizer picks a move, then the minimizer replies
+/// let tree = tree! { 0 => [0 => [3, 12, 8], 0 => [2, 4, 6], 0 => [14, 5, 2]] };
+/// let result = minimax(&tree, 2, |node| node.value);
+/// assert_eq!(result.score, 3);
+/// assert_eq!(result.line, vec![0, 0]);
+/// assert_eq!(result.evaluated, 9);
+/// ```
+pub fn minimax<N: TreeNode, S: PartialOrd + Copy>(
+    root: &N,
+    depth: usize,
+    eval: impl FnMut(&N) -> S,
+) -> Evaluation<S> {
+    search(root, depth, eval, false, &[])
+}
+
+/// Searches a game tree to `depth` plies with alpha-beta pruning.
+///
+/// This gives the same score and, for the same order of children, the same line as
+/// [`minimax`], but skips the replies that cannot change the result, so it evaluates far
+/// fewer positions when good moves come first.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::game::alpha_beta;
+/// use tree_iter::tree;
+///
+/// let tree = tree! { 0 => [0 => [3, 12, 8], 0 => [2, 4, 6], 0 => [14, 5, 2]] };
+/// let result = alpha_beta(&tree, 2, |node| node.value);
+/// assert_eq!(result.score, 3);
+/// assert_eq!(result.line, vec![0, 0]);
+/// // After 2, the second move cannot beat the first
+/// assert_eq!(result.evaluated, 7);
+/// ```
+pub fn alpha_beta<N: TreeNode, S: PartialOrd + Copy>(
+    root: &N,
+    depth: usize,
+    eval: impl FnMut(&N) -> S,
+) -> Evaluation<S> {
+    search(root, depth, eval, true, &[])
+}
+
+/// Searches a game tree with alpha-beta pruning at depths `1, 2, ...` up to `max_depth`,
+/// and returns the result of the deepest search completed.
+///
+/// Each search tries the principal variation of the one before first, which makes its
+/// pruning more effective. `proceed` is called after each search with its result, and
+/// stops the deepening by returning `false`, for instance when a time budget runs out.
+/// A `max_depth` of 0 evaluates the root alone.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::game::iterative_deepening;
+/// use tree_iter::tree;
+///
+/// let tree = tree! { 0 => [1 => [-5, 9], 2 => [7 => [8], 6]] };
+/// let mut depths = Vec::new();
+/// let result = iterative_deepening(&tree, 10, |node| node.value, |result| {
+///     depths.push(result.line.len());
+///     result.line.len() < 2
+/// });
+/// assert_eq!(depths, vec![1, 2]);
+/// assert_eq!((result.score, result.line), (6, vec![1, 1]));
+/// ```
+pub fn iterative_deepening<N: TreeNode, S: PartialOrd + Copy>(
+    root: &N,
+    max_depth: usize,
+    mut eval: impl FnMut(&N) -> S,
+    mut proceed: impl FnMut(&Evaluation<S>) -> bool,
+) -> Evaluation<S> {
+    let mut result = search(root, 0, &mut eval, true, &[]);
+    for depth in 1..=max_depth {
+        result = search(root, depth, &mut eval, true, &result.line);
+        if !proceed(&result) {
+            break;
+        }
+    }
+    result
+}
+
+/// A position whose children are being searched.
+struct Frame<'n, N, S> {
+    /// The children in the order they are searched, with their indices.
+    children: Vec<(usize, &'n N)>,
+    /// The position in `children` of the next child to search.
+    next: usize,
+    /// The index of this position among its siblings.
+    index: usize,
+    /// The number of plies left to search below this position.
+    depth: usize,
+    /// Whether the player to move here maximizes the score.
+    maximizing: bool,
+    /// The score the maximizer is already assured of, if any.
+    alpha: Option<S>,
+    /// The score the minimizer is already assured of, if any.
+    beta: Option<S>,
+    /// The index of the child tried first, if the position is on the line of an earlier
+    /// search.
+    first: Option<usize>,
+    /// The best score found so far, with its line.
+    best: Option<(S, Vec<usize>)>,
+}
+
+impl<'n, N: TreeNode, S: PartialOrd + Copy> Frame<'n, N, S> {
+    /// Creates a frame for a position, to be searched within the given bounds.
+    fn new(
+        node: &'n N,
+        index: usize,
+        depth: usize,
+        maximizing: bool,
+        (alpha, beta): (Option<S>, Option<S>),
+        first: Option<usize>,
+    ) -> Self {
+        let mut children: Vec<(usize, &N)> = node.children().enumerate().collect();
+        if let Some(first) = first.filter(|&first| first < children.len()) {
+            children[..=first].rotate_right(1);
+        }
+        Self {
+            children,
+            next: 0,
+            index,
+            depth,
+            maximizing,
+            alpha,
+            beta,
+            first,
+            best: None,
+        }
+    }
+
+    /// Records the score of a child and its line, and skips the remaining children if
+    /// pruning shows they cannot matter.
+    fn update(&mut self, score: S, line: Vec<usize>, prune: bool) {
+        let better = match &self.best {
+            None => true,
+            Some((best, _)) if self.maximizing => score > *best,
+            Some((best, _)) => score < *best,
+        };
+        if better {
+            self.best = Some((score, line));
+        }
+        if !prune {
+            return;
+        }
+        if self.maximizing {
+            if self.alpha.is_none_or(|alpha| score > alpha) {
+                self.alpha = Some(score);
+            }
+        } else if self.beta.is_none_or(|beta| score < beta) {
+            self.beta = Some(score);
+        }
+        if let (Some(alpha), Some(beta)) = (self.alpha, self.beta)
+            && alpha >= beta
+        {
+            self.next = self.children.len();
+        }
+    }
+}
+
+/// Searches a game tree to `depth` plies, with or without pruning, trying the moves of
+/// `line` first.
+fn search<N: TreeNode, S: PartialOrd + Copy>(
+    root: &N,
+    depth: usize,
+    mut eval: impl FnMut(&N) -> S,
+    prune: bool,
+    line: &[usize],
+) -> Evaluation<S> {
+    let mut evaluated = 0;
+    let mut eval = |node: &N| {
+        evaluated += 1;
+        eval(node)
+    };
+    if depth == 0 || root.children().next().is_none() {
+        let score = eval(root);
+        return Evaluation {
+            score,
+            line: Vec::new(),
+            evaluated,
+        };
+    }
+
+    let bounds = (None, None);
+    let mut stack = vec![Frame::new(
+        root,
+        0,
+        depth,
+        true,
+        bounds,
+        line.first().copied(),
+    )];
+    loop {
+        let ply = stack.len();
+        let frame = stack.last_mut().expect("the root is popped last");
+        if let Some(&(index, child)) = frame.children.get(frame.next) {
+            frame.next += 1;
+            if frame.depth == 1 || child.children().next().is_none() {
+                let score = eval(child);
+                frame.update(score, vec![index], prune);
+            } else {
+                let first = if frame.first == Some(index) {
+                    line.get(ply).copied()
+                } else {
+                    None
+                };
+                let bounds = (frame.alpha, frame.beta);
+                let (depth, maximizing) = (frame.depth - 1, !frame.maximizing);
+                stack.push(Frame::new(child, index, depth, maximizing, bounds, first));
+            }
+            continue;
+        }
+
+        let frame = stack.pop().expect("the root is popped last");
+        let (score, mut best) = frame.best.expect("a searched position has children");
+        match stack.last_mut() {
+            Some(parent) => {
+                best.insert(0, frame.index);
+                parent.update(score, best, prune);
+            }
+            None => {
+                return Evaluation {
+                    score,
+                    line: best,
+                    evaluated,
+                };
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::Node;
+
+    #[test]
+    fn test_pruning_agrees_with_minimax() {
+        // A pseudo-random game tree with scores in the leaves and at every depth
+        let mut seed = 7u64;
+        let tree = Node::unfold::<crate::traversal_order::DepthFirst, _>(0u32, |n| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            let value = (seed >> 40) as i64 % 100;
+            let children = if n < 6 {
+                (0..(seed >> 20) % 5).map(|_| n + 1).collect()
+            } else {
+                vec![]
+            };
+            (value, children)
+        });
+        for depth in 0..8 {
+            let full = minimax(&tree, depth, |node| node.value);
+            let pruned = alpha_beta(&tree, depth, |node| node.value);
+            assert_eq!(pruned.score, full.score);
+            assert_eq!(pruned.line, full.line);
+            assert!(pruned.evaluated <= full.evaluated);
+            let deepened = iterative_deepening(&tree, depth, |node| node.value, |_| true);
+            assert_eq!(deepened.score, full.score);
+            // Following the line reaches a position scoring the result
+            let mut node = &tree;
+            for &index in &full.line {
+                node = &node.children[index];
+            }
+            assert_eq!(node.value, full.score);
+        }
+        assert!(
+            alpha_beta(&tree, 7, |node| node.value).evaluated
+                < minimax(&tree, 7, |node| node.value).evaluated
+        );
+    }
+}
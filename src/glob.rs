@@ -0,0 +1,337 @@
+use std::{error::Error, fmt, str::FromStr};
+
+use crate::iter::TreeNode;
+
+/// One segment of a [`PathGlob`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Segment {
+    /// The child with the given index.
+    Index(usize),
+    /// Any one child.
+    Any,
+    /// Any number of levels, including none.
+    AnyDepth,
+    /// The children whose keys match the pattern, in which `*` stands for any text and `?`
+    /// for any one character.
+    Key(String),
+}
+
+impl Segment {
+    /// Returns whether a child with the given index matches the segment, with `key` giving
+    /// the child's key if the segment needs it.
+    fn matches(&self, index: usize, key: impl FnOnce() -> String) -> bool {
+        match self {
+            Self::Index(i) => *i == index,
+            Self::Any => true,
+            Self::AnyDepth => false,
+            Self::Key(pattern) => wildcard_match(pattern, &key()),
+        }
+    }
+}
+
+/// A glob pattern over child-index and key paths, such as `0.*.2` or `**.leaf`.
+///
+/// A pattern is a list of segments joined by dots, each picking children one level down
+/// from the nodes picked so far, starting at the root:
+///
+/// * a number, such as `2`, picks the child with that index;
+/// * `*` picks every child;
+/// * `**` picks the nodes themselves and all their descendants, any number of levels down;
+/// * anything else picks the children whose key matches it, where `*` in the segment stands
+///   for any text and `?` for any one character, as in `db_*`.
+///
+/// The empty pattern picks only the root. Keys are given by a function passed to
+/// [`PathGlob::find`]; [`PathGlob::matches`] checks child-index paths alone, on which key
+/// segments never match.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::glob::PathGlob;
+/// use tree_iter::tree;
+///
+/// let config = tree! {
+///     "root" => ["db" => ["host", "port"], "cache" => ["port"], "web" => ["tls" => ["port"]]]
+/// };
+///
+/// let ports: PathGlob = "**.port".parse().unwrap();
+/// let found: Vec<Vec<usize>> = ports.find(&config, |node| node.value).map(|(_, path)| path).collect();
+/// assert_eq!(found, vec![vec![0, 1], vec![1, 0], vec![2, 0, 0]]);
+///
+/// let glob: PathGlob = "*.0".parse().unwrap();
+/// assert!(glob.matches(&[2, 0]));
+/// assert!(!glob.matches(&[2, 0, 0]));
+///
+/// let named: PathGlob = "?b.*".parse().unwrap();
+/// let values: Vec<&str> = named.find(&config, |node| node.value).map(|(node, _)| node.value).collect();
+/// assert_eq!(values, vec!["host", "port"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PathGlob {
+    /// The segments, from the root down.
+    segments: Vec<Segment>,
+}
+
+impl PathGlob {
+    /// Returns whether the child-index path matches the pattern.
+    pub fn matches(&self, path: &[usize]) -> bool {
+        let mut states = self.closure(vec![0]);
+        for &index in path {
+            states = self.step(&states, index, String::new);
+            if states.is_empty() {
+                return false;
+            }
+        }
+        states.contains(&self.segments.len())
+    }
+
+    /// Returns an iterator over the nodes of the tree rooted at `root` that match the
+    /// pattern, in depth-first order, each with its child-index path from `root`.
+    ///
+    /// `key` gives the key of a node, and is only called for nodes that a key segment is
+    /// matched against. The tree is walked lazily and iteratively, skipping every subtree
+    /// in which the pattern cannot match.
+    pub fn find<'g, 'n, N: TreeNode, K: AsRef<str>, F: FnMut(&N) -> K>(
+        &'g self,
+        root: &'n N,
+        key: F,
+    ) -> GlobMatches<'g, 'n, N, F> {
+        GlobMatches {
+            glob: self,
+            key,
+            stack: vec![(root, self.closure(vec![0]), 0, 0)],
+            path: Vec::new(),
+        }
+    }
+
+    /// Returns the states reached from `states` by matching a child with the given index
+    /// and key.
+    fn step(&self, states: &[usize], index: usize, mut key: impl FnMut() -> String) -> Vec<usize> {
+        let mut key_text = None;
+        let mut next = Vec::new();
+        for &state in states {
+            let Some(segment) = self.segments.get(state) else {
+                continue;
+            };
+            if *segment == Segment::AnyDepth {
+                next.push(state);
+            } else if segment.matches(index, || key_text.get_or_insert_with(&mut key).clone()) {
+                next.push(state + 1);
+            }
+        }
+        self.closure(next)
+    }
+
+    /// Adds to `states` the states reached by letting `**` segments match no levels, and
+    /// sorts them without duplicates.
+    fn closure(&self, mut states: Vec<usize>) -> Vec<usize> {
+        let mut i = 0;
+        while i < states.len() {
+            let state = states[i];
+            if self.segments.get(state) == Some(&Segment::AnyDepth) {
+                states.push(state + 1);
+            }
+            i += 1;
+        }
+        states.sort_unstable();
+        states.dedup();
+        states
+    }
+}
+
+impl fmt::Display for PathGlob {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, segment) in self.segments.iter().enumerate() {
+            if i > 0 {
+                f.write_str(".")?;
+            }
+            match segment {
+                Segment::Index(index) => write!(f, "{index}")?,
+                Segment::Any => f.write_str("*")?,
+                Segment::AnyDepth => f.write_str("**")?,
+                Segment::Key(pattern) => f.write_str(pattern)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for PathGlob {
+    type Err = ParseGlobError;
+
+    /// Parses segments joined by dots, or the empty string for the root.
+    fn from_str(text: &str) -> Result<Self, ParseGlobError> {
+        if text.is_empty() {
+            return Ok(Self {
+                segments: Vec::new(),
+            });
+        }
+        let segments = text
+            .split('.')
+            .enumerate()
+            .map(|(position, segment)| match segment {
+                "" => Err(ParseGlobError { position }),
+                "*" => Ok(Segment::Any),
+                "**" => Ok(Segment::AnyDepth),
+                _ if segment.bytes().all(|b| b.is_ascii_digit()) => segment
+                    .parse()
+                    .map(Segment::Index)
+                    .map_err(|_| ParseGlobError { position }),
+                _ => Ok(Segment::Key(segment.to_string())),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { segments })
+    }
+}
+
+/// The error returned when a string is not a [`PathGlob`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ParseGlobError {
+    /// The position of the invalid segment, counted from zero.
+    pub position: usize,
+}
+
+impl fmt::Display for ParseGlobError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "segment {} is empty or an index out of range",
+            self.position
+        )
+    }
+}
+
+impl Error for ParseGlobError {}
+
+/// An iterator over the nodes matching a [`PathGlob`], created by [`PathGlob::find`].
+///
+/// # Type Parameters
+///
+/// * `'g` - The lifetime of the pattern.
+/// * `'n` - The lifetime of the nodes being searched.
+/// * `N` - The type of node.
+/// * `F` - Gives the key of a node.
+pub struct GlobMatches<'g, 'n, N, F> {
+    /// The pattern being matched.
+    glob: &'g PathGlob,
+    /// Gives the key of a node.
+    key: F,
+    /// The nodes still to visit, the next one last, each with the states it is reached in,
+    /// its depth and its index among its siblings.
+    stack: Vec<(&'n N, Vec<usize>, usize, usize)>,
+    /// The child-index path of the last visited node.
+    path: Vec<usize>,
+}
+
+impl<'n, N: TreeNode, K: AsRef<str>, F: FnMut(&N) -> K> Iterator for GlobMatches<'_, 'n, N, F> {
+    type Item = (&'n N, Vec<usize>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((node, states, depth, index)) = self.stack.pop() {
+            if depth > 0 {
+                self.path.truncate(depth - 1);
+                self.path.push(index);
+            }
+            let start = self.stack.len();
+            for (i, child) in node.children().enumerate() {
+                let key = &mut self.key;
+                let next = self
+                    .glob
+                    .step(&states, i, || key(child).as_ref().to_string());
+                if !next.is_empty() {
+                    self.stack.push((child, next, depth + 1, i));
+                }
+            }
+            self.stack[start..].reverse();
+            if states.contains(&self.glob.segments.len()) {
+                return Some((node, self.path.clone()));
+            }
+        }
+        None
+    }
+}
+
+impl<N, F> fmt::Debug for GlobMatches<'_, '_, N, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GlobMatches")
+            .field("glob", &self.glob)
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Returns whether `text` matches `pattern`, in which `*` stands for any text and `?` for
+/// any one character.
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // The position after the last `*` and the text position it was tried at
+    let mut backtrack = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                p += 1;
+                backtrack = Some((p, t));
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                // Let the last `*` swallow one more character
+                Some((star, start)) => {
+                    p = star;
+                    t = start + 1;
+                    backtrack = Some((star, t));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::Node;
+
+    #[test]
+    fn test_patterns() {
+        let glob = |text: &str| text.parse::<PathGlob>().unwrap();
+        assert!(glob("").matches(&[]));
+        assert!(!glob("").matches(&[0]));
+        assert!(glob("**").matches(&[]));
+        assert!(glob("**").matches(&[3, 1, 4]));
+        assert!(glob("0.**.2").matches(&[0, 2]));
+        assert!(glob("0.**.2").matches(&[0, 5, 5, 2]));
+        assert!(!glob("0.**.2").matches(&[1, 2]));
+        assert!(glob("*.**.*").matches(&[1, 2]));
+        assert!(!glob("*.**.*").matches(&[1]));
+        assert!(!glob("name").matches(&[0]));
+        assert_eq!(glob("0.*.**.a?c*").to_string(), "0.*.**.a?c*");
+        assert_eq!(
+            "0..1".parse::<PathGlob>(),
+            Err(ParseGlobError { position: 1 })
+        );
+
+        assert!(wildcard_match("a*b*c", "aXbYbZc"));
+        assert!(wildcard_match("*", ""));
+        assert!(wildcard_match("?é*", "xé"));
+        assert!(!wildcard_match("a*b", "acbd"));
+
+        // Keys are only computed for nodes checked against key segments
+        let tree = crate::tree! { "a" => ["b" => ["c", "b"], "c" => ["b" => ["b"]]] };
+        let mut calls = 0;
+        let found: Vec<Vec<usize>> = glob("1.**.b")
+            .find(&tree, |node: &Node<&str>| {
+                calls += 1;
+                node.value
+            })
+            .map(|(_, path)| path)
+            .collect();
+        assert_eq!(found, vec![vec![1, 0], vec![1, 0, 0]]);
+        assert_eq!(calls, 2);
+    }
+}
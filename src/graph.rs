@@ -0,0 +1,258 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    error::Error,
+    fmt,
+    hash::Hash,
+    marker::PhantomData,
+};
+
+use crate::{
+    iter::TreeNode,
+    trace::{Trail, trace},
+    traversal_order::{DepthFirst, OrderKind, TraversalOrder},
+};
+
+/// What a [`GraphIter`] does when it reaches a node it has already visited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum RevisitPolicy {
+    /// Skip the node and its descendants, so every node is visited once.
+    #[default]
+    VisitOnce,
+    /// Visit the node again, as a plain tree traversal would. A cycle makes the traversal endless.
+    VisitAlways,
+    /// Stop the traversal with a [`Revisited`] error.
+    Error,
+}
+
+/// The error returned by a [`GraphIter`] with [`RevisitPolicy::Error`] when a node is reached twice.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Revisited<K> {
+    /// The id of the node that was reached again.
+    pub id: K,
+}
+
+impl<K: fmt::Debug> fmt::Display for Revisited<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "node {:?} was reached more than once", self.id)
+    }
+}
+
+impl<K: fmt::Debug> Error for Revisited<K> {}
+
+/// Gives the id of a node.
+type IdFn<'a, N, K> = Box<dyn FnMut(&N) -> K + 'a>;
+
+/// A traversal of a [`TreeNode`] structure whose children may be shared, as in a DAG.
+///
+/// A plain traversal visits a shared node once per path leading to it. This iterator
+/// tells nodes apart with an id function and applies a [`RevisitPolicy`] to nodes whose id
+/// it has seen before. Items are `Ok` nodes, except for the single `Err` that ends the
+/// traversal under [`RevisitPolicy::Error`].
+///
+/// # Type Parameters
+///
+/// * `'a` - The lifetime of the nodes being traversed.
+/// * `N` - The type of node.
+/// * `O` - The traversal order strategy (e.g., `DepthFirst` or `BreadthFirst`).
+/// * `K` - The type of node ids.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::rc::Rc;
+/// use tree_iter::graph::{GraphIter, RevisitPolicy};
+/// use tree_iter::prelude::*;
+///
+/// struct Task {
+///     name: &'static str,
+///     deps: Vec<Rc<Task>>,
+/// }
+///
+/// impl TreeNode for Task {
+///     fn children(&self) -> impl DoubleEndedIterator<Item = &Self> {
+///         self.deps.iter().map(|dep| &**dep)
+///     }
+/// }
+///
+/// let libc = Rc::new(Task { name: "libc", deps: vec![] });
+/// let ssl = Rc::new(Task { name: "ssl", deps: vec![libc.clone()] });
+/// let app = Task { name: "app", deps: vec![ssl, libc] };
+///
+/// // A plain traversal builds libc twice
+/// assert_eq!(app.iter::<DepthFirst>().count(), 4);
+///
+/// let names: Vec<&str> = GraphIter::new(&app, |task| task.name)
+///     .map(|task| task.unwrap().name)
+///     .collect();
+/// assert_eq!(names, vec!["app", "ssl", "libc"]);
+///
+/// let mut strict = GraphIter::new(&app, |task| task.name).policy(RevisitPolicy::Error);
+/// assert!(strict.by_ref().take(3).all(|task| task.is_ok()));
+/// assert!(matches!(strict.next(), Some(Err(e)) if e.id == "libc"));
+/// assert!(strict.next().is_none());
+/// ```
+pub struct GraphIter<'a, N, O, K> {
+    /// Queue of nodes to be visited, with their paths for logging.
+    nodes: VecDeque<(&'a N, Trail)>,
+    /// The ids of the nodes visited so far.
+    visited: HashSet<K>,
+    /// Gives the id of a node.
+    id: IdFn<'a, N, K>,
+    /// What to do with nodes that were already visited.
+    policy: RevisitPolicy,
+    /// Phantom data to track the traversal order type.
+    _order: PhantomData<O>,
+}
+
+impl<'a, N: TreeNode, K: Hash + Eq> GraphIter<'a, N, DepthFirst, K> {
+    /// Creates a depth-first traversal from `root` that visits every node once.
+    ///
+    /// # Parameters
+    ///
+    /// * `root` - The node to start from.
+    /// * `id` - Gives the id of a node; nodes with equal ids are the same node.
+    pub fn new(root: &'a N, id: impl FnMut(&N) -> K + 'a) -> Self {
+        Self {
+            nodes: VecDeque::from([(root, Trail::root())]),
+            visited: HashSet::new(),
+            id: Box::new(id),
+            policy: RevisitPolicy::default(),
+            _order: PhantomData,
+        }
+    }
+}
+
+impl<'a, N: TreeNode, O: TraversalOrder, K: Hash + Eq> GraphIter<'a, N, O, K> {
+    /// Sets the traversal order.
+    pub fn order<O2: TraversalOrder>(self, order: O2) -> GraphIter<'a, N, O2, K> {
+        let _ = order;
+        GraphIter {
+            nodes: self.nodes,
+            visited: self.visited,
+            id: self.id,
+            policy: self.policy,
+            _order: PhantomData,
+        }
+    }
+
+    /// Sets what to do with nodes that were already visited.
+    pub fn policy(mut self, policy: RevisitPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+}
+
+impl<'a, N: TreeNode, O: TraversalOrder, K: Hash + Eq> Iterator for GraphIter<'a, N, O, K> {
+    type Item = Result<&'a N, Revisited<K>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (node, trail) = loop {
+            let (node, trail) = self.nodes.pop_front()?;
+            if self.policy == RevisitPolicy::VisitAlways {
+                break (node, trail);
+            }
+            let id = (self.id)(node);
+            if !self.visited.contains(&id) {
+                self.visited.insert(id);
+                break (node, trail);
+            }
+            if self.policy == RevisitPolicy::Error {
+                trace!("stopping at {trail}: the node was reached before");
+                self.nodes.clear();
+                return Some(Err(Revisited { id }));
+            }
+            trace!("skipping {trail} with its subtree: the node was reached before");
+        };
+
+        match O::KIND {
+            OrderKind::BreadthFirst => self.nodes.extend(
+                node.children()
+                    .enumerate()
+                    .map(|(i, child)| (child, trail.child(i))),
+            ),
+            OrderKind::DepthFirst => {
+                // Indices are only needed for logging
+                let mut index = if trail.is_kept() {
+                    node.children().count()
+                } else {
+                    0
+                };
+                for child in node.children().rev() {
+                    index = index.saturating_sub(1);
+                    self.nodes.push_front((child, trail.child(index)));
+                }
+            }
+        }
+        Some(Ok(node))
+    }
+}
+
+impl<N: fmt::Debug, O, K: fmt::Debug> fmt::Debug for GraphIter<'_, N, O, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GraphIter")
+            .field("nodes", &self.nodes)
+            .field("visited", &self.visited)
+            .field("policy", &self.policy)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::traversal_order::BreadthFirst;
+
+    struct Shared {
+        id: usize,
+        children: Vec<Rc<Shared>>,
+    }
+
+    impl TreeNode for Shared {
+        fn children(&self) -> impl DoubleEndedIterator<Item = &Self> {
+            self.children.iter().map(|child| &**child)
+        }
+    }
+
+    #[test]
+    fn test_policies() {
+        // A diamond: 0 -> {1, 2} -> 3
+        let bottom = Rc::new(Shared {
+            id: 3,
+            children: vec![],
+        });
+        let left = Rc::new(Shared {
+            id: 1,
+            children: vec![bottom.clone()],
+        });
+        let right = Rc::new(Shared {
+            id: 2,
+            children: vec![bottom],
+        });
+        let root = Shared {
+            id: 0,
+            children: vec![left, right],
+        };
+
+        let ids = |policy| {
+            GraphIter::new(&root, |node: &Shared| node.id)
+                .order(BreadthFirst)
+                .policy(policy)
+                .map(|node| node.map(|node| node.id).map_err(|e| e.id))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            ids(RevisitPolicy::VisitOnce),
+            vec![Ok(0), Ok(1), Ok(2), Ok(3)]
+        );
+        assert_eq!(
+            ids(RevisitPolicy::VisitAlways),
+            vec![Ok(0), Ok(1), Ok(2), Ok(3), Ok(3)]
+        );
+        assert_eq!(
+            ids(RevisitPolicy::Error),
+            vec![Ok(0), Ok(1), Ok(2), Ok(3), Err(3)]
+        );
+    }
+}
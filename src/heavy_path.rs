@@ -0,0 +1,115 @@
+use crate::{flat::Flat, iter::TreeNode};
+
+/// Splits a tree into heavy paths.
+///
+/// Every node has one heavy child, the child with the largest subtree (the earliest one on
+/// ties), and the edges to heavy children join the nodes into chains running downwards.
+/// Since the subtree of a light child is at most half the size of its parent's, any path
+/// from the root to a node crosses at most `log2(n)` chains, which is what makes path
+/// queries over the chains run in logarithmic time.
+///
+/// Each chain is returned as the ids of its nodes in depth-first order, as numbered by
+/// [`PreorderIds`], from its head downwards; [`PreorderIds::path`] gives the child-index
+/// path of a node when needed. Every node is in exactly one chain, and the chains are
+/// ordered by their head, so the first chain starts at the root. The head of every other
+/// chain is a light child. The decomposition takes `O(n)` time and iterates, so the tree
+/// may be of any depth.
+///
+/// [`PreorderIds`]: crate::preorder::PreorderIds
+/// [`PreorderIds::path`]: crate::preorder::PreorderIds::path
+///
+/// # Parameters
+///
+/// * `root` - The root node of the tree.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::heavy_path::heavy_path_decomposition;
+/// use tree_iter::preorder::PreorderIds;
+/// use tree_iter::tree;
+///
+/// // Ids are positions in depth-first order: 1, 2, 3, 4, 5, 6
+/// let tree = tree! { 1 => [2, 3 => [4, 5 => [6]]] };
+/// let chains = heavy_path_decomposition(&tree);
+/// assert_eq!(chains, vec![vec![0, 2, 4, 5], vec![1], vec![3]]);
+///
+/// let ids = PreorderIds::new(&tree);
+/// assert_eq!(ids.path(chains[0][3]), vec![1, 1, 0]);
+/// assert_eq!(ids.parent(chains[2][0]), Some(2));
+/// ```
+pub fn heavy_path_decomposition<N: TreeNode>(root: &N) -> Vec<Vec<usize>> {
+    let flat = Flat::new(root);
+    let size = |id: usize| flat.subtree_end[id] - id;
+
+    // The children of a node are the subtrees that follow it in pre-order
+    let heavy: Vec<Option<usize>> = (0..flat.len())
+        .map(|id| {
+            let mut heavy: Option<usize> = None;
+            let mut child = id + 1;
+            while child < flat.subtree_end[id] {
+                if heavy.is_none_or(|heavy| size(child) > size(heavy)) {
+                    heavy = Some(child);
+                }
+                child = flat.subtree_end[child];
+            }
+            heavy
+        })
+        .collect();
+
+    (0..flat.len())
+        .filter(|&id| flat.parent[id].is_none_or(|parent| heavy[parent] != Some(id)))
+        .map(|head| std::iter::successors(Some(head), |&id| heavy[id]).collect())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{preorder::PreorderIds, tree::Node};
+
+    #[test]
+    fn test_light_edges_are_logarithmic() {
+        // A complete binary tree of 2^10 - 1 nodes
+        let tree = Node::unfold::<crate::traversal_order::DepthFirst, _>(1u32, |n| {
+            (
+                n,
+                if n < 512 {
+                    vec![2 * n, 2 * n + 1]
+                } else {
+                    vec![]
+                },
+            )
+        });
+        let chains = heavy_path_decomposition(&tree);
+        assert_eq!(chains.len(), 512);
+        assert_eq!(chains.iter().map(Vec::len).sum::<usize>(), 1023);
+
+        // Chains run down from parents to children
+        let ids = PreorderIds::new(&tree);
+        let mut is_head = vec![false; ids.len()];
+        for chain in &chains {
+            is_head[chain[0]] = true;
+            for pair in chain.windows(2) {
+                assert_eq!(ids.parent(pair[1]), Some(pair[0]));
+            }
+        }
+
+        // The chain crossings on the way to each node are the light edges above it
+        for chain in &chains {
+            let light = std::iter::successors(Some(chain[0]), |&id| ids.parent(id))
+                .filter(|&id| is_head[id])
+                .count();
+            assert!(light <= 10);
+        }
+
+        // A long chain is a single heavy path
+        let mut deep = Node::new(0);
+        for i in 1..100_000 {
+            deep = Node::with_children(i, [deep]);
+        }
+        let chains = heavy_path_decomposition(&deep);
+        assert_eq!(chains.len(), 1);
+        assert_eq!(chains[0].len(), 100_000);
+    }
+}
@@ -0,0 +1,197 @@
+use std::{
+    collections::HashMap,
+    hash::{BuildHasher, Hash, RandomState},
+};
+
+use crate::{
+    iter::TreeNode,
+    traversal_order::{DepthFirst, TraversalOrder},
+    traverse::{IterBuilder, Nodes},
+    tree::Node,
+};
+
+/// The id of a value stored in an [`InternedTree`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ValueId(u32);
+
+impl ValueId {
+    /// Returns the position of the value in the tree's table of distinct values.
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// A tree that stores each distinct value once, with the nodes holding small ids.
+///
+/// Trees of strings, such as path components or tag names, often repeat the same few
+/// values many times. Here the shape is an ordinary [`Node`] tree of [`ValueId`]s, so all
+/// the traversal and editing methods of `Node` apply to it, and the values are looked up
+/// in a table where each appears once. [`InternedTree::iter`] traverses the values directly.
+///
+/// # Type Parameters
+///
+/// * `T` - The type of value stored in the tree.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::interned::InternedTree;
+/// use tree_iter::prelude::*;
+/// use tree_iter::tree;
+///
+/// let paths = tree! { "src" => ["mod.rs" => [], "a" => ["mod.rs"], "b" => ["mod.rs"]] };
+/// let mut interned = InternedTree::from(paths.clone());
+/// assert_eq!(interned.value_count(), 4);
+///
+/// let values: Vec<&str> = interned.iter::<DepthFirst>().copied().collect();
+/// assert_eq!(values, vec!["src", "mod.rs", "a", "mod.rs", "b", "mod.rs"]);
+///
+/// // Nodes can be added through the shape, with their values interned first
+/// let id = interned.intern("lib.rs");
+/// interned.root.push_child(tree_iter::tree::Node::new(id));
+/// assert_eq!(interned.value(&interned.root.children[3]), &"lib.rs");
+/// ```
+#[derive(Debug, Clone)]
+pub struct InternedTree<T> {
+    /// The shape of the tree, with the id of each node's value.
+    pub root: Node<ValueId>,
+    /// The distinct values, indexed by id.
+    values: Vec<T>,
+    /// The ids of the values, grouped by hash.
+    ids: HashMap<u64, Vec<ValueId>>,
+    /// Hashes the values.
+    hasher: RandomState,
+}
+
+impl<T: Eq + Hash> InternedTree<T> {
+    /// Creates a tree with a single node holding `value`.
+    pub fn new(value: T) -> Self {
+        let mut tree = Self {
+            root: Node::new(ValueId(0)),
+            values: Vec::new(),
+            ids: HashMap::new(),
+            hasher: RandomState::new(),
+        };
+        tree.root.value = tree.intern(value);
+        tree
+    }
+
+    /// Returns the id of `value`, adding it to the table if it is not there yet.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the table would hold more than `u32::MAX` distinct values.
+    pub fn intern(&mut self, value: T) -> ValueId {
+        let hash = self.hasher.hash_one(&value);
+        let ids = self.ids.entry(hash).or_default();
+        if let Some(&id) = ids.iter().find(|id| self.values[id.index()] == value) {
+            return id;
+        }
+        let id =
+            ValueId(u32::try_from(self.values.len()).expect("too many distinct values to intern"));
+        self.values.push(value);
+        ids.push(id);
+        id
+    }
+
+    /// Returns the id of `value`, if it is in the table.
+    pub fn get_id(&self, value: &T) -> Option<ValueId> {
+        let hash = self.hasher.hash_one(value);
+        self.ids
+            .get(&hash)?
+            .iter()
+            .copied()
+            .find(|id| self.values[id.index()] == *value)
+    }
+}
+
+impl<T> InternedTree<T> {
+    /// Returns the value with the given id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` does not come from this tree.
+    pub fn resolve(&self, id: ValueId) -> &T {
+        &self.values[id.index()]
+    }
+
+    /// Returns the value of a node of the tree.
+    pub fn value(&self, node: &Node<ValueId>) -> &T {
+        self.resolve(node.value)
+    }
+
+    /// Returns the number of distinct values in the table.
+    ///
+    /// Values stay in the table when the nodes holding them are removed.
+    pub fn value_count(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns an iterator over the values of the nodes, in the order `O`.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `O` - The traversal order strategy to use (e.g., `DepthFirst` or `BreadthFirst`).
+    pub fn iter<O: TraversalOrder>(&self) -> impl Iterator<Item = &T> {
+        IterBuilder::<_, O, Nodes>::new(&self.root)
+            .build()
+            .map(|node| self.value(node))
+    }
+
+    /// Copies the tree into a [`Node`] tree holding the values themselves.
+    pub fn to_node(&self) -> Node<T>
+    where
+        T: Clone,
+    {
+        self.root
+            .materialize::<DepthFirst, _>(|node| self.value(node).clone())
+    }
+}
+
+impl<T: Eq + Hash> From<Node<T>> for InternedTree<T> {
+    /// Interns the values of a tree, keeping its shape.
+    fn from(root: Node<T>) -> Self {
+        let mut tree = Self {
+            root: Node::new(ValueId(0)),
+            values: Vec::new(),
+            ids: HashMap::new(),
+            hasher: RandomState::new(),
+        };
+        tree.root = Node::unfold::<DepthFirst, _>(root, |node| {
+            let (value, children) = node.into_parts();
+            (tree.intern(value), children)
+        });
+        tree
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traversal_order::BreadthFirst;
+
+    #[test]
+    fn test_interning() {
+        let tree = crate::tree! {
+            "a".to_string() => ["b".to_string() => ["a".to_string()], "b".to_string()]
+        };
+        let interned = InternedTree::from(tree.clone());
+        assert_eq!(interned.value_count(), 2);
+        assert_eq!(
+            interned.root.children[0].value,
+            interned.root.children[1].value
+        );
+        assert_eq!(interned.get_id(&"a".to_string()), Some(interned.root.value));
+        assert_eq!(interned.get_id(&"c".to_string()), None);
+        assert_eq!(
+            interned.iter::<BreadthFirst>().collect::<Vec<_>>(),
+            vec!["a", "b", "b", "a"]
+        );
+        assert_eq!(interned.to_node(), tree);
+
+        let mut single = InternedTree::new(7);
+        assert_eq!(single.intern(7), single.root.value);
+        let id = single.intern(8);
+        assert_eq!(single.resolve(id), &8);
+    }
+}
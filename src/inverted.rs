@@ -0,0 +1,137 @@
+use std::{collections::HashMap, hash::Hash};
+
+use crate::{flat::Flat, iter::TreeNode, preorder::PreorderIds};
+
+/// An index from tokens to the nodes that contain them, for searching a tree by words.
+///
+/// Building the index runs a tokenizer over every node, such as one splitting a file name
+/// or heading into lowercase words, and records for each token the nodes it occurs in and
+/// how often. The index owns its data rather than borrowing the tree, and refers to nodes
+/// by their [`PreorderIds`], returned by [`InvertedIndex::ids`].
+///
+/// # Type Parameters
+///
+/// * `T` - The type of tokens.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::inverted::InvertedIndex;
+/// use tree_iter::tree;
+///
+/// let tree = tree! {
+///     "src" => ["tree iter", "iter mut" => ["mut guard"], "display tree tree"]
+/// };
+/// let index = InvertedIndex::build(&tree, |node| {
+///     node.value.split(' ').collect::<Vec<_>>()
+/// });
+///
+/// // Nodes with more of the query tokens come first, then nodes with more occurrences
+/// assert_eq!(index.search(["tree", "iter"]), vec![vec![0], vec![2], vec![1]]);
+/// assert_eq!(index.search(["guard"]), vec![vec![1, 0]]);
+/// assert!(index.search(["missing"]).is_empty());
+/// ```
+#[derive(Debug, Clone)]
+pub struct InvertedIndex<T> {
+    /// The numbering of the nodes.
+    ids: PreorderIds,
+    /// The nodes each token occurs in, in depth-first order, with the number of
+    /// occurrences.
+    postings: HashMap<T, Vec<(usize, usize)>>,
+}
+
+impl<T: Eq + Hash> InvertedIndex<T> {
+    /// Builds the index for the tree rooted at `root`, with `tokenizer` giving the tokens
+    /// of each node.
+    pub fn build<N: TreeNode, I: IntoIterator<Item = T>>(
+        root: &N,
+        mut tokenizer: impl FnMut(&N) -> I,
+    ) -> Self {
+        let flat = Flat::new(root);
+        let mut postings: HashMap<T, Vec<(usize, usize)>> = HashMap::new();
+        for (id, &node) in flat.nodes.iter().enumerate() {
+            for token in tokenizer(node) {
+                let list = postings.entry(token).or_default();
+                match list.last_mut() {
+                    Some((last, count)) if *last == id => *count += 1,
+                    _ => list.push((id, 1)),
+                }
+            }
+        }
+        Self {
+            ids: PreorderIds::from_flat(&flat),
+            postings,
+        }
+    }
+
+    /// Returns the numbering of the nodes of the tree.
+    pub fn ids(&self) -> &PreorderIds {
+        &self.ids
+    }
+
+    /// Returns the number of nodes the token occurs in.
+    pub fn frequency(&self, token: &T) -> usize {
+        self.postings.get(token).map_or(0, Vec::len)
+    }
+
+    /// Returns the ids of the nodes containing any of the query tokens, best first.
+    ///
+    /// Nodes are ranked by the number of distinct query tokens they contain, then by the
+    /// total number of occurrences of those tokens, then in depth-first order. Repeated
+    /// query tokens count once.
+    pub fn search_ids(&self, query: impl IntoIterator<Item = T>) -> Vec<usize> {
+        let mut scores: HashMap<usize, (usize, usize)> = HashMap::new();
+        let mut seen = Vec::new();
+        for token in query {
+            if seen.contains(&token) {
+                continue;
+            }
+            for &(id, count) in self.postings.get(&token).into_iter().flatten() {
+                let score = scores.entry(id).or_default();
+                score.0 += 1;
+                score.1 += count;
+            }
+            seen.push(token);
+        }
+        let mut ranked: Vec<(usize, (usize, usize))> = scores.into_iter().collect();
+        ranked.sort_unstable_by(|(a, a_score), (b, b_score)| b_score.cmp(a_score).then(a.cmp(b)));
+        ranked.into_iter().map(|(id, _)| id).collect()
+    }
+
+    /// Returns the child-index paths of the nodes containing any of the query tokens, best
+    /// first.
+    ///
+    /// See [`InvertedIndex::search_ids`] for the ranking.
+    pub fn search(&self, query: impl IntoIterator<Item = T>) -> Vec<Vec<usize>> {
+        self.search_ids(query)
+            .into_iter()
+            .map(|id| self.ids.path(id))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::Node;
+
+    #[test]
+    fn test_ranking() {
+        let tree = crate::tree! {
+            "a b" => ["b", "c" => ["a a", "b a c"], "c c c"]
+        };
+        let index = InvertedIndex::build(&tree, |node: &Node<&str>| {
+            node.value.split(' ').map(String::from).collect::<Vec<_>>()
+        });
+        assert_eq!(index.ids().len(), 6);
+        assert_eq!(index.frequency(&"a".to_string()), 3);
+        assert_eq!(index.frequency(&"c".to_string()), 3);
+
+        let query = |tokens: &[&str]| index.search_ids(tokens.iter().map(|t| t.to_string()));
+        // Ids: "a b" 0, "b" 1, "c" 2, "a a" 3, "b a c" 4, "c c c" 5
+        assert_eq!(query(&["a", "b", "c"]), vec![4, 0, 5, 3, 1, 2]);
+        assert_eq!(query(&["c", "c"]), vec![5, 2, 4]);
+        assert_eq!(query(&["a", "z"]), vec![3, 0, 4]);
+        assert_eq!(index.ids().path(4), vec![1, 1]);
+    }
+}
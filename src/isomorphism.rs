@@ -0,0 +1,153 @@
+use std::{collections::HashMap, hash::Hash, mem};
+
+use crate::{flat::Flat, iter::TreeNode};
+
+/// Assigns canonical classes to trees whose children are unordered, by the AHU algorithm.
+///
+/// Two trees get the same class if and only if they are isomorphic when the order of
+/// children is ignored: their roots have equal keys, and their children can be paired up
+/// so that each pair gets the same class. Classes are small integers, numbered in the
+/// order they are first seen, and stay consistent across every tree given to the same
+/// canonicalizer, so they can be used to group or deduplicate many trees.
+///
+/// Each tree is classified bottom-up in one iterative pass, sorting the classes of the
+/// children of each node, in `O(n log n)` time for `n` nodes.
+///
+/// # Type Parameters
+///
+/// * `K` - The type of node keys.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::isomorphism::Canonicalizer;
+/// use tree_iter::tree;
+///
+/// let mut canonicalizer = Canonicalizer::new();
+/// let a = canonicalizer.class_of(&tree! { "a" => ["b" => ["c", "d"], "e"] }, |node| node.value);
+/// let b = canonicalizer.class_of(&tree! { "a" => ["e", "b" => ["d", "c"]] }, |node| node.value);
+/// let c = canonicalizer.class_of(&tree! { "a" => ["e" => ["c", "d"], "b"] }, |node| node.value);
+/// assert_eq!(a, b);
+/// assert_ne!(a, c);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Canonicalizer<K> {
+    /// The class of each combination of a key and the sorted classes of the children.
+    classes: HashMap<(K, Vec<usize>), usize>,
+}
+
+impl<K: Eq + Hash> Canonicalizer<K> {
+    /// Creates a canonicalizer with no classes.
+    pub fn new() -> Self {
+        Self {
+            classes: HashMap::new(),
+        }
+    }
+
+    /// Returns the class of the tree rooted at `root`, with `key` giving the key of a
+    /// node.
+    pub fn class_of<N: TreeNode>(&mut self, root: &N, mut key: impl FnMut(&N) -> K) -> usize {
+        let flat = Flat::new(root);
+        let mut children: Vec<Vec<usize>> = vec![Vec::new(); flat.len()];
+        let mut class = 0;
+        // Children come after their parent in pre-order, so this goes bottom-up
+        for id in (0..flat.len()).rev() {
+            let mut signature = mem::take(&mut children[id]);
+            signature.sort_unstable();
+            let next = self.classes.len();
+            class = *self
+                .classes
+                .entry((key(flat.nodes[id]), signature))
+                .or_insert(next);
+            if let Some(parent) = flat.parent[id] {
+                children[parent].push(class);
+            }
+        }
+        class
+    }
+
+    /// Returns the number of classes seen so far, counting those of every subtree.
+    pub fn len(&self) -> usize {
+        self.classes.len()
+    }
+
+    /// Returns whether no tree has been classified yet.
+    pub fn is_empty(&self) -> bool {
+        self.classes.is_empty()
+    }
+}
+
+impl<K: Eq + Hash> Default for Canonicalizer<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns whether two trees are isomorphic when the order of children is ignored, with
+/// `key` giving the key of a node, as for trees built from hash maps with unstable order.
+///
+/// See [`Canonicalizer`] to compare many trees with each other.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::isomorphism::is_isomorphic_unordered;
+/// use tree_iter::tree;
+///
+/// let a = tree! { 1 => [2 => [3, 4], 2 => [3]] };
+/// let b = tree! { 1 => [2 => [3], 2 => [4, 3]] };
+/// assert!(is_isomorphic_unordered(&a, &b, |node| node.value));
+/// assert!(!is_isomorphic_unordered(&a, &tree! { 1 => [2 => [3, 3], 2 => [4]] }, |node| node.value));
+/// ```
+pub fn is_isomorphic_unordered<N: TreeNode, K: Eq + Hash>(
+    a: &N,
+    b: &N,
+    mut key: impl FnMut(&N) -> K,
+) -> bool {
+    let mut canonicalizer = Canonicalizer::new();
+    canonicalizer.class_of(a, &mut key) == canonicalizer.class_of(b, &mut key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::Node;
+
+    #[test]
+    fn test_shuffled_children() {
+        let tree = Node::unfold::<crate::traversal_order::DepthFirst, _>(3u32, |n| {
+            let children = if n < 200 {
+                (0..n % 4).map(|i| 3 * n + i).collect()
+            } else {
+                vec![]
+            };
+            (n % 3, children)
+        });
+        // Rotate and reverse the children of every node
+        let mut shuffled = tree.clone();
+        let mut stack = vec![&mut shuffled];
+        while let Some(node) = stack.pop() {
+            let len = node.children.len();
+            node.children.rotate_left(len / 2);
+            node.children.reverse();
+            stack.extend(node.children.iter_mut());
+        }
+        assert_ne!(shuffled, tree);
+        assert!(is_isomorphic_unordered(&tree, &shuffled, |node| node.value));
+
+        // Moving a subtree to a parent with fewer children breaks the isomorphism
+        let mut moved = tree.clone();
+        let subtree = moved.children[2].children.pop().unwrap();
+        moved.children[0].children.push(subtree);
+        assert!(!is_isomorphic_unordered(&tree, &moved, |node| node.value));
+
+        // So does changing a key, though the shape stays the same
+        let mut relabeled = shuffled.clone();
+        relabeled.children[1].value += 1;
+        assert!(!is_isomorphic_unordered(&tree, &relabeled, |node| node.value));
+        let mut canonicalizer = Canonicalizer::new();
+        let shape = canonicalizer.class_of(&tree, |_| ());
+        assert_eq!(canonicalizer.class_of(&relabeled, |_| ()), shape);
+        assert_ne!(canonicalizer.class_of(&moved, |_| ()), shape);
+    }
+}
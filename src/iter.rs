@@ -1,6 +1,11 @@
-use std::{collections::VecDeque, marker::PhantomData};
+use std::{collections::VecDeque, fmt, marker::PhantomData, ops::Deref};
 
-use crate::traversal_order::{BreadthFirst, DepthFirst, TraversalOrder};
+use crate::{
+    flat::Flat,
+    traversal_order::{BreadthFirst, DepthFirst, TraversalOrder},
+    traverse::IterBuilder,
+    tree::Node,
+};
 
 /// Trait for immutable tree traversal.
 ///
@@ -46,6 +51,18 @@ pub trait TreeNode {
     /// This method must be implemented by all types implementing `TreeNode`.
     fn children(&self) -> impl DoubleEndedIterator<Item = &Self>;
 
+    /// Returns the children of this node as a contiguous slice, if they are stored that way.
+    ///
+    /// This is an optional fast path: when it returns `Some`, the iterators queue the whole
+    /// slice at once instead of one reference per child. The slice must contain the same
+    /// children, in the same order, as [`TreeNode::children`]. The default returns `None`.
+    fn children_slice(&self) -> Option<&[Self]>
+    where
+        Self: Sized,
+    {
+        None
+    }
+
     /// Creates an iterator that traverses the tree starting from this node.
     ///
     /// # Type Parameters
@@ -57,6 +74,518 @@ pub trait TreeNode {
     {
         TreeIter::new([self])
     }
+
+    /// Creates an iterator that traverses the tree in depth-first order starting from this node.
+    ///
+    /// This is `iter::<DepthFirst>()` without the turbofish.
+    fn iter_dfs(&self) -> TreeIter<'_, Self, DepthFirst>
+    where
+        Self: Sized,
+    {
+        self.iter()
+    }
+
+    /// Creates an iterator that traverses the tree in breadth-first order starting from this node.
+    ///
+    /// This is `iter::<BreadthFirst>()` without the turbofish.
+    fn iter_bfs(&self) -> TreeIter<'_, Self, BreadthFirst>
+    where
+        Self: Sized,
+    {
+        self.iter()
+    }
+
+    /// Starts configuring a traversal from this node, with depth-first order and no limits.
+    ///
+    /// See [`IterBuilder`] for the available options.
+    fn traverse(&self) -> IterBuilder<'_, Self>
+    where
+        Self: Sized,
+    {
+        IterBuilder::new(self)
+    }
+
+    /// Folds the tree bottom-up, combining each node with the results of its children.
+    ///
+    /// `f` is called once per node, after it has been called on all of the node's children,
+    /// and receives the children's results in order. The traversal uses an explicit stack,
+    /// so deep trees do not overflow the call stack.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::prelude::*;
+    /// use tree_iter::tree;
+    ///
+    /// let tree = tree! { 1 => [2 => [4, 5], 3] };
+    ///
+    /// // Sum of each subtree
+    /// let sum = tree.fold(|node, children: Vec<i32>| node.value + children.iter().sum::<i32>());
+    /// assert_eq!(sum, 15);
+    ///
+    /// // Render as a nested expression
+    /// let text = tree.fold(|node, children| {
+    ///     if children.is_empty() {
+    ///         node.value.to_string()
+    ///     } else {
+    ///         format!("{}({})", node.value, children.join(", "))
+    ///     }
+    /// });
+    /// assert_eq!(text, "1(2(4, 5), 3)");
+    /// ```
+    fn fold<R>(&self, mut f: impl FnMut(&Self, Vec<R>) -> R) -> R
+    where
+        Self: Sized,
+    {
+        enum Visit<'a, N> {
+            Enter(&'a N),
+            Exit(&'a N, usize),
+        }
+
+        let mut stack = vec![Visit::Enter(self)];
+        let mut results = Vec::new();
+        while let Some(visit) = stack.pop() {
+            match visit {
+                Visit::Enter(node) => {
+                    let start = stack.len();
+                    stack.extend(node.children().rev().map(Visit::Enter));
+                    let count = stack.len() - start;
+                    stack.insert(start, Visit::Exit(node, count));
+                }
+                Visit::Exit(node, count) => {
+                    let children = results.split_off(results.len() - count);
+                    results.push(f(node, children));
+                }
+            }
+        }
+        results.pop().unwrap()
+    }
+
+    /// Returns the number of nodes in the tree rooted at this node, including itself.
+    fn count(&self) -> usize
+    where
+        Self: Sized,
+    {
+        self.iter::<DepthFirst>().count()
+    }
+
+    /// Returns the height of the tree rooted at this node.
+    ///
+    /// The height is the number of edges on the longest path from this node down to a leaf,
+    /// so a single node has height 0.
+    fn height(&self) -> usize
+    where
+        Self: Sized,
+    {
+        self.width_per_level().len() - 1
+    }
+
+    /// Returns the largest number of children of any node in the tree rooted at this node.
+    fn max_degree(&self) -> usize
+    where
+        Self: Sized,
+    {
+        self.iter::<DepthFirst>()
+            .map(|node| node.children().count())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Returns the number of nodes at each depth of the tree rooted at this node.
+    ///
+    /// The first entry is always 1, for this node at depth 0, and the vector has
+    /// `height() + 1` entries.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::prelude::*;
+    /// use tree_iter::tree;
+    ///
+    /// let tree = tree! { 1 => [2 => [4, 5, 6], 3 => [7]] };
+    /// assert_eq!(tree.width_per_level(), vec![1, 2, 4]);
+    /// assert_eq!(tree.height(), 2);
+    /// assert_eq!(tree.count(), 7);
+    /// assert_eq!(tree.max_degree(), 3);
+    /// ```
+    fn width_per_level(&self) -> Vec<usize>
+    where
+        Self: Sized,
+    {
+        let mut widths = Vec::new();
+        let mut level = vec![self];
+        while !level.is_empty() {
+            widths.push(level.len());
+            level = level.iter().flat_map(|node| node.children()).collect();
+        }
+        widths
+    }
+
+    /// Returns an iterator over the levels of the tree rooted at this node, from the top
+    /// down, that stops at the first level failing `predicate`.
+    ///
+    /// Each level is the list of nodes at one depth, in breadth-first order. A level is
+    /// built only once the level above it has passed `predicate`, so a frontier search that
+    /// ends on a level condition never expands the level it ends on. The level that fails
+    /// is not yielded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::prelude::*;
+    /// use tree_iter::tree;
+    ///
+    /// let tree = tree! { 1 => [2 => [4, 5], 3 => [6 => [7]]] };
+    ///
+    /// // Stop at the first level with a value of 6 or more, without building the level below
+    /// let levels: Vec<Vec<i32>> = tree
+    ///     .iter_levels_while(|level| level.iter().all(|node| node.value < 6))
+    ///     .map(|level| level.iter().map(|node| node.value).collect())
+    ///     .collect();
+    /// assert_eq!(levels, vec![vec![1], vec![2, 3]]);
+    ///
+    /// // The iterator also ends below the deepest level
+    /// assert_eq!(tree.iter_levels_while(|_| true).count(), tree.height() + 1);
+    /// ```
+    fn iter_levels_while<F: FnMut(&[&Self]) -> bool>(
+        &self,
+        predicate: F,
+    ) -> LevelsWhile<'_, Self, F>
+    where
+        Self: Sized,
+    {
+        LevelsWhile {
+            level: vec![self],
+            predicate,
+            started: false,
+        }
+    }
+
+    /// Returns a longest path in the tree rooted at this node, from one end to the other.
+    ///
+    /// The length of the path, in nodes, is the diameter of the tree; a single node is a
+    /// path of one node. The path runs up from one end to the highest node on it and down
+    /// to the other end, starting in the subtree of the earlier child. It is computed in
+    /// one iterative bottom-up pass, so the tree may be of any depth.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::prelude::*;
+    /// use tree_iter::tree;
+    ///
+    /// let tree = tree! { 1 => [2 => [4 => [7]], 3 => [5, 6]] };
+    /// let path: Vec<i32> = tree.diameter().iter().map(|node| node.value).collect();
+    /// assert_eq!(path, vec![7, 4, 2, 1, 3, 5]);
+    /// ```
+    fn diameter(&self) -> Vec<&Self>
+    where
+        Self: Sized,
+    {
+        let flat = Flat::new(self);
+        // For each node: the number of nodes on its longest downward chain, and the
+        // children starting its longest and second longest chains
+        let mut down = vec![1; flat.len()];
+        let mut first: Vec<Option<usize>> = vec![None; flat.len()];
+        let mut second: Vec<Option<usize>> = vec![None; flat.len()];
+        for id in (1..flat.len()).rev() {
+            let parent = flat.parent[id].unwrap();
+            // Siblings are seen last to first, so ties go to the earlier child
+            if first[parent].is_none_or(|child| down[id] >= down[child]) {
+                second[parent] = first[parent];
+                first[parent] = Some(id);
+                down[parent] = down[id] + 1;
+            } else if second[parent].is_none_or(|child| down[id] >= down[child]) {
+                second[parent] = Some(id);
+            }
+        }
+
+        let chain_length = |child: Option<usize>| child.map_or(0, |child| down[child]);
+        let length = |id: usize| 1 + chain_length(first[id]) + chain_length(second[id]);
+        let top = (0..flat.len())
+            .reduce(|best, id| if length(id) > length(best) { id } else { best })
+            .unwrap();
+
+        // The chain in the earlier child comes first, reversed to run up to the top
+        let (head, tail) = match (first[top], second[top]) {
+            (Some(a), Some(b)) => (Some(a.min(b)), Some(a.max(b))),
+            (a, _) => (None, a),
+        };
+        let chain = |start| std::iter::successors(start, |&id| first[id]).map(|id| flat.nodes[id]);
+        let mut path: Vec<&Self> = chain(head).collect();
+        path.reverse();
+        path.push(flat.nodes[top]);
+        path.extend(chain(tail));
+        path
+    }
+
+    /// Returns the first node in depth-first order that satisfies `predicate`, with its
+    /// child-index path from this node, or `None` if there is none.
+    ///
+    /// The search stops at the first match. See [`TreeNode::find_map`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::prelude::*;
+    /// use tree_iter::tree;
+    ///
+    /// let tree = tree! { 1 => [2 => [4, 5], 3 => [6]] };
+    /// let (node, path) = tree.find(|node| node.value % 3 == 0).unwrap();
+    /// assert_eq!((node.value, path), (3, vec![1]));
+    /// assert!(tree.find(|node| node.value > 6).is_none());
+    /// ```
+    fn find(&self, mut predicate: impl FnMut(&Self) -> bool) -> Option<(&Self, Vec<usize>)>
+    where
+        Self: Sized,
+    {
+        self.find_map(|node| predicate(node).then_some(node))
+    }
+
+    /// Applies `f` to the nodes in depth-first order and returns the first result that is
+    /// `Some`, with the child-index path from this node of the node that gave it, or `None`
+    /// if every result is `None`.
+    ///
+    /// The search stops at the first match, without visiting the rest of the tree. `f` gets
+    /// nodes borrowed for as long as the tree, so results may borrow from them. The tree is
+    /// walked iteratively, so it may be of any depth.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::prelude::*;
+    /// use tree_iter::tree;
+    ///
+    /// let tree = tree! { "a" => ["b" => ["7"], "42"] };
+    /// let found = tree.find_map(|node| node.value.parse::<u32>().ok());
+    /// assert_eq!(found, Some((7, vec![0, 0])));
+    /// ```
+    fn find_map<'a, R>(
+        &'a self,
+        mut f: impl FnMut(&'a Self) -> Option<R>,
+    ) -> Option<(R, Vec<usize>)>
+    where
+        Self: Sized,
+    {
+        let mut path = Vec::new();
+        // Each node with its depth and its index among its siblings
+        let mut stack = vec![(self, 0, 0)];
+        while let Some((node, depth, index)) = stack.pop() {
+            if depth > 0 {
+                path.truncate(depth - 1);
+                path.push(index);
+            }
+            if let Some(result) = f(node) {
+                return Some((result, path));
+            }
+            let start = stack.len();
+            stack.extend(
+                node.children()
+                    .enumerate()
+                    .map(|(i, child)| (child, depth + 1, i)),
+            );
+            stack[start..].reverse();
+        }
+        None
+    }
+
+    /// Returns the child-index paths of every subtree of this tree that is equal to
+    /// `needle`, in depth-first order.
+    ///
+    /// A subtree is equal to the needle if it has the same shape and `eq` holds for each
+    /// pair of nodes at the same position, such as for repeated expressions in a syntax
+    /// tree. Occurrences may overlap. Both trees are flattened into depth-first order
+    /// first, so only subtrees of the needle's size are compared, and both may be of any
+    /// depth.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::prelude::*;
+    /// use tree_iter::tree;
+    ///
+    /// // (a + b) * (c - (a + b))
+    /// let expr = tree! { "*" => ["+" => ["a", "b"], "-" => ["c", "+" => ["a", "b"]]] };
+    /// let needle = tree! { "+" => ["a", "b"] };
+    /// let paths = expr.find_subtree(&needle, |node, other| node.value == other.value);
+    /// assert_eq!(paths, vec![vec![0], vec![1, 1]]);
+    /// ```
+    fn find_subtree<M: TreeNode>(
+        &self,
+        needle: &M,
+        mut eq: impl FnMut(&Self, &M) -> bool,
+    ) -> Vec<Vec<usize>>
+    where
+        Self: Sized,
+    {
+        let flat = Flat::new(self);
+        let pattern = Flat::new(needle);
+        // Equal pre-order sequences of subtree sizes mean equal shapes; the root is checked
+        // first, so the rest of the subtree is in bounds
+        let size_of = |ends: &[usize], id: usize| ends[id] - id;
+        (0..flat.len())
+            .filter(|&id| {
+                (0..pattern.len())
+                    .all(|k| size_of(&flat.subtree_end, id + k) == size_of(&pattern.subtree_end, k))
+                    && (0..pattern.len()).all(|k| eq(flat.nodes[id + k], pattern.nodes[k]))
+            })
+            .map(|id| flat.path(id))
+            .collect()
+    }
+
+    /// Walks down from this node, letting `choose` pick one child per step, and returns the
+    /// node where the walk stops with its child-index path.
+    ///
+    /// `choose` is called on each node that has children and returns the index of the
+    /// child to go to, or `None` to stop there. The walk also stops at a node without
+    /// children, which `choose` is not called on. This is the descent of decision trees,
+    /// routing tries and search trees. The walk is a loop, so it may go to any depth.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `choose` returns the index of a child that does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::prelude::*;
+    /// use tree_iter::tree;
+    ///
+    /// // Internal nodes split on a threshold, leaves hold the answer
+    /// let classifier = tree! { 50 => [20 => [-1, -2], 80 => [-3, -4]] };
+    /// let classify = |x: i32| {
+    ///     classifier.descend_by(|node| Some(if x < node.value { 0 } else { 1 }))
+    /// };
+    /// let (leaf, path) = classify(65);
+    /// assert_eq!((leaf.value, path), (-3, vec![1, 0]));
+    ///
+    /// let (node, path) = classifier.descend_by(|node| (node.value == 50).then_some(0));
+    /// assert_eq!((node.value, path), (20, vec![0]));
+    /// ```
+    fn descend_by(&self, mut choose: impl FnMut(&Self) -> Option<usize>) -> (&Self, Vec<usize>)
+    where
+        Self: Sized,
+    {
+        let mut node = self;
+        let mut path = Vec::new();
+        while node.children().next().is_some() {
+            let Some(index) = choose(node) else {
+                break;
+            };
+            node = node
+                .children()
+                .nth(index)
+                .expect("the chosen child does not exist");
+            path.push(index);
+        }
+        (node, path)
+    }
+
+    /// Walks down from this node, choosing each child at random with probability
+    /// proportional to its weight, and returns the node where the walk stops with its
+    /// child-index path.
+    ///
+    /// This is a single rollout, as in Monte Carlo tree search. `rng` must return numbers
+    /// drawn uniformly from `[0, 1)`, such as `rng.random::<f64>()` with the `rand` crate,
+    /// and is called once per step. `weight` gives the weight of a child; weights that are
+    /// negative or not a number count as zero. The walk stops at a node without children,
+    /// or whose children all have weight zero. The walk is a loop, so it may go to any
+    /// depth.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::prelude::*;
+    /// use tree_iter::tree;
+    ///
+    /// let tree = tree! { 0 => [1 => [3, 4], 2 => [5]] };
+    ///
+    /// // A fixed "random" number that picks from the last quarter of the total weight
+    /// let (leaf, path) = tree.random_descent(|| 0.8, |node| f64::from(node.value));
+    /// assert_eq!((leaf.value, path), (5, vec![1, 0]));
+    ///
+    /// // Children of weight zero are never chosen
+    /// let (node, path) = tree.random_descent(|| 0.5, |node| if node.value == 2 { 1.0 } else { 0.0 });
+    /// assert_eq!((node.value, path), (2, vec![1]));
+    /// ```
+    fn random_descent(
+        &self,
+        mut rng: impl FnMut() -> f64,
+        mut weight: impl FnMut(&Self) -> f64,
+    ) -> (&Self, Vec<usize>)
+    where
+        Self: Sized,
+    {
+        let mut node = self;
+        let mut path = Vec::new();
+        let mut weights = Vec::new();
+        loop {
+            weights.clear();
+            weights.extend(node.children().map(|child| {
+                let weight = weight(child);
+                if weight > 0.0 { weight } else { 0.0 }
+            }));
+            let total: f64 = weights.iter().sum();
+            if total <= 0.0 {
+                return (node, path);
+            }
+
+            // The last child of positive weight also takes any rounding error
+            let mut target = rng() * total;
+            let last = weights.iter().rposition(|&weight| weight > 0.0).unwrap();
+            let index = weights
+                .iter()
+                .position(|&weight| {
+                    target -= weight;
+                    weight > 0.0 && target < 0.0
+                })
+                .unwrap_or(last);
+            node = node.children().nth(index).unwrap();
+            path.push(index);
+        }
+    }
+
+    /// Copies the tree rooted at this node into an owned [`Node`] tree of the same shape.
+    ///
+    /// This is the bridge from any [`TreeNode`] implementation, such as a view over parsed
+    /// JSON or a directory listing, to the editing methods of [`Node`]. `value` gives the
+    /// value of each copied node, and is called on the nodes in the order `O`. The tree is
+    /// walked iteratively, so it may be of any depth.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `O` - The order in which `value` is called (e.g., `DepthFirst` or `BreadthFirst`).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::prelude::*;
+    /// use tree_iter::tree;
+    ///
+    /// struct Dir {
+    ///     name: String,
+    ///     entries: Vec<Dir>,
+    /// }
+    ///
+    /// impl TreeNode for Dir {
+    ///     fn children(&self) -> impl DoubleEndedIterator<Item = &Self> {
+    ///         self.entries.iter()
+    ///     }
+    /// }
+    ///
+    /// let dir = |name: &str, entries| Dir { name: name.to_string(), entries };
+    /// let root = dir("/", vec![dir("etc", vec![dir("hosts", vec![])]), dir("tmp", vec![])]);
+    ///
+    /// let mut owned = root.materialize::<DepthFirst, _>(|dir| dir.name.clone());
+    /// owned.sort_children_by(|a, b| b.cmp(a));
+    /// assert_eq!(owned, tree! { "/".to_string() => ["tmp".to_string(), "etc".to_string() => ["hosts".to_string()]] });
+    /// ```
+    fn materialize<O: TraversalOrder, T>(&self, mut value: impl FnMut(&Self) -> T) -> Node<T>
+    where
+        Self: Sized,
+    {
+        Node::unfold::<O, _>(self, |node| (value(node), node.children().collect()))
+    }
 }
 
 /// An iterator over tree nodes in a specified traversal order.
@@ -72,7 +601,7 @@ pub trait TreeNode {
 #[derive(Debug)]
 pub struct TreeIter<'a, N, T> {
     /// Queue of nodes to be visited.
-    nodes: VecDeque<&'a N>,
+    nodes: VecDeque<Pending<'a, N>>,
     /// Phantom data to track the traversal order type.
     _order: PhantomData<T>,
 }
@@ -85,10 +614,33 @@ impl<'a, N, T: TraversalOrder> TreeIter<'a, N, T> {
     /// * `roots` - An iterator yielding the root nodes to start traversal from.
     pub fn new(roots: impl IntoIterator<Item = &'a N>) -> Self {
         Self {
-            nodes: roots.into_iter().collect(),
+            nodes: roots.into_iter().map(Pending::Node).collect(),
             _order: PhantomData,
         }
     }
+
+    /// Removes the node at the front of the queue.
+    fn pop_front(&mut self) -> Option<&'a N> {
+        match self.nodes.pop_front()? {
+            Pending::Node(node) => Some(node),
+            Pending::Slice(slice) => {
+                let (first, rest) = slice.split_first()?;
+                if !rest.is_empty() {
+                    self.nodes.push_front(Pending::Slice(rest));
+                }
+                Some(first)
+            }
+        }
+    }
+}
+
+/// An entry in the queue of a [`TreeIter`].
+#[derive(Debug)]
+enum Pending<'a, N> {
+    /// A single node.
+    Node(&'a N),
+    /// A non-empty run of sibling nodes, from [`TreeNode::children_slice`].
+    Slice(&'a [N]),
 }
 
 /// Implementation of `Iterator` for breadth-first traversal.
@@ -99,8 +651,8 @@ impl<'a, N: TreeNode> Iterator for TreeIter<'a, N, BreadthFirst> {
     ///
     /// Breadth-first traversal visits all nodes at a given depth before moving to the next level.
     fn next(&mut self) -> Option<Self::Item> {
-        let node = self.nodes.pop_front()?;
-        self.nodes.extend(node.children());
+        let node = self.pop_front()?;
+        self.push_children(node);
         Some(node)
     }
 }
@@ -113,10 +665,221 @@ impl<'a, N: TreeNode> Iterator for TreeIter<'a, N, DepthFirst> {
     ///
     /// Depth-first traversal explores as far down a branch as possible before backtracking.
     fn next(&mut self) -> Option<Self::Item> {
-        let node = self.nodes.pop_front()?;
-        for child in node.children().rev() {
-            self.nodes.push_front(child);
-        }
+        let node = self.pop_front()?;
+        self.push_children(node);
         Some(node)
     }
 }
+
+impl<'a, N: TreeNode> TreeIter<'a, N, BreadthFirst> {
+    /// Adds the children of `node` to the back of the queue.
+    fn push_children(&mut self, node: &'a N) {
+        match node.children_slice() {
+            Some([]) => {}
+            Some(children) => self.nodes.push_back(Pending::Slice(children)),
+            None => self.nodes.extend(node.children().map(Pending::Node)),
+        }
+    }
+
+    /// Returns the next node in breadth-first order, wrapped in a guard.
+    ///
+    /// Unlike `next`, the node's children are only added to the traversal queue when the
+    /// guard is dropped, and not at all if [`BFSRefGuard::skip_children`] was called. This
+    /// mirrors the guards of [`TreeIterMut`](crate::iter_mut::TreeIterMut).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::prelude::*;
+    /// use tree_iter::tree;
+    ///
+    /// let tree = tree! { 1 => [2 => [4, 5], 3 => [6]] };
+    /// let mut values = Vec::new();
+    /// let mut iter = tree.iter::<BreadthFirst>();
+    /// while let Some(mut node) = iter.next_guard() {
+    ///     values.push(node.value);
+    ///     if node.value == 2 {
+    ///         node.skip_children();
+    ///     }
+    /// }
+    /// assert_eq!(values, vec![1, 2, 3, 6]);
+    /// ```
+    pub fn next_guard(&mut self) -> Option<BFSRefGuard<'a, '_, N>> {
+        self.pop_front().map(|node| BFSRefGuard {
+            iter: self,
+            node,
+            skip_children: false,
+        })
+    }
+}
+
+impl<'a, N: TreeNode> TreeIter<'a, N, DepthFirst> {
+    /// Adds the children of `node` to the front of the queue, leftmost first.
+    fn push_children(&mut self, node: &'a N) {
+        match node.children_slice() {
+            Some([]) => {}
+            Some(children) => self.nodes.push_front(Pending::Slice(children)),
+            None => {
+                for child in node.children().rev() {
+                    self.nodes.push_front(Pending::Node(child));
+                }
+            }
+        }
+    }
+
+    /// Returns the next node in depth-first order, wrapped in a guard.
+    ///
+    /// Unlike `next`, the node's children are only added to the traversal queue when the
+    /// guard is dropped, and not at all if [`DFSRefGuard::skip_children`] was called. This
+    /// mirrors the guards of [`TreeIterMut`](crate::iter_mut::TreeIterMut).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::prelude::*;
+    /// use tree_iter::tree;
+    ///
+    /// let tree = tree! { 1 => [2 => [4, 5], 3 => [6]] };
+    /// let mut values = Vec::new();
+    /// let mut iter = tree.iter::<DepthFirst>();
+    /// while let Some(mut node) = iter.next_guard() {
+    ///     values.push(node.value);
+    ///     if node.value == 2 {
+    ///         node.skip_children();
+    ///     }
+    /// }
+    /// assert_eq!(values, vec![1, 2, 3, 6]);
+    /// ```
+    pub fn next_guard(&mut self) -> Option<DFSRefGuard<'a, '_, N>> {
+        self.pop_front().map(|node| DFSRefGuard {
+            iter: self,
+            node,
+            skip_children: false,
+        })
+    }
+}
+
+/// A guard for node references in breadth-first traversal.
+///
+/// When the guard is dropped, the node's children are added to the back of the traversal
+/// queue, unless [`skip_children`](Self::skip_children) was called.
+///
+/// The guard implements `Deref` to allow direct access to the underlying node.
+#[derive(Debug)]
+pub struct BFSRefGuard<'a: 'b, 'b, N: TreeNode> {
+    /// Reference to the tree iterator.
+    iter: &'b mut TreeIter<'a, N, BreadthFirst>,
+    /// The current node being visited.
+    node: &'a N,
+    /// Whether the children of the node are left out of the traversal.
+    skip_children: bool,
+}
+
+impl<N: TreeNode> BFSRefGuard<'_, '_, N> {
+    /// Leaves the children of this node, and their subtrees, out of the traversal.
+    pub fn skip_children(&mut self) {
+        self.skip_children = true;
+    }
+}
+
+impl<N: TreeNode> Drop for BFSRefGuard<'_, '_, N> {
+    /// When the guard is dropped, add the node's children to the traversal queue.
+    fn drop(&mut self) {
+        if !self.skip_children {
+            self.iter.push_children(self.node);
+        }
+    }
+}
+
+impl<N: TreeNode> Deref for BFSRefGuard<'_, '_, N> {
+    type Target = N;
+
+    /// Provides access to the wrapped node.
+    fn deref(&self) -> &Self::Target {
+        self.node
+    }
+}
+
+/// A guard for node references in depth-first traversal.
+///
+/// When the guard is dropped, the node's children are added to the front of the traversal
+/// queue, unless [`skip_children`](Self::skip_children) was called.
+///
+/// The guard implements `Deref` to allow direct access to the underlying node.
+#[derive(Debug)]
+pub struct DFSRefGuard<'a: 'b, 'b, N: TreeNode> {
+    /// Reference to the tree iterator.
+    iter: &'b mut TreeIter<'a, N, DepthFirst>,
+    /// The current node being visited.
+    node: &'a N,
+    /// Whether the children of the node are left out of the traversal.
+    skip_children: bool,
+}
+
+impl<N: TreeNode> DFSRefGuard<'_, '_, N> {
+    /// Leaves the children of this node, and their subtrees, out of the traversal.
+    pub fn skip_children(&mut self) {
+        self.skip_children = true;
+    }
+}
+
+impl<N: TreeNode> Drop for DFSRefGuard<'_, '_, N> {
+    /// When the guard is dropped, add the node's children to the traversal queue.
+    fn drop(&mut self) {
+        if !self.skip_children {
+            self.iter.push_children(self.node);
+        }
+    }
+}
+
+impl<N: TreeNode> Deref for DFSRefGuard<'_, '_, N> {
+    type Target = N;
+
+    /// Provides access to the wrapped node.
+    fn deref(&self) -> &Self::Target {
+        self.node
+    }
+}
+
+/// An iterator over the levels of a tree that stops at the first level failing a
+/// predicate, created by [`TreeNode::iter_levels_while`].
+///
+/// # Type Parameters
+///
+/// * `'a` - The lifetime of the tree nodes being traversed.
+/// * `N` - The type of tree node.
+/// * `F` - The predicate on levels.
+pub struct LevelsWhile<'a, N, F> {
+    /// The level yielded last, or the root level before the first call to `next`. Empty
+    /// once the iterator is done.
+    level: Vec<&'a N>,
+    /// The predicate each level must pass to be yielded.
+    predicate: F,
+    /// Whether the root level has been checked.
+    started: bool,
+}
+
+impl<'a, N: TreeNode, F: FnMut(&[&N]) -> bool> Iterator for LevelsWhile<'a, N, F> {
+    type Item = Vec<&'a N>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.started {
+            self.level = self.level.iter().flat_map(|node| node.children()).collect();
+        }
+        self.started = true;
+        if self.level.is_empty() || !(self.predicate)(&self.level) {
+            self.level.clear();
+            return None;
+        }
+        Some(self.level.clone())
+    }
+}
+
+impl<N, F> fmt::Debug for LevelsWhile<'_, N, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LevelsWhile")
+            .field("width", &self.level.len())
+            .field("started", &self.started)
+            .finish_non_exhaustive()
+    }
+}
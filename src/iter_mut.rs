@@ -1,7 +1,10 @@
 use std::{
+    any::Any,
     collections::VecDeque,
+    fmt,
     marker::PhantomData,
     ops::{Deref, DerefMut},
+    panic::{self, AssertUnwindSafe},
 };
 
 use crate::traversal_order::{BreadthFirst, DepthFirst, TraversalOrder};
@@ -53,6 +56,110 @@ pub trait TreeNodeMut {
     {
         TreeIterMut::new([self])
     }
+
+    /// Creates a mutable iterator that traverses the tree in depth-first order starting from this node.
+    ///
+    /// This is `iter_mut::<DepthFirst>()` without the turbofish.
+    fn iter_mut_dfs(&mut self) -> TreeIterMut<'_, Self, DepthFirst>
+    where
+        Self: Sized,
+    {
+        self.iter_mut()
+    }
+
+    /// Creates a mutable iterator that traverses the tree in breadth-first order starting from this node.
+    ///
+    /// This is `iter_mut::<BreadthFirst>()` without the turbofish.
+    fn iter_mut_bfs(&mut self) -> TreeIterMut<'_, Self, BreadthFirst>
+    where
+        Self: Sized,
+    {
+        self.iter_mut()
+    }
+
+    /// Calls `f` on every node in depth-first order, catching any panic from `f` so that
+    /// one bad node does not abort the whole pass.
+    ///
+    /// A panic is recorded with the child-index path of the node, and the pass carries on
+    /// with the node's children and the rest of the tree, as if `f` had returned. The node
+    /// keeps whatever changes `f` made before panicking. Returns the panics caught, in
+    /// depth-first order. The walk uses an explicit stack, so the tree may be of any depth.
+    ///
+    /// `f` is called again after it panics, so it must not rely on state that a panic may
+    /// leave broken. Panics still go to the panic hook, which prints them by default, and
+    /// are not caught when the crate is built with `panic = "abort"`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::prelude::*;
+    /// use tree_iter::tree;
+    ///
+    /// let mut tree = tree! { 10 => [5 => [0, 2], 0] };
+    /// let caught = tree.for_each_mut_catch(|node| node.value = 100 / node.value);
+    ///
+    /// let paths: Vec<&[usize]> = caught.iter().map(|panic| panic.path.as_slice()).collect();
+    /// assert_eq!(paths, vec![&[0, 0][..], &[1]]);
+    /// assert_eq!(caught[0].message(), Some("attempt to divide by zero"));
+    /// assert_eq!(tree, tree! { 10 => [20 => [0, 50], 0] });
+    /// ```
+    fn for_each_mut_catch(&mut self, mut f: impl FnMut(&mut Self)) -> Vec<CaughtPanic>
+    where
+        Self: Sized,
+    {
+        let mut caught = Vec::new();
+        let mut path = Vec::new();
+        // Each node with its depth and its index among its siblings
+        let mut stack = vec![(self, 0, 0)];
+        while let Some((node, depth, index)) = stack.pop() {
+            if depth > 0 {
+                path.truncate(depth - 1);
+                path.push(index);
+            }
+            if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| f(node))) {
+                caught.push(CaughtPanic {
+                    path: path.clone(),
+                    payload,
+                });
+            }
+            let start = stack.len();
+            stack.extend(
+                node.children_mut()
+                    .enumerate()
+                    .map(|(i, child)| (child, depth + 1, i)),
+            );
+            stack[start..].reverse();
+        }
+        caught
+    }
+}
+
+/// A panic caught by [`TreeNodeMut::for_each_mut_catch`].
+pub struct CaughtPanic {
+    /// The child-index path of the node whose call panicked.
+    pub path: Vec<usize>,
+    /// The payload of the panic, as returned by [`std::panic::catch_unwind`].
+    pub payload: Box<dyn Any + Send>,
+}
+
+impl CaughtPanic {
+    /// Returns the panic message, if the payload is a string, as it is for panics raised
+    /// with `panic!` and for the panics of the standard library.
+    pub fn message(&self) -> Option<&str> {
+        match self.payload.downcast_ref::<&str>() {
+            Some(message) => Some(message),
+            None => self.payload.downcast_ref::<String>().map(String::as_str),
+        }
+    }
+}
+
+impl fmt::Debug for CaughtPanic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CaughtPanic")
+            .field("path", &self.path)
+            .field("message", &self.message())
+            .finish()
+    }
 }
 
 /// A mutable iterator over tree nodes in a specified traversal order.
@@ -99,6 +206,15 @@ pub struct BFSRefMutGuard<'a: 'b, 'b, N: TreeNodeMut> {
     iter: &'b mut TreeIterMut<'a, N, BreadthFirst>,
     /// The current node being visited (wrapped in Option to allow taking ownership in drop).
     node: Option<&'a mut N>,
+    /// Whether the children of the node are left out of the traversal.
+    skip_children: bool,
+}
+
+impl<N: TreeNodeMut> BFSRefMutGuard<'_, '_, N> {
+    /// Leaves the children of this node, and their subtrees, out of the traversal.
+    pub fn skip_children(&mut self) {
+        self.skip_children = true;
+    }
 }
 
 impl<N: TreeNodeMut> Drop for BFSRefMutGuard<'_, '_, N> {
@@ -108,7 +224,9 @@ impl<N: TreeNodeMut> Drop for BFSRefMutGuard<'_, '_, N> {
     /// added to the back of the queue to be processed after all nodes at the current level.
     fn drop(&mut self) {
         let node = self.node.take().unwrap();
-        self.iter.nodes.extend(node.children_mut());
+        if !self.skip_children {
+            self.iter.nodes.extend(node.children_mut());
+        }
     }
 }
 
@@ -133,11 +251,15 @@ impl<'a, N: TreeNodeMut> TreeIterMut<'a, N, BreadthFirst> {
     ///
     /// This method returns a guard that implements `DerefMut` to provide
     /// mutable access to the node. When the guard is dropped, the node's children
-    /// are added to the traversal queue in breadth-first order.
+    /// are added to the traversal queue in breadth-first order, unless
+    /// [`BFSRefMutGuard::skip_children`] was called.
+    // The guard borrows the iterator, so this cannot be `Iterator::next`
+    #[allow(clippy::should_implement_trait)]
     pub fn next(&mut self) -> Option<BFSRefMutGuard<'a, '_, N>> {
         self.nodes.pop_front().map(|node| BFSRefMutGuard {
             iter: self,
             node: Some(node),
+            skip_children: false,
         })
     }
 }
@@ -154,6 +276,15 @@ pub struct DFSRefMutGuard<'a: 'b, 'b, N: TreeNodeMut> {
     iter: &'b mut TreeIterMut<'a, N, DepthFirst>,
     /// The current node being visited (wrapped in Option to allow taking ownership in drop).
     node: Option<&'a mut N>,
+    /// Whether the children of the node are left out of the traversal.
+    skip_children: bool,
+}
+
+impl<N: TreeNodeMut> DFSRefMutGuard<'_, '_, N> {
+    /// Leaves the children of this node, and their subtrees, out of the traversal.
+    pub fn skip_children(&mut self) {
+        self.skip_children = true;
+    }
 }
 
 impl<N: TreeNodeMut> Drop for DFSRefMutGuard<'_, '_, N> {
@@ -164,8 +295,10 @@ impl<N: TreeNodeMut> Drop for DFSRefMutGuard<'_, '_, N> {
     /// is processed first.
     fn drop(&mut self) {
         let node = self.node.take().unwrap();
-        for child in node.children_mut().rev() {
-            self.iter.nodes.push_front(child);
+        if !self.skip_children {
+            for child in node.children_mut().rev() {
+                self.iter.nodes.push_front(child);
+            }
         }
     }
 }
@@ -191,11 +324,15 @@ impl<'a, N: TreeNodeMut> TreeIterMut<'a, N, DepthFirst> {
     ///
     /// This method returns a guard that implements `DerefMut` to provide
     /// mutable access to the node. When the guard is dropped, the node's children
-    /// are added to the traversal queue in depth-first order.
+    /// are added to the traversal queue in depth-first order, unless
+    /// [`DFSRefMutGuard::skip_children`] was called.
+    // The guard borrows the iterator, so this cannot be `Iterator::next`
+    #[allow(clippy::should_implement_trait)]
     pub fn next(&mut self) -> Option<DFSRefMutGuard<'a, '_, N>> {
         self.nodes.pop_front().map(|node| DFSRefMutGuard {
             iter: self,
             node: Some(node),
+            skip_children: false,
         })
     }
 }
@@ -0,0 +1,164 @@
+use std::fmt::{self, Write};
+
+use crate::iter::TreeNode;
+
+/// Escapes the characters that are special in LaTeX text, such as `_`, `&` and `{`.
+///
+/// Labels are written as given, so that they can use LaTeX markup such as math; pass plain
+/// text through this function in the label closure.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::latex::escape_latex;
+///
+/// assert_eq!(escape_latex("50% of a_b & {c}"), r"50\% of a\_b \& \{c\}");
+/// assert_eq!(escape_latex(r"\~"), r"\textbackslash{}\textasciitilde{}");
+/// ```
+pub fn escape_latex(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str(r"\textbackslash{}"),
+            '^' => escaped.push_str(r"\textasciicircum{}"),
+            '~' => escaped.push_str(r"\textasciitilde{}"),
+            '{' | '}' | '$' | '&' | '#' | '%' | '_' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Renders a tree as a `forest` environment, with `label` giving the LaTeX label of each
+/// node.
+///
+/// Each node is written as `[{label} ...]`, with its children indented by two spaces
+/// below it, so labels may contain the brackets and commas that `forest` treats
+/// specially. Labels are written as given; see [`escape_latex`]. The tree is walked
+/// iteratively.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::latex::{escape_latex, to_forest};
+/// use tree_iter::tree;
+///
+/// let tree = tree! { "S" => ["NP", "VP" => ["V_t"]] };
+/// assert_eq!(
+///     to_forest(&tree, |node| escape_latex(node.value)),
+///     "\\begin{forest}\n[{S}\n  [{NP}]\n  [{VP}\n    [{V\\_t}]\n  ]\n]\n\\end{forest}\n"
+/// );
+/// ```
+pub fn to_forest<N: TreeNode>(root: &N, label: impl FnMut(&N) -> String) -> String {
+    let mut out = String::new();
+    write_forest(root, label, &mut out).expect("writing to a String cannot fail");
+    out
+}
+
+/// Writes a tree as a `forest` environment to `out`.
+///
+/// See [`to_forest`] for details.
+pub fn write_forest<N: TreeNode>(
+    root: &N,
+    mut label: impl FnMut(&N) -> String,
+    out: &mut impl Write,
+) -> fmt::Result {
+    writeln!(out, "\\begin{{forest}}")?;
+    // `None` closes the bracket of the node at the given depth
+    let mut stack = vec![(Some(root), 0)];
+    while let Some((node, depth)) = stack.pop() {
+        let indent = "  ".repeat(depth);
+        let Some(node) = node else {
+            writeln!(out, "{indent}]")?;
+            continue;
+        };
+        write!(out, "{indent}[{{{}}}", label(node))?;
+        let mut children = node.children().rev().peekable();
+        if children.peek().is_none() {
+            writeln!(out, "]")?;
+        } else {
+            writeln!(out)?;
+            stack.push((None, depth));
+            stack.extend(children.map(|child| (Some(child), depth + 1)));
+        }
+    }
+    writeln!(out, "\\end{{forest}}")
+}
+
+/// Renders a tree as a `qtree` `\Tree` command on one line, with `label` giving the LaTeX
+/// label of each node.
+///
+/// Each inner node is written as `[.{label} ... ]` and each leaf as `{label}`. Labels are
+/// written as given; see [`escape_latex`]. The tree is walked iteratively.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::latex::to_qtree;
+/// use tree_iter::tree;
+///
+/// let tree = tree! { "S" => ["NP", "VP" => ["V"]] };
+/// assert_eq!(
+///     to_qtree(&tree, |node| node.value.to_string()),
+///     "\\Tree [.{S} {NP} [.{VP} {V} ] ]"
+/// );
+/// ```
+pub fn to_qtree<N: TreeNode>(root: &N, label: impl FnMut(&N) -> String) -> String {
+    let mut out = String::new();
+    write_qtree(root, label, &mut out).expect("writing to a String cannot fail");
+    out
+}
+
+/// Writes a tree as a `qtree` `\Tree` command to `out`.
+///
+/// See [`to_qtree`] for details.
+pub fn write_qtree<N: TreeNode>(
+    root: &N,
+    mut label: impl FnMut(&N) -> String,
+    out: &mut impl Write,
+) -> fmt::Result {
+    write!(out, "\\Tree")?;
+    // `None` closes the bracket of an inner node
+    let mut stack = vec![Some(root)];
+    while let Some(node) = stack.pop() {
+        let Some(node) = node else {
+            write!(out, " ]")?;
+            continue;
+        };
+        let mut children = node.children().rev().peekable();
+        if children.peek().is_none() {
+            write!(out, " {{{}}}", label(node))?;
+        } else {
+            write!(out, " [.{{{}}}", label(node))?;
+            stack.push(None);
+            stack.extend(children.map(Some));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::Node;
+
+    #[test]
+    fn test_deep_tree() {
+        let mut deep = Node::new(0);
+        for i in 1..10_000 {
+            deep = Node::with_children(i, [deep]);
+        }
+        let qtree = to_qtree(&deep, |node| node.value.to_string());
+        assert!(qtree.starts_with("\\Tree [.{9999} [.{9998}"));
+        assert!(qtree.contains("[.{1} {0} ] ]"));
+        assert_eq!(qtree.matches(']').count(), 9_999);
+
+        let forest = to_forest(deep.get(&[0; 9_000]).unwrap(), |node| {
+            node.value.to_string()
+        });
+        assert_eq!(forest.lines().count(), 2 * 999 + 3);
+    }
+}
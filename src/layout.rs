@@ -0,0 +1,264 @@
+use crate::{flat::Flat, iter::TreeNode};
+
+/// The position of a node in a drawing of a tree.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Point {
+    /// The horizontal position, growing to the right.
+    pub x: f64,
+    /// The vertical position, growing downwards from the root.
+    pub y: f64,
+}
+
+/// Lays out a tree with the default [`TidyLayout`] settings.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::layout::tidy_layout;
+/// use tree_iter::tree;
+///
+/// let tree = tree! { 1 => [2 => [4, 5], 3] };
+/// let xs: Vec<f64> = tidy_layout(&tree).iter().map(|point| point.x).collect();
+/// // In depth-first order: 1, 2, 4, 5, 3
+/// assert_eq!(xs, vec![1.0, 0.5, 0.0, 1.0, 1.5]);
+/// ```
+pub fn tidy_layout<N: TreeNode>(root: &N) -> Vec<Point> {
+    TidyLayout::new().layout(root)
+}
+
+/// Tidy drawing of any [`TreeNode`], with the Reingold–Tilford algorithm.
+///
+/// Each node is placed at a height given by its depth, and centered above its first and
+/// last child. Subtrees are packed from left to right as closely as the sibling distance
+/// allows at every depth, so a subtree is drawn the same wherever it occurs in the tree.
+///
+/// The nodes are laid out as points; to make room for labels, set the sibling distance to
+/// the widest label plus a margin. The positions are returned in a side table, in
+/// depth-first order, so they can be zipped with `root.iter::<DepthFirst>()`. The leftmost
+/// node is at `x = 0` and the root at `y = 0`. The layout takes linear time and is
+/// computed iteratively, so trees of any depth can be laid out.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::layout::TidyLayout;
+/// use tree_iter::prelude::*;
+/// use tree_iter::tree;
+///
+/// let tree = tree! { "a" => ["b", "c" => ["d"]] };
+/// let points = TidyLayout::new()
+///     .sibling_distance(40.0)
+///     .level_distance(60.0)
+///     .layout(&tree);
+/// for (node, point) in tree.iter::<DepthFirst>().zip(&points) {
+///     println!("{} at ({}, {})", node.value, point.x, point.y);
+/// }
+/// assert_eq!(points[3].x, 40.0);
+/// assert_eq!(points[3].y, 120.0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TidyLayout {
+    /// The least horizontal distance between two nodes at the same depth.
+    sibling_distance: f64,
+    /// The vertical distance between consecutive depths.
+    level_distance: f64,
+}
+
+/// The outline of a laid-out subtree: the leftmost and rightmost position at each depth.
+///
+/// The levels are stored from the deepest up to the subtree's root, so that a parent level
+/// can be added without moving the rest, and the stored positions are relative to `shift`,
+/// so that the whole outline can be moved in constant time.
+#[derive(Debug)]
+struct Contour {
+    /// The leftmost and rightmost position at each depth, from the deepest up.
+    levels: Vec<(f64, f64)>,
+    /// The offset added to every stored position.
+    shift: f64,
+}
+
+impl Contour {
+    /// Returns the leftmost and rightmost position at `depth` below the subtree's root.
+    fn level(&self, depth: usize) -> (f64, f64) {
+        let (left, right) = self.levels[self.levels.len() - 1 - depth];
+        (left + self.shift, right + self.shift)
+    }
+
+    /// Sets the leftmost and rightmost position at `depth` below the subtree's root.
+    fn set_level(&mut self, depth: usize, (left, right): (f64, f64)) {
+        let index = self.levels.len() - 1 - depth;
+        self.levels[index] = (left - self.shift, right - self.shift);
+    }
+}
+
+impl TidyLayout {
+    /// Creates a layout with nodes one unit apart, both across and down.
+    pub fn new() -> Self {
+        Self {
+            sibling_distance: 1.0,
+            level_distance: 1.0,
+        }
+    }
+
+    /// Sets the least horizontal distance between two nodes at the same depth.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `distance` is not positive.
+    pub fn sibling_distance(mut self, distance: f64) -> Self {
+        assert!(distance > 0.0, "the sibling distance must be positive");
+        self.sibling_distance = distance;
+        self
+    }
+
+    /// Sets the vertical distance between consecutive depths.
+    pub fn level_distance(mut self, distance: f64) -> Self {
+        self.level_distance = distance;
+        self
+    }
+
+    /// Returns the position of every node of the tree rooted at `root`, in depth-first
+    /// order.
+    pub fn layout<N: TreeNode>(&self, root: &N) -> Vec<Point> {
+        let flat = Flat::new(root);
+        // The horizontal offset of each node from its parent
+        let mut offset = vec![0.0; flat.len()];
+        let mut contours: Vec<Option<Contour>> = (0..flat.len()).map(|_| None).collect();
+
+        // Children come after their parent in pre-order, so they are laid out first
+        for id in (0..flat.len()).rev() {
+            let mut children = Vec::new();
+            let mut child = id + 1;
+            while child < flat.subtree_end[id] {
+                children.push(child);
+                child = flat.subtree_end[child];
+            }
+
+            let Some((&first, rest)) = children.split_first() else {
+                contours[id] = Some(Contour {
+                    levels: vec![(0.0, 0.0)],
+                    shift: 0.0,
+                });
+                continue;
+            };
+
+            // Place each subtree just clear of the ones to its left, with the first at 0
+            let mut placed = contours[first].take().unwrap();
+            for &child in rest {
+                let mut contour = contours[child].take().unwrap();
+                let common = placed.levels.len().min(contour.levels.len());
+                let position = (0..common)
+                    .map(|depth| {
+                        placed.level(depth).1 - contour.level(depth).0 + self.sibling_distance
+                    })
+                    .fold(f64::NEG_INFINITY, f64::max);
+                offset[child] = position;
+                contour.shift += position;
+
+                // Merge the shallower outline into the deeper one
+                placed = if contour.levels.len() > placed.levels.len() {
+                    for depth in 0..common {
+                        let (_, right) = contour.level(depth);
+                        contour.set_level(depth, (placed.level(depth).0, right));
+                    }
+                    contour
+                } else {
+                    for depth in 0..common {
+                        let (left, _) = placed.level(depth);
+                        placed.set_level(depth, (left, contour.level(depth).1));
+                    }
+                    placed
+                };
+            }
+
+            // Center the node above its first and last child
+            let middle = offset[*children.last().unwrap()] / 2.0;
+            for &child in &children {
+                offset[child] -= middle;
+            }
+            placed.shift -= middle;
+            placed.levels.push((-placed.shift, -placed.shift));
+            contours[id] = Some(placed);
+        }
+
+        let mut points = vec![Point::default(); flat.len()];
+        for id in 1..flat.len() {
+            let parent = points[flat.parent[id].unwrap()];
+            points[id] = Point {
+                x: parent.x + offset[id],
+                y: flat.depth[id] as f64 * self.level_distance,
+            };
+        }
+        let left = points.iter().map(|point| point.x).fold(0.0, f64::min);
+        for point in &mut points {
+            point.x -= left;
+        }
+        points
+    }
+}
+
+impl Default for TidyLayout {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{traversal_order::DepthFirst, tree::Node};
+
+    #[test]
+    fn test_nodes_do_not_overlap() {
+        let tree = Node::unfold::<DepthFirst, _>(1u32, |n| {
+            let children = match n % 4 {
+                _ if n > 60 => vec![],
+                0 => vec![],
+                1 => vec![2 * n],
+                2 => vec![2 * n, 2 * n + 1, 3 * n],
+                _ => vec![n + 1, 2 * n + 1],
+            };
+            (n, children)
+        });
+        let points = tidy_layout(&tree);
+        assert_eq!(points.len(), tree.count());
+        assert_eq!(points.iter().map(|p| p.x).fold(f64::MAX, f64::min), 0.0);
+
+        // Nodes at the same depth are in depth-first order from left to right
+        let mut rows: Vec<Vec<f64>> = Vec::new();
+        for point in &points {
+            let depth = point.y as usize;
+            rows.resize(rows.len().max(depth + 1), Vec::new());
+            rows[depth].push(point.x);
+        }
+        for row in &rows {
+            assert!(row.windows(2).all(|pair| pair[1] - pair[0] >= 1.0 - 1e-9));
+        }
+
+        // Each parent is centered above its first and last child
+        let flat = Flat::new(&tree);
+        for id in 0..flat.len() {
+            let first = id + 1;
+            if first < flat.subtree_end[id] {
+                let mut last = first;
+                while flat.subtree_end[last] < flat.subtree_end[id] {
+                    last = flat.subtree_end[last];
+                }
+                let middle = (points[first].x + points[last].x) / 2.0;
+                assert!((points[id].x - middle).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn test_deep_tree() {
+        let mut deep = Node::new(0);
+        for i in 1..100_000 {
+            deep = Node::with_children(i, [Node::new(-i), deep]);
+        }
+        let points = tidy_layout(&deep);
+        assert_eq!(points.len(), 199_999);
+        assert_eq!(points[0], Point { x: 0.5, y: 0.0 });
+        assert_eq!(points.last().unwrap().y, 99_999.0);
+    }
+}
@@ -0,0 +1,212 @@
+use std::{collections::HashSet, hash::Hash};
+
+use crate::{flat::Flat, iter::TreeNode, preorder::PreorderIds};
+
+/// An index answering lowest common ancestor queries in constant time.
+///
+/// The index is built from an Euler tour of the tree, which lists each node whenever the
+/// walk enters or returns to it, and a sparse table of the shallowest node over every
+/// power-of-two stretch of the tour. The lowest common ancestor of two nodes is the
+/// shallowest node on the tour between them. Building takes `O(n log n)` time and space.
+///
+/// Nodes are identified by their [`PreorderIds`], returned by [`LcaIndex::ids`].
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::lca::LcaIndex;
+/// use tree_iter::tree;
+///
+/// let tree = tree! { 1 => [2 => [4, 5 => [6]], 3] };
+/// let index = LcaIndex::build(&tree);
+///
+/// assert_eq!(index.lca_paths(&[0, 1, 0], &[0, 0]), Some(vec![0]));
+/// assert_eq!(index.lca_paths(&[0, 1, 0], &[1]), Some(vec![]));
+/// assert_eq!(index.lca_paths(&[0, 1], &[2]), None);
+///
+/// // Ids are positions in depth-first order: 1, 2, 4, 5, 6, 3
+/// assert_eq!(index.lca(4, 2), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct LcaIndex {
+    /// The numbering of the nodes.
+    ids: PreorderIds,
+    /// The depth of each node, with the root at depth 0.
+    depth: Vec<usize>,
+    /// The position of each node's first appearance in the Euler tour.
+    first: Vec<usize>,
+    /// For each `k`, the shallowest node over the `2^k` tour positions from each position.
+    table: Vec<Vec<usize>>,
+}
+
+impl LcaIndex {
+    /// Builds the index for the tree rooted at `root`.
+    pub fn build<N: TreeNode>(root: &N) -> Self {
+        let flat = Flat::new(root);
+        let ids = PreorderIds::from_flat(&flat);
+
+        // Nodes come in pre-order, so the walk only has to return up to each node's parent
+        let mut tour = vec![0];
+        let mut first = vec![0; flat.len()];
+        let mut ancestors = vec![0];
+        for (id, &parent) in flat.parent.iter().enumerate().skip(1) {
+            while ancestors.last().copied() != parent {
+                ancestors.pop();
+                tour.push(*ancestors.last().unwrap());
+            }
+            first[id] = tour.len();
+            tour.push(id);
+            ancestors.push(id);
+        }
+        while ancestors.len() > 1 {
+            ancestors.pop();
+            tour.push(*ancestors.last().unwrap());
+        }
+
+        let depth = flat.depth;
+        let mut table = vec![tour];
+        let mut span = 1;
+        while 2 * span <= table[0].len() {
+            let previous = table.last().unwrap();
+            let level = (0..previous.len() - span)
+                .map(|i| shallower(&depth, previous[i], previous[i + span]))
+                .collect();
+            table.push(level);
+            span *= 2;
+        }
+
+        Self {
+            ids,
+            depth,
+            first,
+            table,
+        }
+    }
+
+    /// Returns the numbering of the nodes of the tree.
+    pub fn ids(&self) -> &PreorderIds {
+        &self.ids
+    }
+
+    /// Returns the id of the lowest common ancestor of two nodes, in constant time.
+    ///
+    /// A node counts as its own ancestor, so the result is `a` if `a` is an ancestor of `b`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `a` or `b` is not less than [`PreorderIds::len`].
+    pub fn lca(&self, a: usize, b: usize) -> usize {
+        let (start, end) = if self.first[a] <= self.first[b] {
+            (self.first[a], self.first[b])
+        } else {
+            (self.first[b], self.first[a])
+        };
+        let level = (end - start + 1).ilog2() as usize;
+        let span = 1 << level;
+        let table = &self.table[level];
+        shallower(&self.depth, table[start], table[end + 1 - span])
+    }
+
+    /// Returns the path of the lowest common ancestor of the nodes at two paths, or `None`
+    /// if there is no node at either path.
+    pub fn lca_paths(&self, a: &[usize], b: &[usize]) -> Option<Vec<usize>> {
+        Some(self.ids.path(self.lca(self.ids.id(a)?, self.ids.id(b)?)))
+    }
+}
+
+/// Returns the lowest common ancestor of two nodes identified by keys, with `parent`
+/// giving the key of a node's parent, or `None` for a root.
+///
+/// This suits trees stored with parent links, such as arenas of ids or maps from a node to
+/// its parent, and needs no index: it walks up from `a` to the root, then up from `b` to
+/// the first node seen on the way, in time proportional to their depths. A node counts as
+/// its own ancestor. For trees given by their root, see [`LcaIndex`] or
+/// [`TreePath::common_ancestor`](crate::path::TreePath::common_ancestor).
+///
+/// Returns `None` if the nodes have no common ancestor, as when they are in different
+/// trees of a forest.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::collections::HashMap;
+/// use tree_iter::lca::lca_by_parent;
+///
+/// // usr -> {bin, lib -> {rustlib}}; etc is a second root
+/// let parents = HashMap::from([("bin", "usr"), ("lib", "usr"), ("rustlib", "lib")]);
+/// let parent = |node: &&str| parents.get(node).copied();
+///
+/// assert_eq!(lca_by_parent("rustlib", "bin", parent), Some("usr"));
+/// assert_eq!(lca_by_parent("rustlib", "lib", parent), Some("lib"));
+/// assert_eq!(lca_by_parent("bin", "etc", parent), None);
+/// ```
+pub fn lca_by_parent<K: Eq + Hash + Clone>(
+    a: K,
+    b: K,
+    mut parent: impl FnMut(&K) -> Option<K>,
+) -> Option<K> {
+    let mut ancestors = HashSet::new();
+    let mut node = Some(a);
+    while let Some(current) = node {
+        node = parent(&current);
+        ancestors.insert(current);
+    }
+    let mut node = Some(b);
+    while let Some(current) = node {
+        if ancestors.contains(&current) {
+            return Some(current);
+        }
+        node = parent(&current);
+    }
+    None
+}
+
+/// Returns whichever of two nodes is shallower, preferring `a` on ties.
+fn shallower(depth: &[usize], a: usize, b: usize) -> usize {
+    if depth[b] < depth[a] { b } else { a }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::Node;
+
+    #[test]
+    fn test_matches_naive_lca() {
+        let tree = Node::unfold::<crate::traversal_order::DepthFirst, _>(1u32, |n| {
+            let children = match n % 3 {
+                _ if n > 40 => vec![],
+                0 => vec![],
+                1 => vec![2 * n],
+                _ => vec![2 * n, 2 * n + 1, 3 * n],
+            };
+            (n, children)
+        });
+        let index = LcaIndex::build(&tree);
+        let ids = index.ids();
+        let paths: Vec<Vec<usize>> = (0..ids.len()).map(|id| ids.path(id)).collect();
+        for path_a in &paths {
+            for path_b in &paths {
+                let common = path_a
+                    .iter()
+                    .zip(path_b)
+                    .take_while(|(x, y)| x == y)
+                    .count();
+                assert_eq!(
+                    index.lca_paths(path_a, path_b),
+                    Some(path_a[..common].to_vec())
+                );
+            }
+        }
+
+        // The same answers from parent links between ids
+        for a in 0..ids.len() {
+            for b in 0..ids.len() {
+                let parent = |&id: &usize| ids.parent(id);
+                assert_eq!(lca_by_parent(a, b, parent), Some(index.lca(a, b)));
+            }
+        }
+        let single = LcaIndex::build(&Node::new(()));
+        assert_eq!(single.lca(0, 0), 0);
+    }
+}
@@ -13,6 +13,11 @@
  * - Breadth-first and depth-first traversal orders
  * - Immutable and mutable iteration
  * - Safe interior mutability during traversal using guard patterns
+ * - Optional `serde` support for the default tree types, behind the `serde` feature
+ * - Optional zero-copy `rkyv` archives of [`tree::Node`], traversable in place, behind the `rkyv` feature
+ * - Optional SVG drawings of trees, without any dependencies, behind the `svg` feature
+ * - Optional trace logging of prune decisions, budget hits and revisits, with node paths, through
+ *   the `log` crate, behind the `log` feature
  *
  * ## Example
  *
@@ -34,18 +39,123 @@
  * ```
  */
 
+/// Flat adjacency-list serde representation of trees, for `#[serde(with = ...)]`
+#[cfg(feature = "serde")]
+pub mod adjacency;
+/// Level ancestor queries with binary lifting
+pub mod ancestors;
+/// State machines driven by depth-first walks of trees
+pub mod automaton;
+/// Harness for measuring traversal throughput
+pub mod bench;
+/// Centroids and centroid decomposition of trees
+pub mod centroid;
+/// Checks that custom tree implementations traverse consistently
+pub mod conformance;
+/// Diffs between two trees, rendered side by side, walked in lockstep or applied as patches
+pub mod diff;
+/// Text rendering of trees with box-drawing branches
+pub mod display;
+/// Graphviz DOT export of trees
+pub mod dot;
+/// Building trees from streams of open and close events, and streaming them back
+pub mod events;
+/// Subtree sums over changing node weights, with a Fenwick tree
+pub mod fenwick;
+/// Pre-order flattening shared by the tree algorithms
+mod flat;
+/// Ordered collections of trees
+pub mod forest;
+/// Minimax and alpha-beta search over game trees, with iterative deepening
+pub mod game;
+/// Glob patterns over child-index and key paths
+pub mod glob;
+/// Traversal of trees whose children may be shared, as in a DAG
+pub mod graph;
+/// Heavy-path decomposition of trees
+pub mod heavy_path;
+/// Trees that store each distinct value once
+pub mod interned;
+/// Inverted indexes from tokens to the nodes containing them
+pub mod inverted;
+/// Isomorphism of trees with unordered children, by canonical classes
+pub mod isomorphism;
 /// Tree iteration modules for immutable references
 pub mod iter;
 /// Tree iteration modules for mutable references
 pub mod iter_mut;
+/// LaTeX `forest` and `qtree` export of trees
+pub mod latex;
+/// Tidy tree drawing coordinates, with the Reingold–Tilford algorithm
+pub mod layout;
+/// Lowest common ancestor queries, by index or by parent links
+pub mod lca;
+/// Macros for writing tree literals
+mod macros;
+/// Monte Carlo tree search over mutable trees
+pub mod mcts;
+/// Mermaid flowchart export of trees
+pub mod mermaid;
+/// Validated re-parenting of nodes, as used by drag and drop
+pub mod moves;
+/// Conversion between trees and indented text outlines
+pub mod outline;
+/// Weight-balanced partitioning of trees
+pub mod partition;
+/// Typed child-index paths
+pub mod path;
+/// Structural patterns with wildcards and holes
+pub mod pattern;
+/// Recycling of node allocations
+pub mod pool;
+/// Depth-first numbering of nodes, with conversions between ids and paths
+pub mod preorder;
+/// Selector queries over any tree
+pub mod query;
+/// `Archive`, `Serialize` and `Deserialize` for `Node`, and traversal of archived trees
+#[cfg(feature = "rkyv")]
+mod rkyv_impl;
+/// Structural validation of trees against the allowed children of each node kind
+pub mod schema;
+/// Bloom-filter indexes that skip subtrees during searches
+pub mod search;
+/// Hierarchical selection with tri-state checkbox semantics
+pub mod selection;
+/// `Serialize` and `Deserialize` for the default tree types
+#[cfg(feature = "serde")]
+mod serde_impl;
+/// S-expression import and export of trees
+pub mod sexpr;
+/// Hash-consed trees whose identical subtrees are shared through `Arc`
+pub mod shared;
+/// Breadth-first traversal with a frontier that can spill to disk
+pub mod spill;
+/// Tree nodes that can be built in `const` contexts
+pub mod static_node;
+/// Statistics over trees with numeric values
+mod stats;
+/// SVG drawings of trees
+#[cfg(feature = "svg")]
+pub mod svg;
+/// Helpers for trees labeled with text
+mod text;
+/// Trace logging of traversal decisions, with the `log` feature
+mod trace;
 /// Traversal order definitions (breadth-first and depth-first)
 pub mod traversal_order;
+/// Builder for traversals with pruning, depth limits, node budgets and subtree time budgets
+pub mod traverse;
 /// Default tree implementation
 pub mod tree;
+/// Tree iteration modules for mutable references with fallible child access
+pub mod try_iter_mut;
+/// Helpers for displaying expandable trees
+pub mod view;
 
 /// Prelude module for convenient imports of common types
 pub mod prelude {
     pub use crate::iter::{TreeIter, TreeNode};
     pub use crate::iter_mut::{TreeIterMut, TreeNodeMut};
     pub use crate::traversal_order::{BreadthFirst, DepthFirst, TraversalOrder};
+    pub use crate::try_iter_mut::{TryTreeIterMut, TryTreeNodeMut};
 }
@@ -0,0 +1,44 @@
+/// Builds a [`Node`](crate::tree::Node) tree from a literal description.
+///
+/// Each node is written as its value, optionally followed by `=>` and a bracketed,
+/// comma-separated list of its children. Values can be arbitrary expressions.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::prelude::*;
+/// use tree_iter::tree;
+/// use tree_iter::tree::Node;
+///
+/// let tree = tree! { 1 => [2 => [4, 5], 3] };
+///
+/// assert_eq!(
+///     tree,
+///     Node {
+///         value: 1,
+///         children: vec![
+///             Node {
+///                 value: 2,
+///                 children: vec![Node::new(4), Node::new(5)],
+///             },
+///             Node::new(3),
+///         ],
+///     }
+/// );
+///
+/// let values: Vec<i32> = tree.iter::<BreadthFirst>().map(|node| node.value).collect();
+/// assert_eq!(values, vec![1, 2, 3, 4, 5]);
+/// ```
+#[macro_export]
+macro_rules! tree {
+    // Every child is expanded by the same repetition, so only nesting adds to the recursion
+    ($value:expr => [$($child:expr $(=> [$($grandchildren:tt)*])?),* $(,)?]) => {
+        $crate::tree::Node {
+            value: $value,
+            children: ::std::vec![$($crate::tree!($child $(=> [$($grandchildren)*])?)),*],
+        }
+    };
+    ($value:expr) => {
+        $crate::tree::Node::new($value)
+    };
+}
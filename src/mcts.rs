@@ -0,0 +1,233 @@
+use crate::iter_mut::TreeNodeMut;
+
+/// The domain logic of a Monte Carlo tree search, driven by [`mcts`].
+///
+/// Each iteration of the search selects a path down the tree, expands the node it ends
+/// at, simulates a playout from there and backpropagates the result to every node on the
+/// path. The driver does the walking; the hooks decide where to go and keep the
+/// statistics, usually in the nodes themselves.
+///
+/// # Type Parameters
+///
+/// * `N` - The type of node.
+pub trait MctsHooks<N> {
+    /// The result of a playout, such as a score or a winner.
+    type Reward;
+
+    /// Picks the child to descend into during selection, usually by [`uct_score`], or
+    /// returns `None` to stop at this node and expand it.
+    ///
+    /// The index must be less than the number of children of the node.
+    fn select(&mut self, node: &N) -> Option<usize>;
+
+    /// Expands the node selected last, usually by adding a child for a move not tried
+    /// yet, and returns the index of the child to simulate from, or `None` to simulate
+    /// from the node itself, as for a terminal position.
+    ///
+    /// The index must be less than the number of children of the node.
+    fn expand(&mut self, node: &mut N) -> Option<usize>;
+
+    /// Plays out from a node and returns the result, as with
+    /// [`TreeNode::random_descent`](crate::iter::TreeNode::random_descent) over a tree of
+    /// moves.
+    fn simulate(&mut self, node: &mut N) -> Self::Reward;
+
+    /// Updates a node on the path with the result of the playout, given its depth below
+    /// the root, as for players who alternate by depth.
+    ///
+    /// The nodes of the path are updated from the root down to the simulated node.
+    fn backpropagate(&mut self, node: &mut N, depth: usize, reward: &Self::Reward);
+}
+
+/// Runs `iterations` iterations of Monte Carlo tree search from `root`, with `hooks`
+/// supplying the domain logic.
+///
+/// The search grows the tree in place through [`TreeNodeMut`], and walks it with loops, so
+/// the tree may grow to any depth. Returns the child-index path of the node simulated in
+/// the last iteration, or `None` if there were no iterations.
+///
+/// # Panics
+///
+/// Panics if a hook returns the index of a child that does not exist.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::mcts::{MctsHooks, mcts, uct_score};
+/// use tree_iter::tree::Node;
+///
+/// /// The statistics of an arm of a bandit with fixed payouts.
+/// #[derive(Default)]
+/// struct Stats {
+///     arm: usize,
+///     visits: u32,
+///     total: f64,
+/// }
+///
+/// struct Bandit {
+///     payouts: Vec<f64>,
+/// }
+///
+/// impl MctsHooks<Node<Stats>> for Bandit {
+///     type Reward = f64;
+///
+///     fn select(&mut self, node: &Node<Stats>) -> Option<usize> {
+///         // Arms are leaves, and the root tries every arm before choosing between them
+///         if node.value.arm > 0 || node.children.len() < self.payouts.len() {
+///             return None;
+///         }
+///         let score = |child: &Node<Stats>| {
+///             uct_score(node.value.visits, child.value.visits, child.value.total, 1.0)
+///         };
+///         (0..node.children.len())
+///             .max_by(|&a, &b| score(&node.children[a]).total_cmp(&score(&node.children[b])))
+///     }
+///
+///     fn expand(&mut self, node: &mut Node<Stats>) -> Option<usize> {
+///         if node.value.arm > 0 {
+///             return None;
+///         }
+///         let arm = node.children.len() + 1;
+///         node.push_child(Node::new(Stats { arm, ..Stats::default() }));
+///         Some(arm - 1)
+///     }
+///
+///     fn simulate(&mut self, node: &mut Node<Stats>) -> f64 {
+///         self.payouts[node.value.arm - 1]
+///     }
+///
+///     fn backpropagate(&mut self, node: &mut Node<Stats>, _: usize, reward: &f64) {
+///         node.value.visits += 1;
+///         node.value.total += reward;
+///     }
+/// }
+///
+/// let mut root = Node::new(Stats::default());
+/// mcts(&mut root, &mut Bandit { payouts: vec![0.2, 0.9, 0.5] }, 200);
+///
+/// let best = root.children.iter().max_by_key(|child| child.value.visits).unwrap();
+/// assert_eq!(best.value.arm, 2);
+/// assert_eq!(root.value.visits, 200);
+/// ```
+pub fn mcts<N: TreeNodeMut, H: MctsHooks<N>>(
+    root: &mut N,
+    hooks: &mut H,
+    iterations: usize,
+) -> Option<Vec<usize>> {
+    let mut last = None;
+    for _ in 0..iterations {
+        let mut path = Vec::new();
+        let mut node = &mut *root;
+        while let Some(index) = hooks.select(node) {
+            node = child(node, index);
+            path.push(index);
+        }
+        if let Some(index) = hooks.expand(node) {
+            node = child(node, index);
+            path.push(index);
+        }
+        let reward = hooks.simulate(node);
+
+        let mut node = &mut *root;
+        hooks.backpropagate(node, 0, &reward);
+        for (depth, &index) in path.iter().enumerate() {
+            node = child(node, index);
+            hooks.backpropagate(node, depth + 1, &reward);
+        }
+        last = Some(path);
+    }
+    last
+}
+
+/// Returns the child with the given index.
+fn child<N: TreeNodeMut>(node: &mut N, index: usize) -> &mut N {
+    node.children_mut()
+        .nth(index)
+        .expect("the hook picked a child that does not exist")
+}
+
+/// Returns the upper confidence bound of a child for selection, as in UCT: its mean reward
+/// plus an exploration bonus that shrinks as it is visited.
+///
+/// `exploration` weighs the bonus; `2f64.sqrt()` is the usual choice for rewards in
+/// `[0, 1]`. Children that were never visited score infinity, so each is tried once
+/// before any is tried twice.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::mcts::uct_score;
+///
+/// assert_eq!(uct_score(10, 0, 0.0, 1.0), f64::INFINITY);
+/// assert!(uct_score(100, 10, 5.0, 1.0) > uct_score(100, 50, 25.0, 1.0));
+/// ```
+pub fn uct_score(parent_visits: u32, visits: u32, total_reward: f64, exploration: f64) -> f64 {
+    if visits == 0 {
+        return f64::INFINITY;
+    }
+    let visits = f64::from(visits);
+    let parent_visits = f64::from(parent_visits.max(1));
+    total_reward / visits + exploration * (parent_visits.ln() / visits).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::Node;
+
+    /// Always descends into the first child, grows a chain, and records every call.
+    struct Chain {
+        calls: Vec<String>,
+    }
+
+    impl MctsHooks<Node<u32>> for Chain {
+        type Reward = u32;
+
+        fn select(&mut self, node: &Node<u32>) -> Option<usize> {
+            self.calls.push(format!("select {}", node.value));
+            (!node.children.is_empty()).then_some(0)
+        }
+
+        fn expand(&mut self, node: &mut Node<u32>) -> Option<usize> {
+            self.calls.push(format!("expand {}", node.value));
+            node.push_child(Node::new(node.value + 1));
+            Some(0)
+        }
+
+        fn simulate(&mut self, node: &mut Node<u32>) -> u32 {
+            self.calls.push(format!("simulate {}", node.value));
+            node.value * 10
+        }
+
+        fn backpropagate(&mut self, node: &mut Node<u32>, depth: usize, reward: &u32) {
+            self.calls
+                .push(format!("backpropagate {} {depth} {reward}", node.value));
+        }
+    }
+
+    #[test]
+    fn test_call_order() {
+        let mut root = Node::new(0);
+        let mut hooks = Chain { calls: Vec::new() };
+        assert_eq!(mcts(&mut root, &mut hooks, 0), None);
+        assert_eq!(mcts(&mut root, &mut hooks, 2), Some(vec![0, 0]));
+        assert_eq!(
+            hooks.calls,
+            vec![
+                "select 0",
+                "expand 0",
+                "simulate 1",
+                "backpropagate 0 0 10",
+                "backpropagate 1 1 10",
+                "select 0",
+                "select 1",
+                "expand 1",
+                "simulate 2",
+                "backpropagate 0 0 20",
+                "backpropagate 1 1 20",
+                "backpropagate 2 2 20",
+            ]
+        );
+        assert_eq!(root, crate::tree! { 0 => [1 => [2]] });
+    }
+}
@@ -0,0 +1,96 @@
+use std::fmt::{self, Write};
+
+use crate::iter::TreeNode;
+
+/// Renders a tree as a Mermaid `graph TD` flowchart, with `label` giving the label of each node.
+///
+/// Nodes are named `n0`, `n1`, ... in depth-first order. Each node is declared with its
+/// label, and every other node on the line of the edge from its parent. Labels are quoted,
+/// with `"` written as `#quot;` and line breaks as `<br/>`, so any text can be used. The
+/// tree is walked iteratively.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::mermaid::to_mermaid;
+/// use tree_iter::tree;
+///
+/// let tree = tree! { "src" => ["lib.rs", "tree.rs"] };
+/// assert_eq!(
+///     to_mermaid(&tree, |node| node.value.to_string()),
+///     "graph TD\n    n0[\"src\"]\n    n0 --> n1[\"lib.rs\"]\n    n0 --> n2[\"tree.rs\"]\n"
+/// );
+/// ```
+pub fn to_mermaid<N: TreeNode>(root: &N, label: impl FnMut(&N) -> String) -> String {
+    let mut out = String::new();
+    write_mermaid(root, label, &mut out).expect("writing to a String cannot fail");
+    out
+}
+
+/// Writes a tree as a Mermaid `graph TD` flowchart to `out`.
+///
+/// See [`to_mermaid`] for details.
+pub fn write_mermaid<N: TreeNode>(
+    root: &N,
+    mut label: impl FnMut(&N) -> String,
+    out: &mut impl Write,
+) -> fmt::Result {
+    writeln!(out, "graph TD")?;
+    let mut next_id = 0;
+    let mut stack = vec![(root, None)];
+    while let Some((node, parent_id)) = stack.pop() {
+        let id = next_id;
+        next_id += 1;
+
+        write!(out, "    ")?;
+        if let Some(parent_id) = parent_id {
+            write!(out, "n{parent_id} --> ")?;
+        }
+        writeln!(out, "n{id}[\"{}\"]", escape(&label(node)))?;
+
+        stack.extend(node.children().rev().map(|child| (child, Some(id))));
+    }
+    Ok(())
+}
+
+/// Escapes a label for use inside a quoted Mermaid node label, keeping it on one line.
+fn escape(label: &str) -> String {
+    label
+        .replace('"', "#quot;")
+        .replace("\r\n", "<br/>")
+        .replace(['\n', '\r'], "<br/>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_labels_are_quoted_and_escaped() {
+        let tree = crate::tree! { "say \"hi\"" => ["a[0]", "f(x) --> {y}"] };
+        assert_eq!(
+            to_mermaid(&tree, |node| node.value.to_string()),
+            "graph TD\n    n0[\"say #quot;hi#quot;\"]\n    n0 --> n1[\"a[0]\"]\n    \
+             n0 --> n2[\"f(x) --> {y}\"]\n"
+        );
+
+        // Ids follow depth-first order, also below the first level
+        let tree = crate::tree! { 1 => [2 => [3], 4] };
+        let mut out = String::new();
+        write_mermaid(&tree, |node| node.value.to_string(), &mut out).unwrap();
+        let edges: Vec<&str> = out.lines().skip(2).map(str::trim).collect();
+        assert_eq!(
+            edges,
+            vec!["n0 --> n1[\"2\"]", "n1 --> n2[\"3\"]", "n0 --> n3[\"4\"]"]
+        );
+    }
+
+    #[test]
+    fn test_multi_line_labels() {
+        let tree = crate::tree! { "first\nsecond" => ["a\r\nb\rc"] };
+        assert_eq!(
+            to_mermaid(&tree, |node| node.value.to_string()),
+            "graph TD\n    n0[\"first<br/>second\"]\n    n0 --> n1[\"a<br/>b<br/>c\"]\n"
+        );
+    }
+}
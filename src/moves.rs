@@ -0,0 +1,266 @@
+use std::{error::Error, fmt};
+
+use crate::tree::Node;
+
+/// Where a moved node is dropped relative to the target node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DropPosition {
+    /// Insert the node as the previous sibling of the target.
+    Before,
+    /// Insert the node as the next sibling of the target.
+    After,
+    /// Append the node as the last child of the target.
+    Inside,
+}
+
+/// The reason a move was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MoveError {
+    /// The root node cannot be moved.
+    MoveRoot,
+    /// There is no node at the source path.
+    InvalidSource,
+    /// There is no node at the target path.
+    InvalidTarget,
+    /// Nodes cannot be dropped before or after the root node.
+    RootSibling,
+    /// The node would be moved into its own subtree.
+    IntoOwnSubtree,
+    /// The new parent would exceed the number of children allowed by the policy.
+    ArityExceeded {
+        /// The maximum number of children allowed for the new parent.
+        limit: usize,
+    },
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MoveRoot => write!(f, "the root node cannot be moved"),
+            Self::InvalidSource => write!(f, "there is no node at the source path"),
+            Self::InvalidTarget => write!(f, "there is no node at the target path"),
+            Self::RootSibling => write!(f, "nodes cannot be dropped next to the root node"),
+            Self::IntoOwnSubtree => write!(f, "a node cannot be moved into its own subtree"),
+            Self::ArityExceeded { limit } => {
+                write!(f, "the new parent cannot have more than {limit} children")
+            }
+        }
+    }
+}
+
+impl Error for MoveError {}
+
+/// Policy deciding how many children a node may have.
+///
+/// This is consulted by [`Node::can_move`] and [`Node::apply_move`] for the node that
+/// would become the new parent. Closures taking a `&Node<T>` and returning an
+/// `Option<usize>` implement this trait.
+pub trait MovePolicy<T> {
+    /// Returns the maximum number of children `parent` may have, or `None` if there is no limit.
+    fn max_children(&self, parent: &Node<T>) -> Option<usize>;
+}
+
+/// A policy that puts no limit on the number of children.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Unrestricted;
+
+impl<T> MovePolicy<T> for Unrestricted {
+    fn max_children(&self, _parent: &Node<T>) -> Option<usize> {
+        None
+    }
+}
+
+impl<T, F: Fn(&Node<T>) -> Option<usize>> MovePolicy<T> for F {
+    fn max_children(&self, parent: &Node<T>) -> Option<usize> {
+        self(parent)
+    }
+}
+
+impl<T> Node<T> {
+    /// Checks whether the node at `from` can be moved relative to the node at `to`.
+    ///
+    /// A move is legal when both nodes exist, the root is not moved, the node is not moved
+    /// into its own subtree, and the new parent stays within the child limit of `policy`.
+    /// Dropping a node before or after itself is a legal no-op.
+    ///
+    /// # Parameters
+    ///
+    /// * `from` - The path of the node to move.
+    /// * `to` - The path of the target node.
+    /// * `position` - Where to drop the node relative to the target.
+    /// * `policy` - The policy limiting the number of children of the new parent.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::moves::{DropPosition, MoveError, Unrestricted};
+    /// use tree_iter::tree;
+    ///
+    /// let tree = tree! { 1 => [2 => [4], 3] };
+    ///
+    /// assert_eq!(tree.can_move(&[1], &[0, 0], DropPosition::After, &Unrestricted), Ok(()));
+    /// assert_eq!(
+    ///     tree.can_move(&[0], &[0, 0], DropPosition::Inside, &Unrestricted),
+    ///     Err(MoveError::IntoOwnSubtree)
+    /// );
+    ///
+    /// // Allow at most two children per node
+    /// let binary = |_: &_| Some(2);
+    /// assert_eq!(
+    ///     tree.can_move(&[0, 0], &[], DropPosition::Inside, &binary),
+    ///     Err(MoveError::ArityExceeded { limit: 2 })
+    /// );
+    /// ```
+    pub fn can_move(
+        &self,
+        from: &[usize],
+        to: &[usize],
+        position: DropPosition,
+        policy: &impl MovePolicy<T>,
+    ) -> Result<(), MoveError> {
+        let (_, source_parent) = from.split_last().ok_or(MoveError::MoveRoot)?;
+        self.get(from).ok_or(MoveError::InvalidSource)?;
+        self.get(to).ok_or(MoveError::InvalidTarget)?;
+        let new_parent = match position {
+            DropPosition::Inside => {
+                if to.starts_with(from) {
+                    return Err(MoveError::IntoOwnSubtree);
+                }
+                to
+            }
+            DropPosition::Before | DropPosition::After => {
+                let (_, parent) = to.split_last().ok_or(MoveError::RootSibling)?;
+                if to == from {
+                    return Ok(());
+                }
+                if to.starts_with(from) {
+                    return Err(MoveError::IntoOwnSubtree);
+                }
+                parent
+            }
+        };
+        let parent = self.get(new_parent).ok_or(MoveError::InvalidTarget)?;
+        if let Some(limit) = policy.max_children(parent) {
+            let added = usize::from(new_parent != source_parent);
+            if parent.children.len() + added > limit {
+                return Err(MoveError::ArityExceeded { limit });
+            }
+        }
+        Ok(())
+    }
+
+    /// Moves the node at `from` relative to the node at `to`.
+    ///
+    /// The move is validated with [`Node::can_move`] first, and the tree is left unchanged
+    /// if it is rejected. Paths refer to the tree as it is before the move.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::moves::{DropPosition, Unrestricted};
+    /// use tree_iter::tree;
+    ///
+    /// let mut tree = tree! { 1 => [2 => [4], 3] };
+    /// tree.apply_move(&[0, 0], &[1], DropPosition::After, &Unrestricted).unwrap();
+    /// assert_eq!(tree, tree! { 1 => [2, 3, 4] });
+    /// ```
+    pub fn apply_move(
+        &mut self,
+        from: &[usize],
+        to: &[usize],
+        position: DropPosition,
+        policy: &impl MovePolicy<T>,
+    ) -> Result<(), MoveError> {
+        self.can_move(from, to, position, policy)?;
+        if position != DropPosition::Inside && from == to {
+            return Ok(());
+        }
+
+        let (mut parent, mut index) = match position {
+            DropPosition::Inside => (to.to_vec(), self.get(to).unwrap().children.len()),
+            DropPosition::Before => (to[..to.len() - 1].to_vec(), to[to.len() - 1]),
+            DropPosition::After => (to[..to.len() - 1].to_vec(), to[to.len() - 1] + 1),
+        };
+
+        // Removing the node shifts its later siblings, which the destination may go through
+        let (&removed, source_parent) = from.split_last().unwrap();
+        let node = self.get_mut(source_parent).unwrap().remove_child(removed);
+        let depth = source_parent.len();
+        if parent.len() > depth && parent.starts_with(source_parent) && parent[depth] > removed {
+            parent[depth] -= 1;
+        } else if parent == source_parent && index > removed {
+            index -= 1;
+        }
+        self.get_mut(&parent).unwrap().insert_child(index, node);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree;
+
+    #[test]
+    fn test_move_adjusts_shifted_target() {
+        // Moving node 2 into node 3 shifts node 3 from index 1 to index 0
+        let mut tree = tree! { 1 => [2 => [4], 3 => [5]] };
+        tree.apply_move(&[0], &[1], DropPosition::Inside, &Unrestricted)
+            .unwrap();
+        assert_eq!(tree, tree! { 1 => [3 => [5, 2 => [4]]] });
+
+        // Moving a node after a later sibling
+        let mut tree = tree! { 1 => [2, 3, 4] };
+        tree.apply_move(&[0], &[2], DropPosition::After, &Unrestricted)
+            .unwrap();
+        assert_eq!(tree, tree! { 1 => [3, 4, 2] });
+    }
+
+    #[test]
+    fn test_rejected_moves_leave_tree_unchanged() {
+        let mut tree = tree! { 1 => [2 => [4], 3] };
+        let original = tree.clone();
+        let cases = [
+            (vec![], vec![0], DropPosition::Inside, MoveError::MoveRoot),
+            (
+                vec![5],
+                vec![0],
+                DropPosition::Inside,
+                MoveError::InvalidSource,
+            ),
+            (
+                vec![0],
+                vec![0, 3],
+                DropPosition::Inside,
+                MoveError::InvalidTarget,
+            ),
+            (
+                vec![0],
+                vec![],
+                DropPosition::Before,
+                MoveError::RootSibling,
+            ),
+            (
+                vec![0],
+                vec![0],
+                DropPosition::Inside,
+                MoveError::IntoOwnSubtree,
+            ),
+        ];
+        for (from, to, position, error) in cases {
+            assert_eq!(
+                tree.apply_move(&from, &to, position, &Unrestricted),
+                Err(error)
+            );
+        }
+        assert_eq!(tree, original);
+
+        // Reordering within the same parent does not add a child
+        let limit = |_: &Node<i32>| Some(2);
+        assert_eq!(
+            tree.apply_move(&[0], &[1], DropPosition::After, &limit),
+            Ok(())
+        );
+        assert_eq!(tree, tree! { 1 => [3, 2 => [4]] });
+    }
+}
@@ -0,0 +1,214 @@
+use std::{
+    error::Error,
+    fmt::{self, Display, Write},
+    str::FromStr,
+};
+
+use crate::tree::Node;
+
+/// Writes a tree as an indented outline, one value per line, indented by `indent` per level.
+///
+/// Values are expected to fit on one line; a value whose text contains a newline will not
+/// read back as a single node. The tree is walked iteratively, so it may be of any depth.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::outline::to_outline;
+/// use tree_iter::tree;
+///
+/// let tree = tree! { "src" => ["lib.rs", "tree" => ["mod.rs"]] };
+/// assert_eq!(to_outline(&tree, "  "), "src\n  lib.rs\n  tree\n    mod.rs\n");
+/// ```
+pub fn to_outline<T: Display>(root: &Node<T>, indent: &str) -> String {
+    let mut out = String::new();
+    write_outline(root, indent, &mut out).expect("writing to a String cannot fail");
+    out
+}
+
+/// Writes a tree as an indented outline to `out`.
+///
+/// See [`to_outline`] for details.
+pub fn write_outline<T: Display>(
+    root: &Node<T>,
+    indent: &str,
+    out: &mut impl Write,
+) -> fmt::Result {
+    let mut stack = vec![(root, 0)];
+    while let Some((node, depth)) = stack.pop() {
+        for _ in 0..depth {
+            out.write_str(indent)?;
+        }
+        writeln!(out, "{}", node.value)?;
+        stack.extend(node.children.iter().rev().map(|child| (child, depth + 1)));
+    }
+    Ok(())
+}
+
+/// Parses a tree from an indented outline, one value per line.
+///
+/// The indentation of the first indented line sets the unit of indentation, and every
+/// other line must be indented by a whole number of units, and by at most one more than the
+/// line before it. The first line is the root, and is the only line without indentation.
+/// Blank lines are skipped, and values are trimmed before they are parsed with `T::from_str`.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::outline::parse_outline;
+/// use tree_iter::tree;
+/// use tree_iter::tree::Node;
+///
+/// let outline = "
+/// src
+/// \tlib.rs
+/// \ttree
+/// \t\tmod.rs
+/// ";
+/// let tree: Node<String> = parse_outline(outline).unwrap();
+/// assert_eq!(tree, tree! { "src".to_string() => ["lib.rs".to_string(), "tree".to_string() => ["mod.rs".to_string()]] });
+///
+/// let error = parse_outline::<String>("a\n  b\n      c").unwrap_err();
+/// assert_eq!(error.to_string(), "line 3: indented more than one level deeper than the line before");
+/// ```
+pub fn parse_outline<T: FromStr>(input: &str) -> Result<Node<T>, OutlineError> {
+    // The nodes whose children may still follow, one per level
+    let mut open: Vec<(T, Vec<Node<T>>)> = Vec::new();
+    let mut root = None;
+    let mut unit: Option<&str> = None;
+
+    for (i, line) in input.lines().enumerate() {
+        let error = |kind| OutlineError { line: i + 1, kind };
+        let text = line.trim();
+        if text.is_empty() {
+            continue;
+        }
+
+        let indent = &line[..line.len() - line.trim_start().len()];
+        let depth = if indent.is_empty() {
+            0
+        } else {
+            let unit = *unit.get_or_insert(indent);
+            let depth = indent.len() / unit.len();
+            if indent != unit.repeat(depth) {
+                return Err(error(OutlineErrorKind::InconsistentIndent));
+            }
+            depth
+        };
+        if depth > open.len() {
+            return Err(error(OutlineErrorKind::TooDeep));
+        }
+        if depth == 0 && (root.is_some() || !open.is_empty()) {
+            return Err(error(OutlineErrorKind::MultipleRoots));
+        }
+
+        close(&mut open, depth, &mut root);
+        let value = text
+            .parse()
+            .map_err(|_| error(OutlineErrorKind::InvalidValue(text.to_string())))?;
+        open.push((value, Vec::new()));
+    }
+
+    close(&mut open, 0, &mut root);
+    root.ok_or(OutlineError {
+        line: 0,
+        kind: OutlineErrorKind::Empty,
+    })
+}
+
+/// Closes the open nodes deeper than `depth`, adding each to its parent, or setting it as
+/// the `root` if it has none.
+fn close<T>(open: &mut Vec<(T, Vec<Node<T>>)>, depth: usize, root: &mut Option<Node<T>>) {
+    while open.len() > depth {
+        let node = Node::from(open.pop().unwrap());
+        match open.last_mut() {
+            Some((_, children)) => children.push(node),
+            None => *root = Some(node),
+        }
+    }
+}
+
+/// The error returned by [`parse_outline`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OutlineError {
+    /// The line where the problem was found, counted from one, or zero if the problem is
+    /// with the outline as a whole.
+    pub line: usize,
+    /// What went wrong.
+    pub kind: OutlineErrorKind,
+}
+
+/// What went wrong when parsing an outline.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum OutlineErrorKind {
+    /// The outline has no lines.
+    Empty,
+    /// A line other than the first has no indentation.
+    MultipleRoots,
+    /// The indentation of a line is not a whole number of units.
+    InconsistentIndent,
+    /// A line is indented more than one level deeper than the line before.
+    TooDeep,
+    /// A line could not be parsed as a value.
+    InvalidValue(String),
+}
+
+impl fmt::Display for OutlineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.line > 0 {
+            write!(f, "line {}: ", self.line)?;
+        }
+        match &self.kind {
+            OutlineErrorKind::Empty => write!(f, "empty outline"),
+            OutlineErrorKind::MultipleRoots => write!(f, "more than one unindented line"),
+            OutlineErrorKind::InconsistentIndent => write!(f, "inconsistent indentation"),
+            OutlineErrorKind::TooDeep => {
+                write!(
+                    f,
+                    "indented more than one level deeper than the line before"
+                )
+            }
+            OutlineErrorKind::InvalidValue(text) => write!(f, "invalid value `{text}`"),
+        }
+    }
+}
+
+impl Error for OutlineError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let tree = crate::tree! { 1 => [2 => [4 => [7], 5], 3 => [6]] };
+        let text = to_outline(&tree, "    ");
+        assert_eq!(parse_outline::<i32>(&text).unwrap(), tree);
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |input| parse_outline::<i32>(input).unwrap_err();
+        assert_eq!(error("\n  \n").kind, OutlineErrorKind::Empty);
+        assert_eq!(
+            error("1\n  2\n3"),
+            OutlineError {
+                line: 3,
+                kind: OutlineErrorKind::MultipleRoots
+            }
+        );
+        assert_eq!(
+            error("1\n  2\n   3").kind,
+            OutlineErrorKind::InconsistentIndent
+        );
+        assert_eq!(
+            error("1\n  2\n\t\t3").kind,
+            OutlineErrorKind::InconsistentIndent
+        );
+        assert_eq!(error("  1").kind, OutlineErrorKind::TooDeep);
+        assert_eq!(
+            error("1\n  x").kind,
+            OutlineErrorKind::InvalidValue("x".to_string())
+        );
+    }
+}
@@ -0,0 +1,354 @@
+use std::{collections::HashMap, hash::Hash};
+
+use crate::{flat::Flat, iter::TreeNode};
+
+/// How many times finer than a shard's share the pieces handed out to shards are.
+const PIECES_PER_SHARD: u64 = 4;
+
+/// A connected piece of a partitioned tree.
+///
+/// A piece is the subtree rooted at `path`, minus the subtrees of the other pieces
+/// rooted below it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Piece {
+    /// The child-index path of the root of the piece.
+    pub path: Vec<usize>,
+    /// The total weight of the nodes in the piece.
+    pub weight: u64,
+}
+
+/// Cuts a tree into at most `n` connected pieces of roughly equal weight.
+///
+/// The tree is walked bottom-up, and a subtree is cut off as soon as its remaining
+/// weight reaches `total / n`. The piece containing the root is always returned first,
+/// followed by the other pieces in depth-first order. Fewer than `n` pieces are
+/// returned when the weights cannot be split that finely.
+///
+/// # Parameters
+///
+/// * `root` - The root node of the tree.
+/// * `n` - The maximum number of pieces.
+/// * `weight` - A function returning the weight of a single node.
+///
+/// # Panics
+///
+/// Panics if `n` is zero.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::partition::partition;
+/// use tree_iter::tree;
+///
+/// let tree = tree! { 1 => [2 => [4, 5], 3 => [6, 7]] };
+///
+/// let pieces = partition(&tree, 2, |_| 1);
+/// let paths: Vec<Vec<usize>> = pieces.iter().map(|piece| piece.path.clone()).collect();
+/// assert_eq!(paths, vec![vec![], vec![1]]);
+/// assert_eq!(pieces[0].weight, 4);
+/// assert_eq!(pieces[1].weight, 3);
+/// ```
+pub fn partition<N: TreeNode>(root: &N, n: usize, mut weight: impl FnMut(&N) -> u64) -> Vec<Piece> {
+    assert!(n > 0, "cannot partition a tree into zero pieces");
+    let flat = Flat::new(root);
+    let mut residual: Vec<u64> = flat.nodes.iter().map(|node| weight(node)).collect();
+    let target = (residual.iter().sum::<u64>() / n as u64).max(1);
+
+    // Children come after their parents in pre-order, so walking backwards is bottom-up
+    let mut cuts = Vec::new();
+    for id in (1..flat.len()).rev() {
+        if cuts.len() + 1 < n && residual[id] >= target {
+            cuts.push(id);
+        } else if let Some(parent) = flat.parent[id] {
+            residual[parent] += residual[id];
+        }
+    }
+    cuts.push(0);
+    cuts.reverse();
+    cuts.into_iter()
+        .map(|id| Piece {
+            path: flat.path(id),
+            weight: residual[id],
+        })
+        .collect()
+}
+
+/// An assignment of the nodes of a tree to shards.
+///
+/// The tree is cut into pieces that are small compared to a shard's share of the total weight,
+/// and each piece is owned by one shard. A node belongs to the shard of the nearest piece root
+/// at or above it.
+///
+/// Besides its path, every node has a key given by the caller, such as a database id, which
+/// identifies it across edits of the tree. Reassignment after an edit follows the keys rather
+/// than the paths, so inserting or removing a node does not move the subtrees of its later
+/// siblings, whose paths change.
+///
+/// # Type Parameters
+///
+/// * `K` - The type of the node keys, which should be unique within the tree.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::partition::assign_shards;
+/// use tree_iter::tree;
+///
+/// let mut tree = tree! { 1 => [2 => [4, 5], 3 => [6, 7]] };
+/// let shards = assign_shards(&tree, 2, |_| 1, |node| node.value);
+/// assert_eq!(shards.shard_count(), 2);
+/// let shard = shards.shard_of(&[1, 0]);
+/// assert_eq!(shards.shard_of_key(&6), Some(shard));
+///
+/// // After inserting a node in front, node 6 is at [2, 0] but keeps its shard
+/// tree.children.insert(0, tree::Node::new(8));
+/// let updated = shards.reassign(&tree, |_| 1, |node| node.value);
+/// assert_eq!(updated.shard_of(&[2, 0]), shard);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShardAssignment<K: Hash + Eq> {
+    /// The shard of each piece, keyed by the path of the piece root.
+    shards: HashMap<Vec<usize>, usize>,
+    /// The shard of every node, by its key.
+    owners: HashMap<K, usize>,
+    /// The piece roots of each shard, in depth-first order.
+    roots: Vec<Vec<Vec<usize>>>,
+    /// The total weight of each shard.
+    loads: Vec<u64>,
+}
+
+impl<K: Hash + Eq> ShardAssignment<K> {
+    /// Returns the number of shards.
+    pub fn shard_count(&self) -> usize {
+        self.roots.len()
+    }
+
+    /// Returns the shard of each piece, keyed by the path of the piece root.
+    pub fn shard_map(&self) -> &HashMap<Vec<usize>, usize> {
+        &self.shards
+    }
+
+    /// Returns the paths of the piece roots owned by `shard`, in depth-first order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shard` is out of bounds.
+    pub fn roots(&self, shard: usize) -> &[Vec<usize>] {
+        &self.roots[shard]
+    }
+
+    /// Returns the total weight of the nodes owned by `shard`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shard` is out of bounds.
+    pub fn load(&self, shard: usize) -> u64 {
+        self.loads[shard]
+    }
+
+    /// Returns the shard owning the node at `path` in the tree the assignment was made for.
+    ///
+    /// Paths that do not exist in the tree are attributed to the shard of their nearest
+    /// existing piece root.
+    pub fn shard_of(&self, path: &[usize]) -> usize {
+        (0..=path.len())
+            .rev()
+            .find_map(|len| self.shards.get(&path[..len]).copied())
+            .unwrap_or(0)
+    }
+
+    /// Returns the shard owning the node with the given key, or `None` if no node of the
+    /// tree has that key.
+    pub fn shard_of_key(&self, key: &K) -> Option<usize> {
+        self.owners.get(key).copied()
+    }
+
+    /// Computes a new assignment for an edited version of the tree.
+    ///
+    /// The tree is partitioned again, and every piece stays on the shard that owned its root
+    /// before, unless that would overload the shard. Nodes are matched to the previous
+    /// assignment by their keys, and a new node counts as owned by the shard of its parent.
+    /// This keeps reassignment small when the tree only changed a little.
+    pub fn reassign<N: TreeNode>(
+        &self,
+        root: &N,
+        weight: impl FnMut(&N) -> u64,
+        key: impl FnMut(&N) -> K,
+    ) -> Self {
+        assign(root, self.shard_count(), weight, key, Some(self))
+    }
+}
+
+/// Assigns the nodes of a tree to `n` shards of roughly equal weight.
+///
+/// Use [`ShardAssignment::reassign`] to update the assignment after the tree changes,
+/// which moves as few pieces as possible between shards.
+///
+/// # Parameters
+///
+/// * `root` - The root node of the tree.
+/// * `n` - The number of shards.
+/// * `weight` - A function returning the weight of a single node.
+/// * `key` - A function returning the key identifying a node across edits.
+///
+/// # Panics
+///
+/// Panics if `n` is zero.
+pub fn assign_shards<N: TreeNode, K: Hash + Eq>(
+    root: &N,
+    n: usize,
+    weight: impl FnMut(&N) -> u64,
+    key: impl FnMut(&N) -> K,
+) -> ShardAssignment<K> {
+    assign(root, n, weight, key, None)
+}
+
+/// Assigns pieces to shards, preferring the shards of a previous assignment.
+fn assign<N: TreeNode, K: Hash + Eq>(
+    root: &N,
+    n: usize,
+    mut weight: impl FnMut(&N) -> u64,
+    mut key: impl FnMut(&N) -> K,
+    previous: Option<&ShardAssignment<K>>,
+) -> ShardAssignment<K> {
+    assert!(n > 0, "cannot assign a tree to zero shards");
+    let flat = Flat::new(root);
+    let weights: Vec<u64> = flat.nodes.iter().map(|node| weight(node)).collect();
+    let keys: Vec<K> = flat.nodes.iter().map(|node| key(node)).collect();
+    let mut subtree_weights = weights.clone();
+    for id in (1..flat.len()).rev() {
+        if let Some(parent) = flat.parent[id] {
+            subtree_weights[parent] += subtree_weights[id];
+        }
+    }
+    let share = subtree_weights[0].div_ceil(n as u64);
+    let capacity = share + share / 4;
+
+    // The shard that owned each node before, with new nodes taking their parent's
+    let mut preferred: Vec<Option<usize>> = Vec::with_capacity(flat.len());
+    if let Some(previous) = previous {
+        for (id, key) in keys.iter().enumerate() {
+            let shard = previous
+                .shard_of_key(key)
+                .or_else(|| flat.parent[id].and_then(|parent| preferred[parent]));
+            preferred.push(shard);
+        }
+    }
+
+    // Subtrees heavier than a piece are split up: the node keeps only its own weight, and
+    // each of its children starts a new piece.
+    let granule = (share / PIECES_PER_SHARD).max(1);
+    let heavy = |id: usize| subtree_weights[id] > granule;
+    let mut pieces: Vec<(usize, Piece)> = (0..flat.len())
+        .filter(|&id| flat.parent[id].is_none_or(heavy))
+        .map(|id| {
+            let piece = Piece {
+                path: flat.path(id),
+                weight: if heavy(id) {
+                    weights[id]
+                } else {
+                    subtree_weights[id]
+                },
+            };
+            (id, piece)
+        })
+        .collect();
+
+    // Place heavy pieces first so that the light ones can even out the loads
+    pieces.sort_by(|(_, a), (_, b)| b.weight.cmp(&a.weight).then_with(|| a.path.cmp(&b.path)));
+    let mut piece_shard = vec![0; flat.len()];
+    let mut assignment = ShardAssignment {
+        shards: HashMap::new(),
+        owners: HashMap::with_capacity(flat.len()),
+        roots: vec![Vec::new(); n],
+        loads: vec![0; n],
+    };
+    for (id, piece) in pieces {
+        let lightest = (0..n).min_by_key(|&shard| assignment.loads[shard]).unwrap();
+        let shard = preferred
+            .get(id)
+            .copied()
+            .flatten()
+            .filter(|&shard| shard < n && assignment.loads[shard] + piece.weight <= capacity)
+            .unwrap_or(lightest);
+        piece_shard[id] = shard;
+        assignment.loads[shard] += piece.weight;
+        assignment.roots[shard].push(piece.path.clone());
+        assignment.shards.insert(piece.path, shard);
+    }
+    for roots in &mut assignment.roots {
+        roots.sort();
+    }
+
+    // Every node belongs to the shard of its piece, and parents come before their children
+    for (id, key) in keys.into_iter().enumerate() {
+        let shard = match flat.parent[id] {
+            Some(parent) if !heavy(parent) => piece_shard[parent],
+            _ => piece_shard[id],
+        };
+        piece_shard[id] = shard;
+        assignment.owners.insert(key, shard);
+    }
+    assignment
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::Node;
+
+    fn wide_tree(width: usize) -> Node<usize> {
+        Node::with_children(
+            0,
+            (1..=width).map(|i| Node::with_children(i, [Node::new(1000 + i)])),
+        )
+    }
+
+    #[test]
+    fn test_shards_are_balanced() {
+        let tree = wide_tree(40);
+        let shards = assign_shards(&tree, 4, |_| 1, |node| node.value);
+        let loads: Vec<u64> = (0..4).map(|shard| shards.load(shard)).collect();
+        assert_eq!(loads.iter().sum::<u64>(), 81);
+        assert!(
+            loads.iter().all(|&load| (16..=26).contains(&load)),
+            "{loads:?}"
+        );
+
+        // Every node belongs to the shard of its piece root
+        for (path, &shard) in shards.shard_map() {
+            assert!(shards.roots(shard).contains(path));
+            assert_eq!(shards.shard_of(path), shard);
+        }
+    }
+
+    #[test]
+    fn test_reassign_is_stable() {
+        let mut tree = wide_tree(40);
+        let shards = assign_shards(&tree, 4, |_| 1, |node| node.value);
+        tree.children[39].push_child(Node::new(100));
+        let updated = shards.reassign(&tree, |_| 1, |node| node.value);
+
+        let moved = (0..40)
+            .filter(|&i| shards.shard_of(&[i, 0]) != updated.shard_of(&[i, 0]))
+            .count();
+        assert!(moved <= 4, "{moved} children changed shards");
+    }
+
+    #[test]
+    fn test_insert_keeps_other_subtrees() {
+        let mut tree = wide_tree(40);
+        let shards = assign_shards(&tree, 4, |_| 1, |node| node.value);
+        tree.insert_child(0, Node::with_children(500, [Node::new(501)]));
+        let updated = shards.reassign(&tree, |_| 1, |node| node.value);
+
+        // Every later sibling is now one place further, but stays on its shard
+        for i in 1..=40 {
+            assert_eq!(updated.shard_of_key(&i), shards.shard_of_key(&i));
+            assert_eq!(updated.shard_of_key(&(1000 + i)), shards.shard_of_key(&i));
+            assert_eq!(updated.shard_of(&[i, 0]), shards.shard_of(&[i - 1, 0]));
+        }
+        assert_eq!(updated.shard_of_key(&501), updated.shard_of_key(&500));
+        assert_eq!(updated.shard_of_key(&7777), None);
+    }
+}
@@ -0,0 +1,356 @@
+use std::{borrow::Borrow, error::Error, fmt, ops::Deref, str::FromStr};
+
+/// A child-index path from a root to one of its descendants.
+///
+/// This is a `Vec<usize>` with a type of its own: each index picks a child of the node
+/// reached so far, and the empty path is the root itself. It dereferences to `[usize]`, so
+/// it can be passed wherever a raw path is taken, such as [`Node::get`],
+/// [`Node::get_mut`] and [`Node::graft_at`].
+///
+/// Paths are displayed with their indices joined by dots, as in `0.2.1`, with the root
+/// displayed as the empty string, and parse back from the same form. They are ordered
+/// like the nodes they lead to in depth-first order.
+///
+/// [`Node::get`]: crate::tree::Node::get
+/// [`Node::get_mut`]: crate::tree::Node::get_mut
+/// [`Node::graft_at`]: crate::tree::Node::graft_at
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::path::TreePath;
+/// use tree_iter::tree;
+///
+/// let mut tree = tree! { 1 => [2, 3 => [4, 5]] };
+/// let path: TreePath = "1.0".parse().unwrap();
+/// assert_eq!(tree.get(&path).map(|node| node.value), Some(4));
+///
+/// let sibling = path.parent().unwrap().child(2);
+/// assert_eq!(sibling.to_string(), "1.2");
+/// assert!(tree.graft_at(&sibling, tree! { 6 }).is_ok());
+/// assert_eq!(tree, tree! { 1 => [2, 3 => [4, 5, 6]] });
+///
+/// assert!(TreePath::root().is_ancestor_of(&sibling));
+/// assert!(!sibling.is_ancestor_of(&sibling));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TreePath(Vec<usize>);
+
+impl TreePath {
+    /// Returns the path of the root.
+    pub const fn root() -> Self {
+        Self(Vec::new())
+    }
+
+    /// Returns whether this is the path of the root.
+    pub fn is_root(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the depth of the node the path leads to, with the root at depth 0.
+    pub fn depth(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns the path of the parent, or `None` for the root.
+    pub fn parent(&self) -> Option<Self> {
+        self.0.split_last().map(|(_, parent)| Self(parent.to_vec()))
+    }
+
+    /// Returns the path of the child with the given index.
+    pub fn child(&self, index: usize) -> Self {
+        let mut path = Vec::with_capacity(self.0.len() + 1);
+        path.extend_from_slice(&self.0);
+        path.push(index);
+        Self(path)
+    }
+
+    /// Returns whether the node this path leads to is a proper ancestor of the node `other`
+    /// leads to, that is, whether this path is a shorter prefix of `other`.
+    pub fn is_ancestor_of(&self, other: &TreePath) -> bool {
+        self.0.len() < other.0.len() && other.0.starts_with(&self.0)
+    }
+
+    /// Returns the path of the lowest common ancestor of the nodes this path and `other`
+    /// lead to, which is their longest common prefix.
+    ///
+    /// A node counts as its own ancestor. Both paths are assumed to lead to nodes of the
+    /// same tree; see [`LcaIndex`](crate::lca::LcaIndex) for repeated queries that also
+    /// check this.
+    pub fn common_ancestor(&self, other: &TreePath) -> TreePath {
+        self.0
+            .iter()
+            .zip(&other.0)
+            .take_while(|(a, b)| a == b)
+            .map(|(&index, _)| index)
+            .collect()
+    }
+
+    /// Returns the relative path leading from the node this path leads to, to the node
+    /// `to` leads to, going up to their lowest common ancestor and down from there.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::path::{RelPath, TreePath};
+    ///
+    /// let from: TreePath = "0.2.1".parse().unwrap();
+    /// let to: TreePath = "0.3".parse().unwrap();
+    /// let link = from.relative_path(&to);
+    /// assert_eq!(link, RelPath { ups: 2, down: vec![3] });
+    /// assert_eq!(link.to_string(), "../../3");
+    /// assert_eq!(from.resolve(&link), Some(to));
+    ///
+    /// // The link still holds after both nodes move under another parent
+    /// let moved: TreePath = "4.0.2.1".parse().unwrap();
+    /// assert_eq!(moved.resolve(&link).unwrap().to_string(), "4.0.3");
+    /// ```
+    pub fn relative_path(&self, to: &TreePath) -> RelPath {
+        let common = self.common_ancestor(to).depth();
+        RelPath {
+            ups: self.depth() - common,
+            down: to.0[common..].to_vec(),
+        }
+    }
+
+    /// Returns the path reached by following `rel` from the node this path leads to, or
+    /// `None` if `rel` goes up past the root.
+    pub fn resolve(&self, rel: &RelPath) -> Option<TreePath> {
+        let base = self.0.len().checked_sub(rel.ups)?;
+        let mut path = Vec::with_capacity(base + rel.down.len());
+        path.extend_from_slice(&self.0[..base]);
+        path.extend_from_slice(&rel.down);
+        Some(Self(path))
+    }
+
+    /// Returns the indices of the path.
+    pub fn into_vec(self) -> Vec<usize> {
+        self.0
+    }
+}
+
+impl Deref for TreePath {
+    type Target = [usize];
+
+    fn deref(&self) -> &[usize] {
+        &self.0
+    }
+}
+
+impl AsRef<[usize]> for TreePath {
+    fn as_ref(&self) -> &[usize] {
+        &self.0
+    }
+}
+
+impl Borrow<[usize]> for TreePath {
+    fn borrow(&self) -> &[usize] {
+        &self.0
+    }
+}
+
+impl From<Vec<usize>> for TreePath {
+    fn from(path: Vec<usize>) -> Self {
+        Self(path)
+    }
+}
+
+impl From<&[usize]> for TreePath {
+    fn from(path: &[usize]) -> Self {
+        Self(path.to_vec())
+    }
+}
+
+impl From<TreePath> for Vec<usize> {
+    fn from(path: TreePath) -> Self {
+        path.0
+    }
+}
+
+impl FromIterator<usize> for TreePath {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl fmt::Display for TreePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, index) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(".")?;
+            }
+            write!(f, "{index}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for TreePath {
+    type Err = ParsePathError;
+
+    /// Parses indices joined by dots, as in `0.2.1`, or the empty string for the root.
+    fn from_str(text: &str) -> Result<Self, ParsePathError> {
+        if text.is_empty() {
+            return Ok(Self::root());
+        }
+        text.split('.')
+            .map(|segment| {
+                segment.parse().map_err(|_| ParsePathError {
+                    segment: segment.to_string(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// A path from one node to another relative to the first: some steps up to ancestors,
+/// then child indices down, created by [`TreePath::relative_path`].
+///
+/// Links stored as relative paths stay valid when the subtree holding both ends moves
+/// elsewhere in the tree. They are displayed as a `..` for each step up followed by the
+/// indices down joined by dots, all separated by slashes, as in `../../3.1`, with the path
+/// from a node to itself displayed as the empty string, and parse back from the same form.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct RelPath {
+    /// The number of steps up to the common ancestor.
+    pub ups: usize,
+    /// The child indices down from the common ancestor.
+    pub down: Vec<usize>,
+}
+
+impl fmt::Display for RelPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for i in 0..self.ups {
+            if i > 0 {
+                f.write_str("/")?;
+            }
+            f.write_str("..")?;
+        }
+        if !self.down.is_empty() {
+            if self.ups > 0 {
+                f.write_str("/")?;
+            }
+            for (i, index) in self.down.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(".")?;
+                }
+                write!(f, "{index}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for RelPath {
+    type Err = ParsePathError;
+
+    /// Parses `..` steps and then a path down, separated by slashes, as in `../../3.1`.
+    fn from_str(text: &str) -> Result<Self, ParsePathError> {
+        let mut rel = Self::default();
+        let mut parts = text.split('/').peekable();
+        while parts.next_if_eq(&"..").is_some() {
+            rel.ups += 1;
+        }
+        match (parts.next(), parts.next()) {
+            (None, _) => Ok(rel),
+            (Some(""), None) if rel.ups == 0 => Ok(rel),
+            (Some(down), None) => {
+                rel.down = down.parse::<TreePath>()?.into_vec();
+                if rel.down.is_empty() {
+                    return Err(ParsePathError {
+                        segment: String::new(),
+                    });
+                }
+                Ok(rel)
+            }
+            (Some(_), Some(segment)) => Err(ParsePathError {
+                segment: segment.to_string(),
+            }),
+        }
+    }
+}
+
+/// The error returned when a string is not a [`TreePath`] or a [`RelPath`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ParsePathError {
+    /// The first part between dots that is not an index, or for a [`RelPath`], the first
+    /// part between slashes out of place.
+    pub segment: String,
+}
+
+impl fmt::Display for ParsePathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid path index `{}`", self.segment)
+    }
+}
+
+impl Error for ParsePathError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display() {
+        for text in ["", "0", "0.2.1", "10.0.3"] {
+            let path: TreePath = text.parse().unwrap();
+            assert_eq!(path.to_string(), text);
+        }
+        assert_eq!(
+            "0.2.1".parse::<TreePath>(),
+            Ok(TreePath::from(vec![0, 2, 1]))
+        );
+        assert_eq!(
+            "0..1".parse::<TreePath>(),
+            Err(ParsePathError {
+                segment: String::new()
+            })
+        );
+        assert_eq!(
+            "1.-2".parse::<TreePath>().unwrap_err().to_string(),
+            "invalid path index `-2`"
+        );
+
+        let path = TreePath::from(vec![1, 2]);
+        assert_eq!(path.depth(), 2);
+        assert_eq!(
+            path.parent().and_then(|p| p.parent()),
+            Some(TreePath::root())
+        );
+        assert!(TreePath::root().parent().is_none());
+        assert!(!path.child(0).is_ancestor_of(&path));
+        assert!(TreePath::from(vec![1]).is_ancestor_of(&path.child(0)));
+        assert!(TreePath::from(vec![0, 5]) < TreePath::from(vec![1]));
+        assert_eq!(
+            path.child(3)
+                .common_ancestor(&TreePath::from(vec![1, 2, 0, 4])),
+            path
+        );
+        assert_eq!(
+            path.common_ancestor(&TreePath::from(vec![0])),
+            TreePath::root()
+        );
+    }
+
+    #[test]
+    fn test_relative_paths() {
+        let paths: Vec<TreePath> = ["", "0", "0.1", "0.1.2", "0.2", "1", "1.0.0"]
+            .iter()
+            .map(|text| text.parse().unwrap())
+            .collect();
+        for from in &paths {
+            for to in &paths {
+                let rel = from.relative_path(to);
+                assert_eq!(from.resolve(&rel).as_ref(), Some(to));
+                assert_eq!(rel.to_string().parse::<RelPath>(), Ok(rel));
+            }
+        }
+        assert_eq!(paths[3].relative_path(&paths[1]).to_string(), "../..");
+        assert_eq!(paths[1].relative_path(&paths[3]).to_string(), "1.2");
+        assert_eq!(paths[2].relative_path(&paths[2]).to_string(), "");
+        assert_eq!(paths[1].resolve(&"../../0".parse().unwrap()), None);
+        for text in ["../", "1/..", "0/1", "../x"] {
+            assert!(text.parse::<RelPath>().is_err(), "{text}");
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+
+use crate::iter::TreeNode;
+
+/// The subtrees bound to the holes of a [`Pattern`] by a match, by hole name.
+pub type Bindings<'p, 'n, N> = HashMap<&'p str, &'n N>;
+
+/// A tree-shaped pattern with wildcards and named holes, matched against any [`TreeNode`].
+///
+/// A pattern describes the top of a subtree: its nodes fix the values and the exact number
+/// of children of the nodes they match, and the leaves of the pattern may be holes that
+/// match whole subtrees. Matching a pattern against a node gives the subtree each hole
+/// matched, which is what rewriting rules such as `add(X, 0) => X` need.
+///
+/// How pattern values compare to nodes is up to the caller, so one pattern type serves
+/// every kind of tree.
+///
+/// # Type Parameters
+///
+/// * `T` - The type of values in the pattern.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::pattern::Pattern;
+/// use tree_iter::tree;
+/// use tree_iter::tree::Node;
+///
+/// // `add(X, 0)`
+/// let pattern = Pattern::Value("add", vec![Pattern::hole("X"), Pattern::leaf("0")]);
+/// let same = |value: &&str, node: &Node<&str>| *value == node.value;
+///
+/// let term = tree! { "mul" => ["add" => ["mul" => ["x", "y"], "0"], "add" => ["1", "2"]] };
+/// assert!(pattern.match_node(&term, same).is_none());
+///
+/// let matches = pattern.find_matches(&term, same);
+/// assert_eq!(matches.len(), 1);
+/// let (path, bindings) = &matches[0];
+/// assert_eq!(path, &vec![0]);
+/// assert_eq!(bindings["X"], &tree! { "mul" => ["x", "y"] });
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Pattern<T> {
+    /// Matches a node with a value that compares equal to this one, and with exactly as
+    /// many children as given, each matching its pattern.
+    Value(T, Vec<Pattern<T>>),
+    /// Matches a node with any value and with exactly as many children as given, each
+    /// matching its pattern.
+    AnyValue(Vec<Pattern<T>>),
+    /// Matches any subtree and binds it to the given name.
+    ///
+    /// Each name should occur once in a pattern; if it occurs more than once, it is bound
+    /// to the subtree matched by its first occurrence in depth-first order.
+    Hole(String),
+    /// Matches any subtree without binding it.
+    Wildcard,
+}
+
+impl<T> Pattern<T> {
+    /// Creates a pattern matching a node with the given value and no children.
+    pub fn leaf(value: T) -> Self {
+        Self::Value(value, Vec::new())
+    }
+
+    /// Creates a hole with the given name.
+    pub fn hole(name: impl Into<String>) -> Self {
+        Self::Hole(name.into())
+    }
+
+    /// Returns the names of the holes of the pattern, in depth-first order.
+    pub fn holes(&self) -> Vec<&str> {
+        let mut holes = Vec::new();
+        let mut stack = vec![self];
+        while let Some(pattern) = stack.pop() {
+            match pattern {
+                Self::Value(_, children) | Self::AnyValue(children) => {
+                    stack.extend(children.iter().rev());
+                }
+                Self::Hole(name) => holes.push(name.as_str()),
+                Self::Wildcard => {}
+            }
+        }
+        holes
+    }
+
+    /// Matches the pattern against the subtree rooted at `node`, with `eq` deciding whether
+    /// a pattern value matches the value of a node.
+    ///
+    /// Returns the bindings of the holes, or `None` if the subtree does not match. The
+    /// pattern and the subtree are walked iteratively.
+    pub fn match_node<'p, 'n, N: TreeNode>(
+        &'p self,
+        node: &'n N,
+        mut eq: impl FnMut(&T, &N) -> bool,
+    ) -> Option<Bindings<'p, 'n, N>> {
+        self.match_with(node, &mut eq)
+    }
+
+    /// Matches the pattern against every subtree of the tree rooted at `root`, returning the
+    /// child-index path and the bindings of each match, in depth-first order.
+    ///
+    /// Matches may overlap, as when one matching subtree contains another.
+    pub fn find_matches<'p, 'n, N: TreeNode>(
+        &'p self,
+        root: &'n N,
+        mut eq: impl FnMut(&T, &N) -> bool,
+    ) -> Vec<(Vec<usize>, Bindings<'p, 'n, N>)> {
+        let mut matches = Vec::new();
+        let mut path = Vec::new();
+        // Each node with its depth and its index among its siblings
+        let mut stack = vec![(root, 0, 0)];
+        while let Some((node, depth, index)) = stack.pop() {
+            if depth > 0 {
+                path.truncate(depth - 1);
+                path.push(index);
+            }
+            if let Some(bindings) = self.match_with(node, &mut eq) {
+                matches.push((path.clone(), bindings));
+            }
+            let start = stack.len();
+            stack.extend(
+                node.children()
+                    .enumerate()
+                    .map(|(i, child)| (child, depth + 1, i)),
+            );
+            stack[start..].reverse();
+        }
+        matches
+    }
+
+    /// Matches the pattern against the subtree rooted at `node`.
+    fn match_with<'p, 'n, N: TreeNode>(
+        &'p self,
+        node: &'n N,
+        eq: &mut impl FnMut(&T, &N) -> bool,
+    ) -> Option<Bindings<'p, 'n, N>> {
+        let mut bindings = HashMap::new();
+        let mut stack = vec![(self, node)];
+        while let Some((pattern, node)) = stack.pop() {
+            let children = match pattern {
+                Self::Value(value, children) => {
+                    if !eq(value, node) {
+                        return None;
+                    }
+                    children
+                }
+                Self::AnyValue(children) => children,
+                Self::Hole(name) => {
+                    bindings.entry(name.as_str()).or_insert(node);
+                    continue;
+                }
+                Self::Wildcard => continue,
+            };
+            let start = stack.len();
+            let mut nodes = node.children();
+            for child in children {
+                stack.push((child, nodes.next()?));
+            }
+            if nodes.next().is_some() {
+                return None;
+            }
+            stack[start..].reverse();
+        }
+        Some(bindings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::Node;
+
+    #[test]
+    fn test_shapes_and_holes() {
+        let same = |value: &i32, node: &Node<i32>| *value == node.value;
+        let tree = crate::tree! { 1 => [2 => [3, 4], 2 => [3], 2 => [5, 6]] };
+
+        // `_(2(A, B))` with any root value: children counts must agree exactly
+        let pattern = Pattern::AnyValue(vec![
+            Pattern::Wildcard,
+            Pattern::Wildcard,
+            Pattern::Value(2, vec![Pattern::hole("A"), Pattern::hole("B")]),
+        ]);
+        let bindings = pattern.match_node(&tree, same).unwrap();
+        assert_eq!(bindings["A"].value, 5);
+        assert_eq!(bindings["B"].value, 6);
+        assert_eq!(pattern.holes(), vec!["A", "B"]);
+
+        let pair = Pattern::Value(2, vec![Pattern::hole("A"), Pattern::Wildcard]);
+        let paths: Vec<Vec<usize>> = pair
+            .find_matches(&tree, same)
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect();
+        assert_eq!(paths, vec![vec![0], vec![2]]);
+
+        // A repeated name keeps its first binding
+        let repeated = Pattern::AnyValue(vec![Pattern::hole("X"), Pattern::hole("X")]);
+        let bindings = repeated.match_node(&tree.children[0], same).unwrap();
+        assert_eq!(bindings["X"].value, 3);
+        assert!(Pattern::leaf(3).match_node(&tree, same).is_none());
+    }
+}
@@ -0,0 +1,146 @@
+use std::cell::RefCell;
+
+use crate::tree::Node;
+
+/// A pool of recycled child buffers for building [`Node`] trees.
+///
+/// The heap allocations of a `Node` tree are the vectors holding each node's children.
+/// When many short-lived trees are built and dropped, such as one parse tree per request,
+/// handing finished trees to [`NodePool::recycle`] keeps those buffers around, and
+/// [`Node::new_in`] reuses them for new nodes instead of allocating.
+///
+/// The pool uses interior mutability and is meant to be owned by a single thread.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::pool::NodePool;
+/// use tree_iter::tree::Node;
+///
+/// let pool = NodePool::new();
+/// for request in 0..3 {
+///     let mut tree = Node::new_in(request, &pool);
+///     tree.push_child(Node::new_in(request * 10, &pool));
+///     // ... use the tree ...
+///     pool.recycle(tree);
+/// }
+/// assert_eq!(pool.len(), 1);
+/// ```
+#[derive(Debug)]
+pub struct NodePool<T> {
+    /// Empty child buffers with spare capacity.
+    buffers: RefCell<Vec<Vec<Node<T>>>>,
+}
+
+impl<T> NodePool<T> {
+    /// Creates an empty pool.
+    pub fn new() -> Self {
+        Self {
+            buffers: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Returns the number of buffers available for reuse.
+    pub fn len(&self) -> usize {
+        self.buffers.borrow().len()
+    }
+
+    /// Returns `true` if there are no buffers available for reuse.
+    pub fn is_empty(&self) -> bool {
+        self.buffers.borrow().is_empty()
+    }
+
+    /// Frees every buffer held by the pool.
+    pub fn clear(&self) {
+        self.buffers.borrow_mut().clear();
+    }
+
+    /// Takes a buffer from the pool, or creates an unallocated one if the pool is empty.
+    ///
+    /// The returned vector is always empty.
+    pub fn take_buffer(&self) -> Vec<Node<T>> {
+        self.buffers.borrow_mut().pop().unwrap_or_default()
+    }
+
+    /// Dismantles a tree, keeping the allocated child buffers of all its nodes for reuse.
+    ///
+    /// The values are dropped. Like dropping a tree, this works iteratively and handles
+    /// trees of any depth.
+    pub fn recycle(&self, tree: Node<T>) {
+        let mut buffers = self.buffers.borrow_mut();
+        let mut stack = vec![tree];
+        while let Some(node) = stack.pop() {
+            let (_, mut children) = node.into_parts();
+            stack.append(&mut children);
+            if children.capacity() > 0 {
+                buffers.push(children);
+            }
+        }
+    }
+}
+
+impl<T> Default for NodePool<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Node<T> {
+    /// Creates a new node with no children, reusing a child buffer from `pool` if one is available.
+    ///
+    /// # Parameters
+    ///
+    /// * `value` - The value to store in the node.
+    /// * `pool` - The pool to take the child buffer from.
+    pub fn new_in(value: T, pool: &NodePool<T>) -> Self {
+        Self {
+            value,
+            children: pool.take_buffer(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffers_are_reused() {
+        let pool = NodePool::new();
+        assert!(pool.is_empty());
+
+        // Only nodes with children have an allocated buffer to give back
+        let tree = crate::tree! { 1 => [2 => [4, 5], 3 => [6]] };
+        pool.recycle(tree);
+        assert_eq!(pool.len(), 3);
+
+        let mut node = Node::new_in(7, &pool);
+        assert!(node.children.is_empty());
+        assert!(node.children.capacity() > 0);
+        let buffer = node.children.as_ptr();
+        node.push_child(Node::new(8));
+        assert_eq!(node.children.as_ptr(), buffer);
+        assert_eq!(pool.len(), 2);
+
+        pool.clear();
+        assert!(pool.is_empty());
+        assert_eq!(Node::new_in(9, &pool).children.capacity(), 0);
+
+        // Deep trees are taken apart without recursion
+        let mut chain = Node::new(0);
+        for i in 1..100_000 {
+            chain = Node::with_children(i, [chain]);
+        }
+        pool.recycle(chain);
+        assert_eq!(pool.len(), 99_999);
+    }
+
+    #[test]
+    fn test_default_needs_no_default_values() {
+        struct Opaque;
+
+        let pool: NodePool<Opaque> = NodePool::default();
+        pool.recycle(Node::with_children(Opaque, [Node::new(Opaque)]));
+        assert_eq!(pool.len(), 1);
+    }
+}
@@ -0,0 +1,202 @@
+use std::ops::Range;
+
+use crate::{flat::Flat, iter::TreeNode};
+
+/// The nodes of a tree numbered in depth-first order, with conversions between these ids
+/// and child-index paths.
+///
+/// The root is node 0, every parent comes before its children, and the subtree of every
+/// node is the interval of ids starting at the node itself. The indexes built over a whole
+/// tree, such as [`LcaIndex`], [`AncestorTable`], [`SubtreeSums`] and [`InvertedIndex`],
+/// refer to nodes by these ids and expose their numbering through an `ids` method. The
+/// numbering owns its data and captures the shape of the tree when it is built.
+///
+/// Looking up the id of a path takes time in its length, and the path of an id in the
+/// depth of the node, so paths are only built when asked for.
+///
+/// [`LcaIndex`]: crate::lca::LcaIndex
+/// [`AncestorTable`]: crate::ancestors::AncestorTable
+/// [`SubtreeSums`]: crate::fenwick::SubtreeSums
+/// [`InvertedIndex`]: crate::inverted::InvertedIndex
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::preorder::PreorderIds;
+/// use tree_iter::tree;
+///
+/// let tree = tree! { 1 => [2 => [4, 5], 3] };
+/// let ids = PreorderIds::new(&tree);
+/// assert_eq!(ids.len(), 5);
+/// assert_eq!(ids.id(&[0, 1]), Some(3));
+/// assert_eq!(ids.id(&[0, 2]), None);
+/// assert_eq!(ids.path(4), vec![1]);
+/// assert_eq!(ids.parent(3), Some(1));
+/// assert_eq!(ids.children(1), &[2, 3]);
+/// assert_eq!(ids.range(1), 1..4);
+/// ```
+#[derive(Debug, Clone)]
+pub struct PreorderIds {
+    /// The parent of each node, or `None` for the root.
+    parent: Vec<Option<usize>>,
+    /// The position of each node among its siblings.
+    sibling_index: Vec<usize>,
+    /// The depth of each node, with the root at depth 0.
+    depth: Vec<usize>,
+    /// The id following the last node of each node's subtree.
+    subtree_end: Vec<usize>,
+    /// The children of every node, grouped by parent in depth-first order.
+    children: Vec<usize>,
+    /// Where the children of each node start in `children`, followed by the total.
+    child_start: Vec<usize>,
+}
+
+impl PreorderIds {
+    /// Numbers the nodes of the tree rooted at `root`.
+    pub fn new<N: TreeNode>(root: &N) -> Self {
+        Self::from_flat(&Flat::new(root))
+    }
+
+    /// Numbers the nodes of a flattened tree.
+    pub(crate) fn from_flat<N>(flat: &Flat<'_, N>) -> Self {
+        let mut child_start = vec![0; flat.len() + 1];
+        for parent in flat.parent.iter().flatten() {
+            child_start[parent + 1] += 1;
+        }
+        for i in 1..child_start.len() {
+            child_start[i] += child_start[i - 1];
+        }
+        // Children come in increasing order, so each one goes after its earlier siblings
+        let mut next = child_start.clone();
+        let mut children = vec![0; flat.len().saturating_sub(1)];
+        for (id, parent) in flat.parent.iter().enumerate() {
+            if let Some(parent) = *parent {
+                children[next[parent]] = id;
+                next[parent] += 1;
+            }
+        }
+        Self {
+            parent: flat.parent.clone(),
+            sibling_index: flat.sibling_index.clone(),
+            depth: flat.depth.clone(),
+            subtree_end: flat.subtree_end.clone(),
+            children,
+            child_start,
+        }
+    }
+
+    /// Returns the number of nodes in the tree.
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    /// Always returns `false`, as a tree has at least one node.
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Returns the id of the node at `path`, or `None` if there is no node there.
+    pub fn id(&self, path: &[usize]) -> Option<usize> {
+        path.iter()
+            .try_fold(0, |id, &index| self.children(id).get(index).copied())
+    }
+
+    /// Returns the child-index path of the node with the given id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is not less than [`PreorderIds::len`].
+    pub fn path(&self, id: usize) -> Vec<usize> {
+        let mut path = Vec::with_capacity(self.depth[id]);
+        let mut node = id;
+        while let Some(parent) = self.parent[node] {
+            path.push(self.sibling_index[node]);
+            node = parent;
+        }
+        path.reverse();
+        path
+    }
+
+    /// Returns the parent of the node with the given id, or `None` for the root.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is not less than [`PreorderIds::len`].
+    pub fn parent(&self, id: usize) -> Option<usize> {
+        self.parent[id]
+    }
+
+    /// Returns the children of the node with the given id, in order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is not less than [`PreorderIds::len`].
+    pub fn children(&self, id: usize) -> &[usize] {
+        &self.children[self.child_start[id]..self.child_start[id + 1]]
+    }
+
+    /// Returns the depth of the node with the given id, with the root at depth 0.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is not less than [`PreorderIds::len`].
+    pub fn depth(&self, id: usize) -> usize {
+        self.depth[id]
+    }
+
+    /// Returns the ids of the nodes in the subtree of the node with the given id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is not less than [`PreorderIds::len`].
+    pub fn range(&self, id: usize) -> Range<usize> {
+        id..self.subtree_end[id]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{traversal_order::DepthFirst, tree::Node};
+
+    #[test]
+    fn test_ids_and_paths() {
+        let tree = Node::unfold::<DepthFirst, _>(1u32, |n| {
+            let children = if n < 60 {
+                (0..n % 4).map(|i| 4 * n + i).collect()
+            } else {
+                vec![]
+            };
+            (n, children)
+        });
+        let ids = PreorderIds::new(&tree);
+        assert_eq!(ids.len(), tree.count());
+        for (id, node) in tree.iter::<DepthFirst>().enumerate() {
+            let path = ids.path(id);
+            assert_eq!(ids.id(&path), Some(id));
+            assert!(std::ptr::eq(tree.get(&path).unwrap(), node));
+            assert_eq!(ids.depth(id), path.len());
+            assert_eq!(ids.range(id).len(), node.count());
+            assert_eq!(ids.children(id).len(), node.children.len());
+            for (i, &child) in ids.children(id).iter().enumerate() {
+                assert_eq!(ids.parent(child), Some(id));
+                assert_eq!(ids.id(&[&path[..], &[i]].concat()), Some(child));
+            }
+            // Just past the last child, and below a leaf
+            let past = [&path[..], &[node.children.len()]].concat();
+            assert_eq!(ids.id(&past), None);
+        }
+        assert_eq!(ids.parent(0), None);
+        assert_eq!(ids.id(&[usize::MAX]), None);
+
+        let pair = PreorderIds::new(&crate::tree! { 1 => [2] });
+        assert_eq!(pair.id(&[0]), Some(1));
+        assert_eq!(pair.id(&[0, 1]), None);
+        assert_eq!(pair.id(&[1]), None);
+
+        let single = PreorderIds::new(&Node::new(()));
+        assert_eq!(single.len(), 1);
+        assert_eq!(single.children(0), &[] as &[usize]);
+        assert_eq!(single.id(&[0]), None);
+    }
+}
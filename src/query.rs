@@ -0,0 +1,323 @@
+use std::{fmt, rc::Rc};
+
+use crate::iter::TreeNode;
+
+/// Decides whether a node matches a step.
+type Predicate<'a, N> = Box<dyn Fn(&N) -> bool + 'a>;
+
+/// How a step relates to the node matched by the step before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Combinator {
+    /// The node is a child of the previous match.
+    Child,
+    /// The node is a proper descendant of the previous match.
+    Descendant,
+}
+
+/// One step of a [`Selector`].
+struct Step<'a, N> {
+    /// How the node relates to the previous match.
+    combinator: Combinator,
+    /// The predicates the node must satisfy.
+    predicates: Vec<Predicate<'a, N>>,
+    /// The index the node must have among its siblings, if any.
+    nth: Option<usize>,
+}
+
+impl<N> Step<'_, N> {
+    /// Returns whether the node, with the given index among its siblings, matches the step.
+    fn matches(&self, node: &N, index: Option<usize>) -> bool {
+        self.nth.is_none_or(|nth| index == Some(nth))
+            && self.predicates.iter().all(|predicate| predicate(node))
+    }
+}
+
+/// A pattern picking nodes out of a tree by their own properties and those of their
+/// ancestors, in the manner of CSS selectors.
+///
+/// A selector is a chain of steps, each with predicates on a node. The first step starts
+/// either at the root, with [`Selector::root`], or anywhere in the tree, with
+/// [`Selector::any`]. Each further step matches a [child](Selector::child) or a proper
+/// [descendant](Selector::descendant) of a node matched by the step before it, and
+/// [`Selector::and`] and [`Selector::nth_child`] narrow down the last step. A node matches
+/// the selector if it matches the last step.
+///
+/// [`Selector::query`] walks a tree iteratively and lazily, tracking which steps are open
+/// at each node, so each node is visited once whatever the selector, and subtrees in
+/// which no step can match any more are skipped.
+///
+/// # Type Parameters
+///
+/// * `'a` - The lifetime of the predicates.
+/// * `N` - The type of node.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::query::Selector;
+/// use tree_iter::tree;
+/// use tree_iter::tree::Node;
+///
+/// let doc = tree! {
+///     "body" => ["ul" => ["li" => ["a"], "li"], "div" => ["ul" => ["li" => ["span" => ["a"]]]]]
+/// };
+/// let is = |name: &'static str| move |node: &Node<&str>| node.value == name;
+///
+/// // `ul > li a`: links anywhere inside list items
+/// let links = Selector::any(is("ul")).child(is("li")).descendant(is("a"));
+/// let paths: Vec<Vec<usize>> = links.query(&doc).map(|(_, path)| path).collect();
+/// assert_eq!(paths, vec![vec![0, 0, 0], vec![1, 0, 0, 0, 0]]);
+///
+/// // `body > ul > li:nth-child(2)`, counting from zero
+/// let second = Selector::root(is("body")).child(is("ul")).child(is("li")).nth_child(1);
+/// assert_eq!(second.query(&doc).map(|(_, path)| path).collect::<Vec<_>>(), vec![vec![0, 1]]);
+/// ```
+pub struct Selector<'a, N> {
+    /// The steps, from the outermost to the one picking the matches.
+    steps: Vec<Step<'a, N>>,
+}
+
+impl<'a, N: TreeNode> Selector<'a, N> {
+    /// Creates a selector matching the root, if it satisfies `predicate`.
+    pub fn root(predicate: impl Fn(&N) -> bool + 'a) -> Self {
+        Self::start(Combinator::Child, predicate)
+    }
+
+    /// Creates a selector matching any node that satisfies `predicate`, including the root.
+    pub fn any(predicate: impl Fn(&N) -> bool + 'a) -> Self {
+        Self::start(Combinator::Descendant, predicate)
+    }
+
+    /// Creates a selector with one step, related by `combinator` to a virtual parent of the
+    /// root.
+    fn start(combinator: Combinator, predicate: impl Fn(&N) -> bool + 'a) -> Self {
+        Self { steps: Vec::new() }.push(combinator, predicate)
+    }
+
+    /// Adds a step matching the children of the current matches that satisfy `predicate`.
+    pub fn child(self, predicate: impl Fn(&N) -> bool + 'a) -> Self {
+        self.push(Combinator::Child, predicate)
+    }
+
+    /// Adds a step matching the proper descendants of the current matches that satisfy
+    /// `predicate`.
+    pub fn descendant(self, predicate: impl Fn(&N) -> bool + 'a) -> Self {
+        self.push(Combinator::Descendant, predicate)
+    }
+
+    /// Adds a step related to the current matches by `combinator`.
+    fn push(mut self, combinator: Combinator, predicate: impl Fn(&N) -> bool + 'a) -> Self {
+        self.steps.push(Step {
+            combinator,
+            predicates: vec![Box::new(predicate)],
+            nth: None,
+        });
+        self
+    }
+
+    /// Narrows the last step to nodes that also satisfy `predicate`.
+    pub fn and(mut self, predicate: impl Fn(&N) -> bool + 'a) -> Self {
+        self.last_step().predicates.push(Box::new(predicate));
+        self
+    }
+
+    /// Narrows the last step to nodes with the given index among their siblings, counting
+    /// from zero. The root has no siblings, so it never matches such a step.
+    pub fn nth_child(mut self, index: usize) -> Self {
+        self.last_step().nth = Some(index);
+        self
+    }
+
+    /// Returns the last step, which every selector has.
+    fn last_step(&mut self) -> &mut Step<'a, N> {
+        self.steps
+            .last_mut()
+            .expect("a selector has at least one step")
+    }
+
+    /// Returns an iterator over the matches in the tree rooted at `root`, in depth-first
+    /// order, each with its child-index path from `root`.
+    pub fn query<'s, 'n>(&'s self, root: &'n N) -> Matches<'s, 'a, 'n, N> {
+        let len = self.steps.len() + 1;
+        let mut above_root = State {
+            ended: vec![false; len],
+            open: vec![false; len],
+        };
+        above_root.ended[0] = true;
+        above_root.open[0] = true;
+        Matches {
+            selector: self,
+            stack: vec![(root, Rc::new(above_root), 0, 0)],
+            path: Vec::new(),
+        }
+    }
+
+    /// Returns whether any node in the tree rooted at `root` matches.
+    pub fn is_match(&self, root: &N) -> bool {
+        self.query(root).next().is_some()
+    }
+
+    /// Returns the matches in the tree rooted at `root`, in depth-first order.
+    pub fn select<'n>(&self, root: &'n N) -> Vec<&'n N> {
+        self.query(root).map(|(node, _)| node).collect()
+    }
+}
+
+impl<N> fmt::Debug for Selector<'_, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Selector")
+            .field("steps", &self.steps.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// The progress of a selector at a node.
+#[derive(Debug)]
+struct State {
+    /// Whether the first `k` steps match a chain ending at this node, for each `k`.
+    ended: Vec<bool>,
+    /// Whether the first `k` steps match a chain ending at this node or an ancestor, for
+    /// each `k`.
+    open: Vec<bool>,
+}
+
+/// An iterator over the matches of a [`Selector`], created by [`Selector::query`].
+///
+/// # Type Parameters
+///
+/// * `'s` - The lifetime of the selector.
+/// * `'a` - The lifetime of the selector's predicates.
+/// * `'n` - The lifetime of the nodes being searched.
+/// * `N` - The type of node.
+pub struct Matches<'s, 'a, 'n, N> {
+    /// The selector being matched.
+    selector: &'s Selector<'a, N>,
+    /// The nodes still to visit, the next one last, each with the state of its parent,
+    /// its depth and its index among its siblings.
+    stack: Vec<(&'n N, Rc<State>, usize, usize)>,
+    /// The child-index path of the last visited node.
+    path: Vec<usize>,
+}
+
+impl<'n, N: TreeNode> Iterator for Matches<'_, '_, 'n, N> {
+    type Item = (&'n N, Vec<usize>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let steps = &self.selector.steps;
+        while let Some((node, parent, depth, index)) = self.stack.pop() {
+            let index = if depth > 0 {
+                self.path.truncate(depth - 1);
+                self.path.push(index);
+                Some(index)
+            } else {
+                None
+            };
+
+            let mut state = State {
+                ended: vec![false; steps.len() + 1],
+                open: parent.open.clone(),
+            };
+            for (k, step) in steps.iter().enumerate() {
+                let reached = match step.combinator {
+                    Combinator::Child => parent.ended[k],
+                    Combinator::Descendant => parent.open[k],
+                };
+                if reached && step.matches(node, index) {
+                    state.ended[k + 1] = true;
+                    state.open[k + 1] = true;
+                }
+            }
+            let matched = state.ended[steps.len()];
+
+            // Children can only match steps that follow a chain ending here or above
+            let live = steps
+                .iter()
+                .enumerate()
+                .any(|(k, step)| match step.combinator {
+                    Combinator::Child => state.ended[k],
+                    Combinator::Descendant => state.open[k],
+                });
+            if live {
+                let state = Rc::new(state);
+                let start = self.stack.len();
+                self.stack.extend(
+                    node.children()
+                        .enumerate()
+                        .map(|(i, child)| (child, Rc::clone(&state), depth + 1, i)),
+                );
+                self.stack[start..].reverse();
+            }
+            if matched {
+                return Some((node, self.path.clone()));
+            }
+        }
+        None
+    }
+}
+
+impl<N> fmt::Debug for Matches<'_, '_, '_, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Matches")
+            .field("selector", &self.selector)
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::Node;
+
+    #[test]
+    fn test_against_brute_force() {
+        let tree = Node::unfold::<crate::traversal_order::DepthFirst, _>(1u32, |n| {
+            let children = if n < 300 {
+                (0..n % 4).map(|i| 3 * n + i).collect()
+            } else {
+                vec![]
+            };
+            (n, children)
+        });
+        let even = |node: &Node<u32>| node.value.is_multiple_of(2);
+        let odd = |node: &Node<u32>| node.value % 2 == 1;
+
+        // Checks a match of `any(even) > odd nth-child(1) descendant(even)` directly
+        let brute = |path: &[usize]| {
+            let target = tree.get(path).unwrap();
+            if path.len() < 2 || !even(target) {
+                return false;
+            }
+            (1..path.len()).any(|middle| {
+                let parent = tree.get(&path[..middle - 1]).unwrap();
+                let node = tree.get(&path[..middle]).unwrap();
+                even(parent) && odd(node) && path[middle - 1] == 1
+            })
+        };
+        let mut expected = Vec::new();
+        let mut stack = vec![Vec::new()];
+        while let Some(path) = stack.pop() {
+            if brute(&path) {
+                expected.push(path.clone());
+            }
+            let count = tree.get(&path).unwrap().children.len();
+            for i in (0..count).rev() {
+                let mut child = path.clone();
+                child.push(i);
+                stack.push(child);
+            }
+        }
+
+        let selector = Selector::any(even).child(odd).nth_child(1).descendant(even);
+        let found: Vec<Vec<usize>> = selector.query(&tree).map(|(_, path)| path).collect();
+        assert!(!expected.is_empty());
+        assert_eq!(found, expected);
+
+        assert!(Selector::root(odd).is_match(&tree));
+        assert!(!Selector::root(even).is_match(&tree));
+        assert_eq!(
+            Selector::any(|_: &Node<u32>| true).select(&tree).len(),
+            tree.iter_dfs().count()
+        );
+    }
+}
@@ -0,0 +1,38 @@
+use rkyv::Archive;
+
+use crate::{iter::TreeNode, tree::ArchivedNode};
+
+impl<T: Archive> TreeNode for ArchivedNode<T> {
+    fn children(&self) -> impl DoubleEndedIterator<Item = &Self> {
+        self.children.iter()
+    }
+
+    fn children_slice(&self) -> Option<&[Self]> {
+        Some(self.children.as_slice())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rkyv::rancor::Error;
+
+    use super::*;
+    use crate::{traversal_order::BreadthFirst, tree::Node};
+
+    #[test]
+    fn test_archived_traversal() {
+        let tree = crate::tree! { 1 => [2 => [4, 5], 3] };
+        let bytes = rkyv::to_bytes::<Error>(&tree).unwrap();
+
+        let archived = rkyv::access::<ArchivedNode<i32>, Error>(&bytes).unwrap();
+        let values: Vec<i32> = archived
+            .iter::<BreadthFirst>()
+            .map(|node| node.value.to_native())
+            .collect();
+        assert_eq!(values, vec![1, 2, 3, 4, 5]);
+        assert_eq!(archived.height(), 2);
+
+        let restored = rkyv::deserialize::<Node<i32>, Error>(archived).unwrap();
+        assert_eq!(restored, tree);
+    }
+}
@@ -0,0 +1,344 @@
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fmt,
+    hash::Hash,
+    ops::{Bound, RangeBounds},
+};
+
+use crate::{
+    automaton::{Automaton, Transition, run_automaton},
+    iter::TreeNode,
+};
+
+/// Gives the kind of a node.
+type KindFn<'a, N, K> = Box<dyn Fn(&N) -> K + 'a>;
+
+/// The constraints on the nodes of one kind.
+///
+/// # Type Parameters
+///
+/// * `K` - The type of node kinds.
+#[derive(Debug, Clone)]
+pub struct Rule<K> {
+    /// The kinds allowed as children, or `None` if any kind is allowed.
+    children: Option<HashSet<K>>,
+    /// The least number of children.
+    min: usize,
+    /// The greatest number of children, if any.
+    max: Option<usize>,
+}
+
+impl<K: Eq + Hash> Rule<K> {
+    /// Creates a rule that allows any children.
+    pub fn new() -> Self {
+        Self {
+            children: None,
+            min: 0,
+            max: None,
+        }
+    }
+
+    /// Creates a rule that allows no children.
+    pub fn leaf() -> Self {
+        Self::new().arity(0..=0)
+    }
+
+    /// Allows only children of the given kinds.
+    pub fn children(mut self, kinds: impl IntoIterator<Item = K>) -> Self {
+        self.children = Some(kinds.into_iter().collect());
+        self
+    }
+
+    /// Bounds the number of children, as in `1..`, `..=3` or `2..=2`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is empty, such as `..0` or `3..2`, since no node could match it.
+    pub fn arity(mut self, range: impl RangeBounds<usize>) -> Self {
+        const EMPTY: &str = "the arity range must not be empty";
+        self.min = match range.start_bound() {
+            Bound::Included(&min) => min,
+            Bound::Excluded(&min) => min.checked_add(1).expect(EMPTY),
+            Bound::Unbounded => 0,
+        };
+        self.max = match range.end_bound() {
+            Bound::Included(&max) => Some(max),
+            Bound::Excluded(&max) => Some(max.checked_sub(1).expect(EMPTY)),
+            Bound::Unbounded => None,
+        };
+        assert!(self.max.is_none_or(|max| self.min <= max), "{EMPTY}");
+        self
+    }
+}
+
+impl<K: Eq + Hash> Default for Rule<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The allowed structure of a tree whose nodes have kinds, such as the element names of a
+/// document or the sections of a configuration.
+///
+/// A schema gives the kind of each node with a function, and has a [`Rule`] for each kind
+/// bounding the kinds and number of its children. Nodes of a kind without a rule are
+/// violations, so the schema lists every kind that may occur. The root may be limited to
+/// some kinds as well.
+///
+/// # Type Parameters
+///
+/// * `'a` - The lifetime of the kind function.
+/// * `N` - The type of node.
+/// * `K` - The type of node kinds.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::schema::{Rule, Schema, ViolationKind, validate_schema};
+/// use tree_iter::tree;
+/// use tree_iter::tree::Node;
+///
+/// let schema = Schema::new(|node: &Node<&str>| node.value.to_string())
+///     .root(["list".to_string()])
+///     .rule("list".to_string(), Rule::new().children(["item".to_string()]).arity(1..))
+///     .rule("item".to_string(), Rule::leaf());
+///
+/// assert!(validate_schema(&tree! { "list" => ["item", "item"] }, &schema).is_empty());
+///
+/// let violations = validate_schema(&tree! { "list" => ["item" => ["item"], "list"] }, &schema);
+/// assert_eq!(violations.len(), 3);
+/// assert_eq!(violations[0].path, vec![0]);
+/// assert_eq!(violations[0].kind, ViolationKind::Arity { kind: "item".to_string(), count: 1 });
+/// assert_eq!(violations[1].to_string(), "at [1]: `\"list\"` is not allowed in `\"list\"`");
+/// ```
+pub struct Schema<'a, N, K> {
+    /// Gives the kind of a node.
+    kind: KindFn<'a, N, K>,
+    /// The kinds allowed at the root, or `None` if any kind is allowed.
+    root: Option<HashSet<K>>,
+    /// The rule for each kind.
+    rules: HashMap<K, Rule<K>>,
+}
+
+impl<'a, N, K: Eq + Hash> Schema<'a, N, K> {
+    /// Creates a schema without rules, with `kind` giving the kind of each node.
+    pub fn new(kind: impl Fn(&N) -> K + 'a) -> Self {
+        Self {
+            kind: Box::new(kind),
+            root: None,
+            rules: HashMap::new(),
+        }
+    }
+
+    /// Allows only roots of the given kinds.
+    pub fn root(mut self, kinds: impl IntoIterator<Item = K>) -> Self {
+        self.root = Some(kinds.into_iter().collect());
+        self
+    }
+
+    /// Sets the rule for nodes of a kind, replacing any earlier rule for it.
+    pub fn rule(mut self, kind: K, rule: Rule<K>) -> Self {
+        self.rules.insert(kind, rule);
+        self
+    }
+}
+
+impl<N, K: fmt::Debug> fmt::Debug for Schema<'_, N, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Schema")
+            .field("root", &self.root)
+            .field("rules", &self.rules)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A node that breaks a [`Schema`].
+///
+/// # Type Parameters
+///
+/// * `K` - The type of node kinds.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Violation<K> {
+    /// The child-index path of the node.
+    pub path: Vec<usize>,
+    /// The way the node breaks the schema.
+    pub kind: ViolationKind<K>,
+}
+
+/// The way a node breaks a [`Schema`].
+///
+/// # Type Parameters
+///
+/// * `K` - The type of node kinds.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ViolationKind<K> {
+    /// The schema has no rule for the kind of the node.
+    UnknownKind(K),
+    /// The kind of the root is not allowed at the root.
+    RootKind(K),
+    /// The kind of the node is not allowed as a child of its parent's kind.
+    ChildKind {
+        /// The kind of the parent.
+        parent: K,
+        /// The kind of the node.
+        child: K,
+    },
+    /// The node has too few or too many children for its kind.
+    Arity {
+        /// The kind of the node.
+        kind: K,
+        /// The number of children of the node.
+        count: usize,
+    },
+}
+
+impl<K: fmt::Debug> fmt::Display for Violation<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "at {:?}: ", self.path)?;
+        match &self.kind {
+            ViolationKind::UnknownKind(kind) => write!(f, "no rule for `{kind:?}`"),
+            ViolationKind::RootKind(kind) => write!(f, "`{kind:?}` is not allowed at the root"),
+            ViolationKind::ChildKind { parent, child } => {
+                write!(f, "`{child:?}` is not allowed in `{parent:?}`")
+            }
+            ViolationKind::Arity { kind, count } => {
+                write!(f, "`{kind:?}` cannot have {count} children")
+            }
+        }
+    }
+}
+
+impl<K: fmt::Debug> Error for Violation<K> {}
+
+/// Checks a tree against a schema, returning every violation, in depth-first order.
+///
+/// A node of a kind without a rule is reported once, and its children are still checked
+/// against their own rules. The tree is walked iteratively with [`run_automaton`], so
+/// trees of any depth can be checked.
+///
+/// See [`Schema`] for an example.
+pub fn validate_schema<N: TreeNode, K: Eq + Hash + Clone>(
+    root: &N,
+    schema: &Schema<'_, N, K>,
+) -> Vec<Violation<K>> {
+    run_automaton(root, Validator { schema })
+}
+
+/// Checks each node against the rule of its parent's kind and its own.
+struct Validator<'s, 'a, N, K> {
+    /// The schema to check against.
+    schema: &'s Schema<'a, N, K>,
+}
+
+impl<N: TreeNode, K: Eq + Hash + Clone> Automaton<N> for Validator<'_, '_, N, K> {
+    /// The kind of the node, or `None` above the root.
+    type State = Option<K>;
+    type Output = Violation<K>;
+
+    fn initial(&mut self) -> Option<K> {
+        None
+    }
+
+    fn enter(
+        &mut self,
+        parent: &Option<K>,
+        node: &N,
+        path: &[usize],
+        output: &mut Vec<Violation<K>>,
+    ) -> Transition<Option<K>> {
+        let kind = (self.schema.kind)(node);
+        let mut report = |kind| {
+            output.push(Violation {
+                path: path.to_vec(),
+                kind,
+            })
+        };
+
+        let allowed = match parent {
+            None => self.schema.root.as_ref(),
+            Some(parent) => self
+                .schema
+                .rules
+                .get(parent)
+                .and_then(|rule| rule.children.as_ref()),
+        };
+        if allowed.is_some_and(|allowed| !allowed.contains(&kind)) {
+            report(match parent {
+                None => ViolationKind::RootKind(kind.clone()),
+                Some(parent) => ViolationKind::ChildKind {
+                    parent: parent.clone(),
+                    child: kind.clone(),
+                },
+            });
+        }
+
+        match self.schema.rules.get(&kind) {
+            None => report(ViolationKind::UnknownKind(kind.clone())),
+            Some(rule) => {
+                let count = node.children().count();
+                if count < rule.min || rule.max.is_some_and(|max| count > max) {
+                    report(ViolationKind::Arity {
+                        kind: kind.clone(),
+                        count,
+                    });
+                }
+            }
+        }
+        Transition::Descend(Some(kind))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::Node;
+
+    #[test]
+    fn test_all_violations() {
+        let schema = Schema::new(|node: &Node<(char, u8)>| node.value.0)
+            .root(['a'])
+            .rule('a', Rule::new().children(['b', 'c']).arity(..3))
+            .rule('b', Rule::new().arity(1..=1))
+            .rule('c', Rule::leaf());
+        let tree = crate::tree! {
+            ('b', 0) => [('a', 1) => [('c', 2), ('x', 3) => [('c', 4)], ('b', 5)], ('c', 6) => [('c', 7)]]
+        };
+        let violations: Vec<String> = validate_schema(&tree, &schema)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            violations,
+            vec![
+                "at []: `'b'` is not allowed at the root",
+                "at []: `'b'` cannot have 2 children",
+                "at [0]: `'a'` cannot have 3 children",
+                "at [0, 1]: `'x'` is not allowed in `'a'`",
+                "at [0, 1]: no rule for `'x'`",
+                "at [0, 2]: `'b'` cannot have 0 children",
+                "at [1]: `'c'` cannot have 1 children",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_arity_bounds() {
+        let rule: Rule<char> = Rule::new().arity(..1);
+        assert_eq!((rule.min, rule.max), (0, Some(0)));
+        let rule: Rule<char> = Rule::new().arity(2..);
+        assert_eq!((rule.min, rule.max), (2, None));
+        let rule: Rule<char> = Rule::new().arity((Bound::Excluded(1), Bound::Included(2)));
+        assert_eq!((rule.min, rule.max), (2, Some(2)));
+
+        for empty in [
+            (Bound::Unbounded, Bound::Excluded(0)),
+            (Bound::Included(3), Bound::Excluded(3)),
+            (Bound::Included(3), Bound::Included(2)),
+            (Bound::Excluded(usize::MAX), Bound::Unbounded),
+        ] {
+            let result = std::panic::catch_unwind(|| Rule::<char>::new().arity(empty));
+            assert!(result.is_err(), "{empty:?}");
+        }
+    }
+}
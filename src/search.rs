@@ -0,0 +1,239 @@
+use std::{
+    fmt,
+    hash::{BuildHasher, Hash, RandomState},
+};
+
+use crate::{flat::Flat, iter::TreeNode};
+
+/// Gives the key of a node.
+type KeyFn<'a, N, K> = Box<dyn Fn(&N) -> K + 'a>;
+
+/// The number of bits each key sets in a filter.
+const HASHES: u64 = 3;
+
+/// An index over a static tree that skips the subtrees that cannot hold a key.
+///
+/// Every node gets a Bloom filter of the keys in its subtree: a fixed set of bits, of
+/// which each key sets a few. A subtree whose filter lacks any bit of the key cannot hold
+/// the key, so a search skips it without visiting its nodes. Filters give false positives
+/// but never false negatives, so matches are always confirmed by comparing the keys.
+///
+/// The filters take `bits / 8` bytes per node, 32 by default. Filters of large subtrees
+/// fill up and rarely prune, but those of small subtrees near the matches do, so searches
+/// for rare keys visit few nodes. The index borrows the tree, which cannot change while
+/// the index exists.
+///
+/// # Type Parameters
+///
+/// * `'a` - The lifetime of the tree and the key function.
+/// * `N` - The type of node.
+/// * `K` - The type of keys.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::search::SearchIndex;
+/// use tree_iter::tree;
+///
+/// let tree = tree! { "/" => ["etc" => ["hosts", "passwd"], "home" => ["alice" => ["notes"]]] };
+/// let index = SearchIndex::build(&tree, |node| node.value);
+///
+/// assert_eq!(index.find(&"alice").map(|node| node.children.len()), Some(1));
+/// assert_eq!(index.find_paths(&"passwd"), vec![vec![0, 1]]);
+/// assert!(!index.contains(&"shadow"));
+/// ```
+pub struct SearchIndex<'a, N, K> {
+    /// The tree in pre-order.
+    flat: Flat<'a, N>,
+    /// Gives the key of a node.
+    key: KeyFn<'a, N, K>,
+    /// The hash of each node's key.
+    hashes: Vec<u64>,
+    /// The filters of the subtrees, `words` words each, in pre-order.
+    filters: Vec<u64>,
+    /// The number of words in each filter.
+    words: usize,
+    /// Hashes the keys.
+    hasher: RandomState,
+}
+
+impl<'a, N: TreeNode, K: Eq + Hash> SearchIndex<'a, N, K> {
+    /// Builds the index with 256-bit filters, with `key` giving the key of each node.
+    pub fn build(root: &'a N, key: impl Fn(&N) -> K + 'a) -> Self {
+        Self::with_bits(root, 256, key)
+    }
+
+    /// Builds the index with filters of `bits` bits, rounded up to a multiple of 64.
+    ///
+    /// Larger filters prune more subtrees, at the cost of memory.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` is 0.
+    pub fn with_bits(root: &'a N, bits: usize, key: impl Fn(&N) -> K + 'a) -> Self {
+        assert!(bits > 0, "filters must have at least one bit");
+        let flat = Flat::new(root);
+        let hasher = RandomState::new();
+        let hashes: Vec<u64> = flat
+            .nodes
+            .iter()
+            .map(|&node| hasher.hash_one(key(node)))
+            .collect();
+
+        // Each subtree's filter is its root's key added to the union of its children's
+        let words = bits.div_ceil(64);
+        let mut filters = vec![0; flat.len() * words];
+        for id in (0..flat.len()).rev() {
+            for bit in bits_of(hashes[id], words) {
+                filters[id * words + bit / 64] |= 1 << (bit % 64);
+            }
+            if let Some(parent) = flat.parent[id] {
+                for word in 0..words {
+                    filters[parent * words + word] |= filters[id * words + word];
+                }
+            }
+        }
+
+        Self {
+            flat,
+            key: Box::new(key),
+            hashes,
+            filters,
+            words,
+            hasher,
+        }
+    }
+
+    /// Returns the first node with the given key in depth-first order, or `None` if there
+    /// is none.
+    pub fn find(&self, key: &K) -> Option<&'a N> {
+        self.matches(key).next().map(|id| self.flat.nodes[id])
+    }
+
+    /// Returns whether any node has the given key.
+    pub fn contains(&self, key: &K) -> bool {
+        self.matches(key).next().is_some()
+    }
+
+    /// Returns every node with the given key, in depth-first order.
+    pub fn find_all(&self, key: &K) -> Vec<&'a N> {
+        self.matches(key).map(|id| self.flat.nodes[id]).collect()
+    }
+
+    /// Returns the child-index paths of every node with the given key, in depth-first
+    /// order.
+    pub fn find_paths(&self, key: &K) -> Vec<Vec<usize>> {
+        self.matches(key).map(|id| self.flat.path(id)).collect()
+    }
+
+    /// Returns the pre-order ids of the nodes with the given key.
+    fn matches<'s>(&'s self, key: &'s K) -> impl Iterator<Item = usize> + 's {
+        let hash = self.hasher.hash_one(key);
+        self.visits(hash)
+            .filter(move |&id| self.hashes[id] == hash && (self.key)(self.flat.nodes[id]) == *key)
+    }
+
+    /// Returns the pre-order ids of the nodes a search for a key with the given hash looks
+    /// at, skipping every subtree whose filter rules the key out.
+    fn visits(&self, hash: u64) -> impl Iterator<Item = usize> + '_ {
+        let bits: Vec<usize> = bits_of(hash, self.words).collect();
+        let mut id = 0;
+        std::iter::from_fn(move || {
+            while id < self.flat.len() {
+                let current = id;
+                if self.admits(current, &bits) {
+                    id += 1;
+                    return Some(current);
+                }
+                id = self.flat.subtree_end[current];
+            }
+            None
+        })
+    }
+
+    /// Returns whether the filter of the subtree of `id` has all of the given bits set.
+    fn admits(&self, id: usize, bits: &[usize]) -> bool {
+        let filter = &self.filters[id * self.words..(id + 1) * self.words];
+        bits.iter()
+            .all(|&bit| filter[bit / 64] & (1 << (bit % 64)) != 0)
+    }
+}
+
+impl<N, K> fmt::Debug for SearchIndex<'_, N, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SearchIndex")
+            .field("len", &self.flat.len())
+            .field("bits", &(self.words * 64))
+            .finish_non_exhaustive()
+    }
+}
+
+/// Returns the bits a key with the given hash sets in a filter of `words` words.
+fn bits_of(hash: u64, words: usize) -> impl Iterator<Item = usize> {
+    let bits = words as u64 * 64;
+    let step = hash.rotate_left(32) | 1;
+    (0..HASHES).map(move |i| (hash.wrapping_add(i.wrapping_mul(step)) % bits) as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{traversal_order::DepthFirst, tree::Node};
+
+    #[test]
+    fn test_matches_scan() {
+        let tree = Node::unfold::<DepthFirst, _>(1u32, |n| {
+            let children = if n < 2000 {
+                vec![2 * n, 2 * n + 1]
+            } else {
+                vec![]
+            };
+            (n, children)
+        });
+        let index = SearchIndex::with_bits(&tree, 64, |node| node.value % 1000);
+        for key in [0, 1, 7, 999, 1000] {
+            let expected: Vec<u32> = tree
+                .iter_dfs()
+                .filter(|node| node.value % 1000 == key)
+                .map(|node| node.value)
+                .collect();
+            let found: Vec<u32> = index.find_all(&key).iter().map(|node| node.value).collect();
+            assert_eq!(found, expected);
+            assert_eq!(index.contains(&key), !expected.is_empty());
+            for path in index.find_paths(&key) {
+                assert_eq!(tree.get(&path).unwrap().value % 1000, key);
+            }
+        }
+    }
+
+    #[test]
+    fn test_pruned_subtrees_are_skipped() {
+        let tree = Node::unfold::<DepthFirst, _>(1u32, |n| {
+            let children = if n < 2000 {
+                vec![2 * n, 2 * n + 1]
+            } else {
+                vec![]
+            };
+            (n, children)
+        });
+        let index = SearchIndex::build(&tree, |node| node.value);
+        let hash = index.hasher.hash_one(1234u32);
+        let bits: Vec<usize> = bits_of(hash, index.words).collect();
+        let visited: Vec<usize> = index.visits(hash).collect();
+
+        // A node is looked at exactly when the filters of it and all its ancestors admit the key
+        let mut admitted = vec![false; index.flat.len()];
+        for id in 0..index.flat.len() {
+            admitted[id] = index.flat.parent[id].is_none_or(|parent| admitted[parent])
+                && index.admits(id, &bits);
+        }
+        let expected: Vec<usize> = (0..index.flat.len()).filter(|&id| admitted[id]).collect();
+        assert_eq!(visited, expected);
+        assert!(
+            visited.len() < index.flat.len() / 10,
+            "{} visited",
+            visited.len()
+        );
+        assert_eq!(index.find_paths(&1234).len(), 1);
+    }
+}
@@ -0,0 +1,242 @@
+use std::collections::BTreeSet;
+
+use crate::iter::TreeNode;
+
+/// The state of a node in a hierarchical checkbox tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CheckState {
+    /// Neither the node nor any of its descendants is selected.
+    Unchecked,
+    /// The node and its whole subtree are selected.
+    Checked,
+    /// Some, but not all, of the node's descendants are selected.
+    Indeterminate,
+}
+
+/// A set of selected subtrees, identified by their child-index paths.
+///
+/// Selecting a node selects its whole subtree. The selection is kept normalized:
+///
+/// - a selected node never has a selected ancestor in the set, since the ancestor already covers it;
+/// - when every child of a node is selected, the children are replaced by the node itself.
+///
+/// Operations that need to know the shape of the tree, such as selecting and deselecting,
+/// take the root node as a parameter. Queries only look at the selected paths.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::selection::{CheckState, Selection};
+/// use tree_iter::tree::Node;
+///
+/// let tree = Node {
+///     value: 1,
+///     children: vec![
+///         Node {
+///             value: 2,
+///             children: vec![Node::new(4), Node::new(5)],
+///         },
+///         Node::new(3),
+///     ],
+/// };
+///
+/// let mut selection = Selection::new();
+/// selection.select(&tree, &[0, 0]);
+/// assert_eq!(selection.effective_state(&[0]), CheckState::Indeterminate);
+///
+/// // Selecting the last child of node 2 selects node 2 itself
+/// selection.select(&tree, &[0, 1]);
+/// assert_eq!(selection.effective_state(&[0]), CheckState::Checked);
+/// assert_eq!(selection.paths().collect::<Vec<_>>(), vec![&[0][..]]);
+///
+/// // Deselecting a node below a selected subtree keeps its siblings selected
+/// selection.deselect(&tree, &[0, 0]);
+/// assert!(selection.is_selected(&[0, 1]));
+/// assert!(!selection.is_selected(&[0, 0]));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Selection {
+    /// Roots of the selected subtrees, with no path being a prefix of another.
+    roots: BTreeSet<Vec<usize>>,
+}
+
+impl Selection {
+    /// Creates an empty selection.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `true` if nothing is selected.
+    pub fn is_empty(&self) -> bool {
+        self.roots.is_empty()
+    }
+
+    /// Deselects every node.
+    pub fn clear(&mut self) {
+        self.roots.clear();
+    }
+
+    /// Returns an iterator over the roots of the selected subtrees, in depth-first order.
+    pub fn paths(&self) -> impl Iterator<Item = &[usize]> {
+        self.roots.iter().map(Vec::as_slice)
+    }
+
+    /// Returns `true` if the node at `path` is selected, either directly or through an ancestor.
+    pub fn is_selected(&self, path: &[usize]) -> bool {
+        (0..=path.len()).any(|len| self.roots.contains(&path[..len]))
+    }
+
+    /// Returns the checkbox state of the node at `path`.
+    pub fn effective_state(&self, path: &[usize]) -> CheckState {
+        if self.is_selected(path) {
+            CheckState::Checked
+        } else if self.descendants(path).next().is_some() {
+            CheckState::Indeterminate
+        } else {
+            CheckState::Unchecked
+        }
+    }
+
+    /// Selects the subtree rooted at the node at `path`.
+    ///
+    /// Returns `false`, leaving the selection unchanged, if there is no node at `path`.
+    pub fn select<N: TreeNode>(&mut self, root: &N, path: &[usize]) -> bool {
+        if child_count(root, path).is_none() {
+            return false;
+        }
+        if self.is_selected(path) {
+            return true;
+        }
+        self.remove_descendants(path);
+        self.roots.insert(path.to_vec());
+
+        // Replace complete sets of siblings with their parent
+        let mut path = path;
+        while let Some((_, parent)) = path.split_last() {
+            let selected = self
+                .descendants(parent)
+                .filter(|p| p.len() == parent.len() + 1)
+                .count();
+            if Some(selected) != child_count(root, parent) {
+                break;
+            }
+            self.remove_descendants(parent);
+            self.roots.insert(parent.to_vec());
+            path = parent;
+        }
+        true
+    }
+
+    /// Deselects the subtree rooted at the node at `path`.
+    ///
+    /// If the node was selected through an ancestor, the ancestor is replaced by the
+    /// siblings along the way, so the rest of its subtree stays selected.
+    /// Returns `false`, leaving the selection unchanged, if there is no node at `path`.
+    pub fn deselect<N: TreeNode>(&mut self, root: &N, path: &[usize]) -> bool {
+        if child_count(root, path).is_none() {
+            return false;
+        }
+        self.remove_descendants(path);
+        let Some(ancestor) = (0..path.len()).find(|&len| self.roots.contains(&path[..len])) else {
+            return true;
+        };
+        self.roots.remove(&path[..ancestor]);
+        for len in ancestor..path.len() {
+            let count = child_count(root, &path[..len]).unwrap_or(0);
+            for i in (0..count).filter(|&i| i != path[len]) {
+                let mut sibling = path[..len].to_vec();
+                sibling.push(i);
+                self.roots.insert(sibling);
+            }
+        }
+        true
+    }
+
+    /// Toggles the node at `path` the way a checkbox click does.
+    ///
+    /// Checked nodes become unchecked, while unchecked and indeterminate nodes become checked.
+    /// Returns the new state of the node, or `None` if there is no node at `path`.
+    pub fn toggle<N: TreeNode>(&mut self, root: &N, path: &[usize]) -> Option<CheckState> {
+        let changed = if self.is_selected(path) {
+            self.deselect(root, path)
+        } else {
+            self.select(root, path)
+        };
+        changed.then(|| self.effective_state(path))
+    }
+
+    /// Returns the selected paths strictly below `path`.
+    fn descendants<'s>(&'s self, path: &'s [usize]) -> impl Iterator<Item = &'s Vec<usize>> {
+        self.roots
+            .range(path.to_vec()..)
+            .take_while(move |p| p.starts_with(path))
+            .filter(move |p| p.len() > path.len())
+    }
+
+    /// Removes every selected path at or below `path`.
+    fn remove_descendants(&mut self, path: &[usize]) {
+        let covered: Vec<_> = self
+            .roots
+            .range(path.to_vec()..)
+            .take_while(|p| p.starts_with(path))
+            .cloned()
+            .collect();
+        for p in covered {
+            self.roots.remove(&p);
+        }
+    }
+}
+
+/// Returns the number of children of the node at `path`, or `None` if there is no such node.
+fn child_count<N: TreeNode>(root: &N, path: &[usize]) -> Option<usize> {
+    let mut node = root;
+    for &i in path {
+        node = node.children().nth(i)?;
+    }
+    Some(node.children().count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::Node;
+
+    fn sample_tree() -> Node<i32> {
+        Node::with_children(
+            1,
+            [
+                Node::with_children(2, [Node::new(4), Node::new(5)]),
+                Node::with_children(3, [Node::new(6)]),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_select_normalizes_to_root() {
+        let tree = sample_tree();
+        let mut selection = Selection::new();
+        assert!(selection.select(&tree, &[0]));
+        assert!(selection.select(&tree, &[1, 0]));
+        assert_eq!(selection.paths().collect::<Vec<_>>(), vec![&[][..]]);
+        assert_eq!(selection.effective_state(&[0, 1]), CheckState::Checked);
+    }
+
+    #[test]
+    fn test_deselect_splits_ancestor() {
+        let tree = sample_tree();
+        let mut selection = Selection::new();
+        selection.select(&tree, &[]);
+        selection.deselect(&tree, &[0, 1]);
+        assert_eq!(
+            selection.paths().collect::<Vec<_>>(),
+            vec![&[0, 0][..], &[1][..]]
+        );
+        assert_eq!(selection.effective_state(&[]), CheckState::Indeterminate);
+        assert_eq!(selection.effective_state(&[0, 1]), CheckState::Unchecked);
+
+        assert_eq!(selection.toggle(&tree, &[]), Some(CheckState::Checked));
+        assert_eq!(selection.toggle(&tree, &[]), Some(CheckState::Unchecked));
+        assert!(selection.is_empty());
+        assert_eq!(selection.toggle(&tree, &[5]), None);
+    }
+}
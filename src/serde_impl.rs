@@ -0,0 +1,215 @@
+use std::fmt;
+
+use serde::{
+    de::{self, Deserialize, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor},
+    ser::{Serialize, SerializeStruct, Serializer},
+};
+
+use crate::tree::{Node, Tree};
+
+impl<T: Serialize> Serialize for Node<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut node = serializer.serialize_struct("Node", 2)?;
+        node.serialize_field("value", &self.value)?;
+        node.serialize_field("children", &self.children)?;
+        node.end()
+    }
+}
+
+/// The fields of a serialized [`Node`].
+enum Field {
+    Value,
+    Children,
+    Ignored,
+}
+
+impl<'de> Deserialize<'de> for Field {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FieldVisitor;
+
+        impl Visitor<'_> for FieldVisitor {
+            type Value = Field;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("`value` or `children`")
+            }
+
+            fn visit_str<E: de::Error>(self, field: &str) -> Result<Field, E> {
+                Ok(match field {
+                    "value" => Field::Value,
+                    "children" => Field::Children,
+                    _ => Field::Ignored,
+                })
+            }
+        }
+
+        deserializer.deserialize_identifier(FieldVisitor)
+    }
+}
+
+/// A node being deserialized: its value once read, and the children read so far.
+struct Partial<T> {
+    /// The value of the node.
+    value: Option<T>,
+    /// The children of the node that have been read.
+    children: Vec<Node<T>>,
+}
+
+/// The nodes being deserialized, from the root down to the node being read.
+///
+/// Each node is kept here while its children are read, rather than in the frames of nested
+/// visitors, and is moved into the children of the node below it once it is complete. The
+/// bottom entry only collects the root.
+type Partials<T> = Vec<Partial<T>>;
+
+/// Reads one serialized [`Node`], given either as a map or as a `[value, children]`
+/// sequence, and adds it to the children of the last partial node.
+struct NodeSeed<'s, T> {
+    /// The nodes being deserialized.
+    partials: &'s mut Partials<T>,
+}
+
+impl<'de, T: Deserialize<'de>> DeserializeSeed<'de> for NodeSeed<'_, T> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_struct("Node", &["value", "children"], self)
+    }
+}
+
+impl<T> NodeSeed<'_, T> {
+    /// Starts a node.
+    fn open(&mut self) {
+        self.partials.push(Partial {
+            value: None,
+            children: Vec::new(),
+        });
+    }
+
+    /// Completes the last node and adds it to the children of its parent.
+    fn close<E: de::Error>(self) -> Result<(), E> {
+        let partial = self.partials.pop().expect("the node was opened");
+        let node = Node {
+            value: partial.value.ok_or_else(|| E::missing_field("value"))?,
+            children: partial.children,
+        };
+        let parent = self
+            .partials
+            .last_mut()
+            .expect("the root has a parent entry");
+        parent.children.push(node);
+        Ok(())
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for NodeSeed<'_, T> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a tree node")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(mut self, mut seq: A) -> Result<(), A::Error> {
+        self.open();
+        let value = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &"a tree node"))?;
+        self.partials.last_mut().unwrap().value = Some(value);
+        seq.next_element_seed(ChildrenSeed {
+            partials: self.partials,
+        })?;
+        self.close()
+    }
+
+    fn visit_map<A: MapAccess<'de>>(mut self, mut map: A) -> Result<(), A::Error> {
+        self.open();
+        let mut has_children = false;
+        while let Some(field) = map.next_key()? {
+            match field {
+                Field::Value if self.partials.last().unwrap().value.is_some() => {
+                    return Err(de::Error::duplicate_field("value"));
+                }
+                Field::Value => self.partials.last_mut().unwrap().value = Some(map.next_value()?),
+                Field::Children if has_children => {
+                    return Err(de::Error::duplicate_field("children"));
+                }
+                Field::Children => {
+                    map.next_value_seed(ChildrenSeed {
+                        partials: self.partials,
+                    })?;
+                    has_children = true;
+                }
+                Field::Ignored => {
+                    map.next_value::<de::IgnoredAny>()?;
+                }
+            }
+        }
+        self.close()
+    }
+}
+
+/// Reads the children of the last partial node.
+struct ChildrenSeed<'s, T> {
+    /// The nodes being deserialized.
+    partials: &'s mut Partials<T>,
+}
+
+impl<'de, T: Deserialize<'de>> DeserializeSeed<'de> for ChildrenSeed<'_, T> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for ChildrenSeed<'_, T> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a list of tree nodes")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        while seq
+            .next_element_seed(NodeSeed {
+                partials: &mut *self.partials,
+            })?
+            .is_some()
+        {}
+        Ok(())
+    }
+}
+
+/// Deserializes a node with its partial nodes on an explicit stack.
+///
+/// The visitors only hold a reference to the stack, so each level of the tree costs the
+/// format's own frames and a few small ones here, and a partial tree left by an error is
+/// dropped without recursion.
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Node<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut partials = vec![Partial {
+            value: None,
+            children: Vec::with_capacity(1),
+        }];
+        NodeSeed {
+            partials: &mut partials,
+        }
+        .deserialize(deserializer)?;
+        Ok(partials
+            .pop()
+            .and_then(|mut root| root.children.pop())
+            .expect("the root was read"))
+    }
+}
+
+impl<T: Serialize> Serialize for Tree<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.root().serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Tree<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Option::<Node<T>>::deserialize(deserializer).map(Tree::from)
+    }
+}
@@ -0,0 +1,283 @@
+use std::{
+    error::Error,
+    fmt::{self, Display, Write},
+    str::FromStr,
+};
+
+use crate::tree::Node;
+
+/// Writes a tree as an s-expression.
+///
+/// A leaf is written as its value, and a node with children as a list of its value
+/// followed by its children: `(1 (2 4 5) 3)`. Values that would not read back as a single
+/// atom, because they are empty or contain whitespace, parentheses or quotes, are written
+/// as quoted strings. The tree is walked iteratively, so it may be of any depth.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::sexpr::to_sexpr;
+/// use tree_iter::tree;
+///
+/// assert_eq!(to_sexpr(&tree! { 1 => [2 => [4, 5], 3] }), "(1 (2 4 5) 3)");
+/// assert_eq!(to_sexpr(&tree! { "a b" => ["c"] }), r#"("a b" c)"#);
+/// ```
+pub fn to_sexpr<T: Display>(root: &Node<T>) -> String {
+    let mut out = String::new();
+    write_sexpr(root, &mut out).expect("writing to a String cannot fail");
+    out
+}
+
+/// Writes a tree as an s-expression to `out`.
+///
+/// See [`to_sexpr`] for details.
+pub fn write_sexpr<T: Display>(root: &Node<T>, out: &mut impl Write) -> fmt::Result {
+    /// A step of writing the tree.
+    enum Step<'a, T> {
+        /// Write a node, preceded by a space unless it is the root.
+        Node(&'a Node<T>, bool),
+        /// Close the list of a node with children.
+        Close,
+    }
+
+    let mut atom = String::new();
+    let mut stack = vec![Step::Node(root, false)];
+    while let Some(step) = stack.pop() {
+        let (node, spaced) = match step {
+            Step::Node(node, spaced) => (node, spaced),
+            Step::Close => {
+                out.write_char(')')?;
+                continue;
+            }
+        };
+        if spaced {
+            out.write_char(' ')?;
+        }
+        if !node.children.is_empty() {
+            out.write_char('(')?;
+            stack.push(Step::Close);
+            stack.extend(
+                node.children
+                    .iter()
+                    .rev()
+                    .map(|child| Step::Node(child, true)),
+            );
+        }
+        atom.clear();
+        write!(atom, "{}", node.value)?;
+        write_atom(&atom, out)?;
+    }
+    Ok(())
+}
+
+/// Writes `atom`, quoting it if it would not read back as a single bare atom.
+fn write_atom(atom: &str, out: &mut impl Write) -> fmt::Result {
+    let bare = !atom.is_empty()
+        && !atom
+            .chars()
+            .any(|c| c.is_whitespace() || "()\"".contains(c));
+    if bare {
+        return out.write_str(atom);
+    }
+    out.write_char('"')?;
+    for c in atom.chars() {
+        if c == '"' || c == '\\' {
+            out.write_char('\\')?;
+        }
+        out.write_char(c)?;
+    }
+    out.write_char('"')
+}
+
+/// Parses a tree from an s-expression, as written by [`to_sexpr`].
+///
+/// Each atom is parsed with `T::from_str`. A list must start with an atom, the value of
+/// the node; the rest of the list are its children. Bare atoms are separated by whitespace
+/// or parentheses, and quoted atoms may contain anything, with `\"` and `\\` escaping a
+/// quote and a backslash. The input is parsed iteratively, so it may nest to any depth.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::sexpr::parse_sexpr;
+/// use tree_iter::tree;
+/// use tree_iter::tree::Node;
+///
+/// let tree: Node<i32> = parse_sexpr("(1 (2 4 5) 3)").unwrap();
+/// assert_eq!(tree, tree! { 1 => [2 => [4, 5], 3] });
+///
+/// let error = parse_sexpr::<i32>("(1 (2 x))").unwrap_err();
+/// assert_eq!(error.to_string(), "invalid value `x` at offset 6");
+/// ```
+pub fn parse_sexpr<T: FromStr>(input: &str) -> Result<Node<T>, SexprError> {
+    let error = |offset, kind| Err(SexprError { offset, kind });
+
+    // The nodes whose lists are still open, with the children read so far
+    let mut open: Vec<(T, Vec<Node<T>>)> = Vec::new();
+    let mut root = None;
+    let mut rest = input;
+    loop {
+        rest = rest.trim_start();
+        let offset = input.len() - rest.len();
+        let Some(c) = rest.chars().next() else {
+            break;
+        };
+        if root.is_some() {
+            return error(offset, SexprErrorKind::TrailingInput);
+        }
+
+        let node = match c {
+            '(' => {
+                rest = &rest[1..];
+                let start = input.len() - rest.trim_start().len();
+                match read_atom(input, &mut rest)? {
+                    Some(value) => open.push((parse_value(value, start)?, Vec::new())),
+                    None => return error(start, SexprErrorKind::MissingValue),
+                }
+                continue;
+            }
+            ')' => {
+                rest = &rest[1..];
+                match open.pop() {
+                    Some(parts) => Node::from(parts),
+                    None => return error(offset, SexprErrorKind::UnexpectedClose),
+                }
+            }
+            _ => match read_atom(input, &mut rest)? {
+                Some(value) => Node::new(parse_value(value, offset)?),
+                None => {
+                    unreachable!("a non-space character other than a parenthesis starts an atom")
+                }
+            },
+        };
+        match open.last_mut() {
+            Some((_, children)) => children.push(node),
+            None => root = Some(node),
+        }
+    }
+
+    match root {
+        Some(root) if open.is_empty() => Ok(root),
+        _ => error(input.len(), SexprErrorKind::UnexpectedEnd),
+    }
+}
+
+/// Reads the atom at the start of `rest`, after any whitespace, advancing past it.
+///
+/// Returns `None` if `rest` does not start with an atom.
+fn read_atom(input: &str, rest: &mut &str) -> Result<Option<String>, SexprError> {
+    *rest = rest.trim_start();
+    let start = input.len() - rest.len();
+    let Some(quoted) = rest.strip_prefix('"') else {
+        let end = rest
+            .find(|c: char| c.is_whitespace() || "()\"".contains(c))
+            .unwrap_or(rest.len());
+        let atom = &rest[..end];
+        *rest = &rest[end..];
+        return Ok((!atom.is_empty()).then(|| atom.to_string()));
+    };
+
+    let mut atom = String::new();
+    let mut chars = quoted.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => {
+                *rest = &quoted[i + 1..];
+                return Ok(Some(atom));
+            }
+            '\\' => match chars.next() {
+                Some((_, escaped)) => atom.push(escaped),
+                None => break,
+            },
+            _ => atom.push(c),
+        }
+    }
+    Err(SexprError {
+        offset: start,
+        kind: SexprErrorKind::UnterminatedString,
+    })
+}
+
+/// Parses the text of an atom found at `offset`.
+fn parse_value<T: FromStr>(atom: String, offset: usize) -> Result<T, SexprError> {
+    atom.parse().map_err(|_| SexprError {
+        offset,
+        kind: SexprErrorKind::InvalidValue(atom),
+    })
+}
+
+/// The error returned by [`parse_sexpr`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SexprError {
+    /// The byte offset in the input where the problem was found.
+    pub offset: usize,
+    /// What went wrong.
+    pub kind: SexprErrorKind,
+}
+
+/// What went wrong when parsing an s-expression.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SexprErrorKind {
+    /// The input ended before the tree was complete.
+    UnexpectedEnd,
+    /// A `)` has no matching `(`.
+    UnexpectedClose,
+    /// A list does not start with the value of its node.
+    MissingValue,
+    /// A quoted atom has no closing quote.
+    UnterminatedString,
+    /// An atom could not be parsed as a value.
+    InvalidValue(String),
+    /// There is more input after the tree.
+    TrailingInput,
+}
+
+impl fmt::Display for SexprError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            SexprErrorKind::UnexpectedEnd => write!(f, "unexpected end of input")?,
+            SexprErrorKind::UnexpectedClose => write!(f, "unmatched `)`")?,
+            SexprErrorKind::MissingValue => write!(f, "list without a node value")?,
+            SexprErrorKind::UnterminatedString => write!(f, "unterminated string")?,
+            SexprErrorKind::InvalidValue(atom) => write!(f, "invalid value `{atom}`")?,
+            SexprErrorKind::TrailingInput => write!(f, "unexpected input after the tree")?,
+        }
+        write!(f, " at offset {}", self.offset)
+    }
+}
+
+impl Error for SexprError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let tree = crate::tree! {
+            String::from("say \"hi\"") => [String::new(), String::from("a\\b") => [String::from("(x)")]]
+        };
+        let text = to_sexpr(&tree);
+        assert_eq!(text, r#"("say \"hi\"" "" (a\b "(x)"))"#);
+        assert_eq!(parse_sexpr::<String>(&text).unwrap(), tree);
+
+        let mut deep = Node::new(0);
+        for i in 1..100_000 {
+            deep = Node::with_children(i, [deep]);
+        }
+        let text = to_sexpr(&deep);
+        assert_eq!(to_sexpr(&parse_sexpr::<i32>(&text).unwrap()), text);
+    }
+
+    #[test]
+    fn test_errors() {
+        let kind = |input| parse_sexpr::<i32>(input).unwrap_err().kind;
+        assert_eq!(kind(""), SexprErrorKind::UnexpectedEnd);
+        assert_eq!(kind("(1 2"), SexprErrorKind::UnexpectedEnd);
+        assert_eq!(kind("1)"), SexprErrorKind::TrailingInput);
+        assert_eq!(kind(")"), SexprErrorKind::UnexpectedClose);
+        assert_eq!(kind("(() 1)"), SexprErrorKind::MissingValue);
+        assert_eq!(kind("(\"1 2)"), SexprErrorKind::UnterminatedString);
+        assert_eq!(kind("1 2"), SexprErrorKind::TrailingInput);
+    }
+}
@@ -0,0 +1,265 @@
+use std::{
+    collections::HashMap,
+    hash::{BuildHasher, Hash, RandomState},
+    mem,
+    sync::Arc,
+};
+
+use crate::{iter::TreeNode, traversal_order::DepthFirst, tree::Node};
+
+/// A node of a tree whose identical subtrees are stored once and shared through [`Arc`],
+/// built by a [`SubtreeInterner`].
+///
+/// The node and its subtree cannot be changed once built, which is what makes sharing
+/// them safe. It implements [`TreeNode`], so the usual traversals see it as a tree, with a
+/// shared subtree visited once per place it occurs; [`GraphIter`](crate::graph::GraphIter)
+/// visits each shared subtree once.
+///
+/// # Type Parameters
+///
+/// * `T` - The type of value stored in each node.
+#[derive(Debug)]
+pub struct SharedNode<T> {
+    /// The value stored in this node.
+    value: T,
+    /// The children of this node, possibly shared with other parents.
+    children: Vec<Arc<SharedNode<T>>>,
+}
+
+impl<T> SharedNode<T> {
+    /// Returns the value stored in this node.
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    /// Returns the shared handles of the children of this node.
+    pub fn shared_children(&self) -> &[Arc<SharedNode<T>>] {
+        &self.children
+    }
+
+    /// Copies the tree rooted at this node into a [`Node`] tree, with every shared subtree
+    /// copied at each place it occurs.
+    pub fn to_node(&self) -> Node<T>
+    where
+        T: Clone,
+    {
+        self.materialize::<DepthFirst, _>(|node| node.value.clone())
+    }
+}
+
+impl<T> TreeNode for SharedNode<T> {
+    fn children(&self) -> impl DoubleEndedIterator<Item = &Self> {
+        self.children.iter().map(|child| &**child)
+    }
+}
+
+/// Drops the subtree iteratively.
+///
+/// The children only owned by this node are moved onto a work list, so dropping a deep
+/// tree does not overflow the stack. Shared children are left to their other owners.
+impl<T> Drop for SharedNode<T> {
+    fn drop(&mut self) {
+        let mut stack = mem::take(&mut self.children);
+        while let Some(child) = stack.pop() {
+            if let Some(mut child) = Arc::into_inner(child) {
+                stack.append(&mut child.children);
+            }
+        }
+    }
+}
+
+/// Hash-conses trees into [`SharedNode`]s, so that every distinct subtree is stored once.
+///
+/// Two subtrees are identical if their roots have equal values and identical children in
+/// the same order. The interner keeps one handle to each distinct subtree it has built,
+/// and hands out clones of that handle for any identical subtree built later, so trees
+/// with a lot of repetition, such as expression trees, take memory in the number of
+/// distinct subtrees rather than the number of nodes. Subtrees are compared by value at
+/// their roots and by address for their children, so each comparison takes time in the
+/// number of children.
+///
+/// # Type Parameters
+///
+/// * `T` - The type of value stored in each node.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use tree_iter::prelude::*;
+/// use tree_iter::shared::SubtreeInterner;
+/// use tree_iter::tree;
+///
+/// // (x + y) * (x + y) - (x + y)
+/// let expr = tree! { "-" => ["*" => ["+" => ["x", "y"], "+" => ["x", "y"]], "+" => ["x", "y"]] };
+///
+/// let mut interner = SubtreeInterner::new();
+/// let shared = interner.intern_tree(expr.clone());
+/// assert_eq!(shared.count(), 11);
+/// assert_eq!(interner.len(), 5);
+///
+/// let sum = &shared.shared_children()[1];
+/// assert!(Arc::ptr_eq(sum, &shared.shared_children()[0].shared_children()[0]));
+/// assert_eq!(shared.to_node(), expr);
+/// ```
+#[derive(Debug)]
+pub struct SubtreeInterner<T> {
+    /// The distinct subtrees, grouped by hash.
+    subtrees: HashMap<u64, Vec<Arc<SharedNode<T>>>>,
+    /// The number of distinct subtrees.
+    len: usize,
+    /// Hashes the values and children of subtrees.
+    hasher: RandomState,
+}
+
+impl<T: Eq + Hash> SubtreeInterner<T> {
+    /// Creates an interner with no subtrees.
+    pub fn new() -> Self {
+        Self {
+            subtrees: HashMap::new(),
+            len: 0,
+            hasher: RandomState::new(),
+        }
+    }
+
+    /// Returns the shared node with the given value and children, building it if no
+    /// identical subtree was built before.
+    ///
+    /// The children are compared by address, so they should come from this interner for
+    /// identical subtrees to be found.
+    pub fn intern(&mut self, value: T, children: Vec<Arc<SharedNode<T>>>) -> Arc<SharedNode<T>> {
+        let addresses: Vec<*const SharedNode<T>> = children.iter().map(Arc::as_ptr).collect();
+        let hash = self.hasher.hash_one((&value, &addresses));
+        let group = self.subtrees.entry(hash).or_default();
+        let found = group.iter().find(|node| {
+            node.value == value
+                && node.children.len() == children.len()
+                && node
+                    .children
+                    .iter()
+                    .zip(&children)
+                    .all(|(a, b)| Arc::ptr_eq(a, b))
+        });
+        if let Some(node) = found {
+            return Arc::clone(node);
+        }
+        let node = Arc::new(SharedNode { value, children });
+        group.push(Arc::clone(&node));
+        self.len += 1;
+        node
+    }
+
+    /// Converts a [`Node`] tree into shared nodes, bottom-up and iteratively, so the tree
+    /// may be of any depth.
+    pub fn intern_tree(&mut self, root: Node<T>) -> Arc<SharedNode<T>> {
+        enum Visit<T> {
+            Enter(Node<T>),
+            Exit(T, usize),
+        }
+
+        let mut stack = vec![Visit::Enter(root)];
+        let mut built = Vec::new();
+        while let Some(visit) = stack.pop() {
+            match visit {
+                Visit::Enter(node) => {
+                    let (value, children) = node.into_parts();
+                    stack.push(Visit::Exit(value, children.len()));
+                    stack.extend(children.into_iter().rev().map(Visit::Enter));
+                }
+                Visit::Exit(value, count) => {
+                    let children = built.split_off(built.len() - count);
+                    built.push(self.intern(value, children));
+                }
+            }
+        }
+        built.pop().expect("the root is built last")
+    }
+}
+
+impl<T> SubtreeInterner<T> {
+    /// Returns the number of distinct subtrees built so far.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether no subtree has been built yet.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<T> Clone for SubtreeInterner<T> {
+    /// Returns an interner sharing the subtrees built so far, without cloning any value.
+    fn clone(&self) -> Self {
+        Self {
+            subtrees: self.subtrees.clone(),
+            len: self.len,
+            hasher: self.hasher.clone(),
+        }
+    }
+}
+
+impl<T: Eq + Hash> Default for SubtreeInterner<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sharing_and_deep_trees() {
+        // A complete binary tree of one value shares one node per level
+        let mut interner = SubtreeInterner::new();
+        let full = Node::unfold::<DepthFirst, _>(0u32, |depth| {
+            let children = if depth < 12 {
+                vec![depth + 1; 2]
+            } else {
+                vec![]
+            };
+            (7, children)
+        });
+        let shared = interner.intern_tree(full.clone());
+        assert_eq!(interner.len(), 13);
+        assert_eq!(shared.count(), full.count());
+        assert_eq!(shared.to_node(), full);
+
+        // Interning again builds nothing new
+        let again = interner.intern_tree(full);
+        assert!(Arc::ptr_eq(&shared, &again));
+        assert_eq!(interner.len(), 13);
+
+        // Deep chains are built and dropped without recursion
+        let chain = Node::unfold::<DepthFirst, _>(0u32, |depth| {
+            let children = if depth < 100_000 {
+                vec![depth + 1]
+            } else {
+                vec![]
+            };
+            (depth % 2, children)
+        });
+        let mut interner = SubtreeInterner::new();
+        let shared = interner.intern_tree(chain);
+        assert_eq!(interner.len(), 100_001);
+        assert_eq!(shared.height(), 100_000);
+        drop(interner);
+        drop(shared);
+    }
+
+    #[test]
+    fn test_clone_shares_subtrees() {
+        #[derive(Debug, PartialEq, Eq, Hash)]
+        struct Token(u8);
+
+        let mut interner = SubtreeInterner::new();
+        let first = interner.intern(Token(1), Vec::new());
+        let mut copy = interner.clone();
+        assert_eq!(copy.len(), 1);
+        assert!(Arc::ptr_eq(&copy.intern(Token(1), Vec::new()), &first));
+        copy.intern(Token(2), Vec::new());
+        assert_eq!(copy.len(), 2);
+        assert_eq!(interner.len(), 1);
+    }
+}
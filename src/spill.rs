@@ -0,0 +1,467 @@
+use std::{
+    collections::VecDeque,
+    fmt,
+    fs::{self, File, OpenOptions},
+    io::{self, BufReader, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::iter::TreeNode;
+
+/// Storage for the frontier of a breadth-first traversal.
+///
+/// The frontier is a first-in, first-out queue of child-index paths. Paths, unlike node
+/// references, can be written out of memory, which lets implementations keep very wide
+/// frontiers on disk.
+pub trait FrontierStore {
+    /// Appends a path to the back of the queue.
+    fn push_back(&mut self, path: Vec<usize>) -> io::Result<()>;
+
+    /// Removes the path at the front of the queue, or returns `None` if the queue is empty.
+    fn pop_front(&mut self) -> io::Result<Option<Vec<usize>>>;
+
+    /// Returns the number of paths in the queue.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if the queue is empty.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// A frontier store that keeps every path in memory.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryFrontier {
+    /// The queued paths.
+    queue: VecDeque<Vec<usize>>,
+}
+
+impl MemoryFrontier {
+    /// Creates an empty in-memory frontier.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl FrontierStore for MemoryFrontier {
+    fn push_back(&mut self, path: Vec<usize>) -> io::Result<()> {
+        self.queue.push_back(path);
+        Ok(())
+    }
+
+    fn pop_front(&mut self) -> io::Result<Option<Vec<usize>>> {
+        Ok(self.queue.pop_front())
+    }
+
+    fn len(&self) -> usize {
+        self.queue.len()
+    }
+}
+
+/// Counter keeping the names of spill files unique within the process.
+static SPILL_FILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// A frontier store that spills to a temporary file once it exceeds a memory threshold.
+///
+/// At most `threshold` paths are kept in memory. Paths pushed while the memory queue is full
+/// are appended to the spill file and read back in batches as the memory queue drains,
+/// which preserves first-in, first-out order. The spill file is created lazily and removed
+/// when the store is dropped.
+#[derive(Debug)]
+pub struct SpillFrontier {
+    /// The paths at the front of the queue.
+    memory: VecDeque<Vec<usize>>,
+    /// The maximum number of paths kept in memory.
+    threshold: usize,
+    /// The directory the spill file is created in.
+    dir: PathBuf,
+    /// The spill file and its path, once created.
+    file: Option<(File, PathBuf)>,
+    /// The offset of the first unread path in the spill file.
+    read_pos: u64,
+    /// The offset at which the next spilled path is written.
+    write_pos: u64,
+    /// The number of paths in the spill file.
+    spilled: usize,
+}
+
+impl SpillFrontier {
+    /// Creates a frontier that spills to the system's temporary directory.
+    ///
+    /// # Parameters
+    ///
+    /// * `threshold` - The maximum number of paths kept in memory (at least one).
+    pub fn new(threshold: usize) -> Self {
+        Self::with_dir(threshold, std::env::temp_dir())
+    }
+
+    /// Creates a frontier that spills to a file in `dir`.
+    ///
+    /// # Parameters
+    ///
+    /// * `threshold` - The maximum number of paths kept in memory (at least one).
+    /// * `dir` - The directory the spill file is created in.
+    pub fn with_dir(threshold: usize, dir: impl AsRef<Path>) -> Self {
+        Self {
+            memory: VecDeque::new(),
+            threshold: threshold.max(1),
+            dir: dir.as_ref().to_path_buf(),
+            file: None,
+            read_pos: 0,
+            write_pos: 0,
+            spilled: 0,
+        }
+    }
+
+    /// Returns the number of paths currently stored in the spill file.
+    pub fn spilled(&self) -> usize {
+        self.spilled
+    }
+
+    /// Returns the spill file, creating it if needed.
+    fn file(&mut self) -> io::Result<&mut File> {
+        if self.file.is_none() {
+            let name = format!(
+                "tree-iter-frontier-{}-{}",
+                std::process::id(),
+                SPILL_FILE_COUNTER.fetch_add(1, Ordering::Relaxed)
+            );
+            let path = self.dir.join(name);
+            let file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create_new(true)
+                .open(&path)?;
+            self.file = Some((file, path));
+        }
+        Ok(&mut self.file.as_mut().unwrap().0)
+    }
+
+    /// Moves the next batch of paths from the spill file into memory.
+    fn reload(&mut self) -> io::Result<()> {
+        let count = self.spilled.min(self.threshold);
+        let read_pos = self.read_pos;
+        let file = self.file()?;
+        file.seek(SeekFrom::Start(read_pos))?;
+        let mut reader = BufReader::new(&*file);
+        let mut consumed = 0;
+        let mut batch = Vec::with_capacity(count);
+        for _ in 0..count {
+            let len = read_u64(&mut reader)? as usize;
+            let path = (0..len)
+                .map(|_| read_u64(&mut reader).map(|i| i as usize))
+                .collect::<io::Result<Vec<_>>>()?;
+            consumed += 8 * (len as u64 + 1);
+            batch.push(path);
+        }
+        self.memory.extend(batch);
+        self.read_pos += consumed;
+        self.spilled -= count;
+        if self.spilled == 0 {
+            self.file()?.set_len(0)?;
+            self.read_pos = 0;
+            self.write_pos = 0;
+        }
+        Ok(())
+    }
+}
+
+impl FrontierStore for SpillFrontier {
+    fn push_back(&mut self, path: Vec<usize>) -> io::Result<()> {
+        if self.spilled == 0 && self.memory.len() < self.threshold {
+            self.memory.push_back(path);
+            return Ok(());
+        }
+        let mut buf = Vec::with_capacity(8 * (path.len() + 1));
+        buf.extend_from_slice(&(path.len() as u64).to_le_bytes());
+        for i in path {
+            buf.extend_from_slice(&(i as u64).to_le_bytes());
+        }
+        let write_pos = self.write_pos;
+        let file = self.file()?;
+        file.seek(SeekFrom::Start(write_pos))?;
+        file.write_all(&buf)?;
+        self.write_pos += buf.len() as u64;
+        self.spilled += 1;
+        Ok(())
+    }
+
+    fn pop_front(&mut self) -> io::Result<Option<Vec<usize>>> {
+        if self.memory.is_empty() && self.spilled > 0 {
+            self.reload()?;
+        }
+        Ok(self.memory.pop_front())
+    }
+
+    fn len(&self) -> usize {
+        self.memory.len() + self.spilled
+    }
+}
+
+impl Drop for SpillFrontier {
+    /// Removes the spill file, if one was created.
+    fn drop(&mut self) {
+        if let Some((file, path)) = self.file.take() {
+            drop(file);
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// Reads a little-endian `u64`.
+fn read_u64(reader: &mut impl Read) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// The siblings following a node, still to be walked.
+type Siblings<'a, N> = Box<dyn Iterator<Item = &'a N> + 'a>;
+
+/// A node on the way to the node whose children are visited, with its later siblings.
+struct Step<'a, N> {
+    /// The index of the node among its siblings.
+    index: usize,
+    /// The node.
+    node: &'a N,
+    /// The siblings after the node.
+    rest: Siblings<'a, N>,
+}
+
+/// A breadth-first iterator whose frontier lives in a [`FrontierStore`].
+///
+/// Instead of keeping a reference to every pending node, the frontier stores the
+/// child-index paths of the nodes whose children are still to be visited, one path per
+/// parent, and the children of each parent are walked with a single iterator. Parents are
+/// found again through a cursor that keeps the path to the last one found, with the
+/// iterators over the siblings along it, and only moves forward along each level. Visiting
+/// a level then walks each node above it at most once, whatever the width of the tree, and
+/// the frontier of very wide trees can be kept out of memory.
+///
+/// Errors from the store are yielded once, after which the iteration ends.
+///
+/// # Type Parameters
+///
+/// * `'a` - The lifetime of the tree nodes being traversed.
+/// * `N` - The type of tree node.
+/// * `S` - The frontier store.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::prelude::*;
+/// use tree_iter::spill::{SpillBfs, SpillFrontier};
+/// use tree_iter::tree;
+///
+/// let tree = tree! { 1 => [2 => [4, 5], 3 => [6, 7]] };
+///
+/// // Keep at most two paths in memory
+/// let values: Vec<i32> = SpillBfs::new(&tree, SpillFrontier::new(2))
+///     .map(|node| node.map(|node| node.value))
+///     .collect::<std::io::Result<_>>()
+///     .unwrap();
+/// assert_eq!(values, vec![1, 2, 3, 4, 5, 6, 7]);
+/// ```
+pub struct SpillBfs<'a, N, S> {
+    /// The root node that paths are resolved against.
+    root: &'a N,
+    /// The paths of the nodes whose children are still to be visited.
+    frontier: S,
+    /// Whether the root is still to be visited.
+    root_pending: bool,
+    /// The path to the node whose children are being visited, the index of the next
+    /// child, and the children still to be visited.
+    expanding: Option<(Vec<usize>, usize, Siblings<'a, N>)>,
+    /// The nodes on the way to the last node found from its path.
+    cursor: Vec<Step<'a, N>>,
+    /// An error from queueing the root, yielded by the first call to `next`.
+    pending_error: Option<io::Error>,
+    /// Whether the iteration ended because of an error.
+    failed: bool,
+}
+
+impl<'a, N: TreeNode, S: FrontierStore> SpillBfs<'a, N, S> {
+    /// Creates a breadth-first iterator from `root` using the given frontier store.
+    ///
+    /// Any paths already in the store are taken as nodes whose children are visited after
+    /// the root, and before its children.
+    pub fn new(root: &'a N, mut frontier: S) -> Self {
+        let pending_error = frontier.push_back(Vec::new()).err();
+        Self {
+            root,
+            frontier,
+            root_pending: true,
+            expanding: None,
+            cursor: Vec::new(),
+            pending_error,
+            failed: false,
+        }
+    }
+
+    /// Returns the frontier store.
+    pub fn frontier(&self) -> &S {
+        &self.frontier
+    }
+
+    /// Visits the next node.
+    fn step(&mut self) -> io::Result<Option<&'a N>> {
+        if self.root_pending {
+            self.root_pending = false;
+            return Ok(Some(self.root));
+        }
+        loop {
+            if let Some((path, index, children)) = &mut self.expanding {
+                if let Some(child) = children.next() {
+                    if child.children().next().is_some() {
+                        let mut child_path = Vec::with_capacity(path.len() + 1);
+                        child_path.extend_from_slice(path);
+                        child_path.push(*index);
+                        self.frontier.push_back(child_path)?;
+                    }
+                    *index += 1;
+                    return Ok(Some(child));
+                }
+                self.expanding = None;
+            }
+            let Some(path) = self.frontier.pop_front()? else {
+                return Ok(None);
+            };
+            let node = self.find(&path)?;
+            self.expanding = Some((path, 0, Box::new(node.children())));
+        }
+    }
+
+    /// Returns the node at `path`, moving the cursor there.
+    ///
+    /// The part of the cursor shared with `path` is kept, and where `path` leads to a later
+    /// sibling, the cursor walks on to it rather than starting over from its parent.
+    fn find(&mut self, path: &[usize]) -> io::Result<&'a N> {
+        let missing = || {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "frontier path is not in the tree",
+            )
+        };
+        let mut depth = 0;
+        while depth < self.cursor.len().min(path.len()) && self.cursor[depth].index == path[depth] {
+            depth += 1;
+        }
+        if depth < self.cursor.len().min(path.len()) && self.cursor[depth].index < path[depth] {
+            let step = &mut self.cursor[depth];
+            let skip = path[depth] - step.index - 1;
+            step.node = step.rest.nth(skip).ok_or_else(missing)?;
+            step.index = path[depth];
+            depth += 1;
+        }
+        self.cursor.truncate(depth);
+        for &index in &path[depth..] {
+            let parent = self.cursor.last().map_or(self.root, |step| step.node);
+            let mut rest: Siblings<'a, N> = Box::new(parent.children());
+            let node = rest.nth(index).ok_or_else(missing)?;
+            self.cursor.push(Step { index, node, rest });
+        }
+        Ok(self.cursor.last().map_or(self.root, |step| step.node))
+    }
+}
+
+impl<'a, N: TreeNode, S: FrontierStore> Iterator for SpillBfs<'a, N, S> {
+    type Item = io::Result<&'a N>;
+
+    /// Returns the next node in breadth-first order.
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let result = match self.pending_error.take() {
+            Some(e) => Err(e),
+            None => self.step(),
+        };
+        self.failed = result.is_err();
+        result.transpose()
+    }
+}
+
+impl<N, S: fmt::Debug> fmt::Debug for SpillBfs<'_, N, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SpillBfs")
+            .field("frontier", &self.frontier)
+            .field("failed", &self.failed)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use crate::tree::Node;
+
+    #[test]
+    fn test_spill_matches_breadth_first() {
+        let tree = Node::with_children(
+            0,
+            (1..=20).map(|i| Node::with_children(i, (0..5).map(|j| Node::new(i * 100 + j)))),
+        );
+        let expected: Vec<i32> = tree.iter::<BreadthFirst>().map(|n| n.value).collect();
+
+        let mut iter = SpillBfs::new(&tree, SpillFrontier::new(3));
+        let mut values = Vec::new();
+        let mut max_spilled = 0;
+        while let Some(node) = iter.next() {
+            values.push(node.unwrap().value);
+            max_spilled = max_spilled.max(iter.frontier().spilled());
+        }
+        assert_eq!(values, expected);
+        assert!(max_spilled > 0);
+        assert!(iter.frontier().is_empty());
+
+        let values: Vec<i32> = SpillBfs::new(&tree, MemoryFrontier::new())
+            .map(|node| node.unwrap().value)
+            .collect();
+        assert_eq!(values, expected);
+    }
+
+    thread_local! {
+        /// The number of children walked by [`Counted::children`] on this thread.
+        static WALKED: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+    }
+
+    /// A tree node counting the children walked through it.
+    struct Counted(Vec<Counted>);
+
+    impl TreeNode for Counted {
+        fn children(&self) -> impl DoubleEndedIterator<Item = &Self> {
+            self.0
+                .iter()
+                .inspect(|_| WALKED.with(|walked| walked.set(walked.get() + 1)))
+        }
+    }
+
+    #[test]
+    fn test_wide_parents_are_walked_once() {
+        // A wide node whose children all have children of their own, under a narrow chain
+        let wide = Counted((0..5_000).map(|_| Counted(vec![Counted(vec![])])).collect());
+        let tree = Counted(vec![Counted(vec![]), Counted(vec![Counted(vec![]), wide])]);
+        let count = tree.count();
+        assert_eq!(count, 10_005);
+
+        WALKED.with(|walked| walked.set(0));
+        let visited = SpillBfs::new(&tree, SpillFrontier::new(16))
+            .map(|node| node.map(|_| ()))
+            .collect::<io::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(visited.len(), count);
+        // Each node is walked to a bounded number of times, rather than once per sibling
+        // before it
+        let walked = WALKED.with(|walked| walked.get());
+        assert!(walked < 5 * count, "walked {walked} children");
+
+        // Paths that are not in the tree end the iteration with an error
+        let mut frontier = MemoryFrontier::new();
+        frontier.push_back(vec![1, 7]).unwrap();
+        let results: Vec<_> = SpillBfs::new(&tree, frontier).collect();
+        assert_eq!(results.len(), 2);
+        assert!(results[1].is_err());
+    }
+}
@@ -0,0 +1,67 @@
+use crate::iter::TreeNode;
+
+/// A tree node with statically allocated children.
+///
+/// Since its children are a `&'static` slice, a whole tree of `StaticNode`s can be written
+/// as a `const` or `static` item, with no setup or allocation at runtime. It implements
+/// `TreeNode`, so it can be traversed like any other tree.
+///
+/// # Type Parameters
+///
+/// * `T` - The type of value stored in each node.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::prelude::*;
+/// use tree_iter::static_node::StaticNode;
+///
+/// static MENU: StaticNode<&str> = StaticNode::with_children(
+///     "File",
+///     &[
+///         StaticNode::new("Open"),
+///         StaticNode::with_children("Recent", &[StaticNode::new("notes.txt")]),
+///         StaticNode::new("Quit"),
+///     ],
+/// );
+///
+/// let items: Vec<&str> = MENU.iter::<DepthFirst>().map(|node| node.value).collect();
+/// assert_eq!(items, vec!["File", "Open", "Recent", "notes.txt", "Quit"]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StaticNode<T: 'static> {
+    /// The value stored in this node.
+    pub value: T,
+    /// The children of this node.
+    pub children: &'static [StaticNode<T>],
+}
+
+impl<T> StaticNode<T> {
+    /// Creates a new node with the given value and no children.
+    pub const fn new(value: T) -> Self {
+        Self {
+            value,
+            children: &[],
+        }
+    }
+
+    /// Creates a new node with the given value and children.
+    pub const fn with_children(value: T, children: &'static [StaticNode<T>]) -> Self {
+        Self { value, children }
+    }
+}
+
+/// Implementation of `TreeNode` for `StaticNode<T>`.
+///
+/// This allows immutable iteration over the tree.
+impl<T> TreeNode for StaticNode<T> {
+    /// Returns an iterator over the children of this node.
+    fn children(&self) -> impl DoubleEndedIterator<Item = &Self> {
+        self.children.iter()
+    }
+
+    /// Returns the children of this node, which are stored contiguously.
+    fn children_slice(&self) -> Option<&[Self]> {
+        Some(self.children)
+    }
+}
@@ -0,0 +1,139 @@
+use std::{iter::Sum, ops::Add};
+
+use crate::{iter::TreeNode, traversal_order::BreadthFirst, tree::Node};
+
+/// Statistics over trees with numeric values.
+impl<T: Copy> Node<T> {
+    /// Returns the sum of the values of all nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::tree;
+    ///
+    /// assert_eq!(tree! { 1 => [2 => [4, 5], 3] }.sum(), 15);
+    /// ```
+    pub fn sum(&self) -> T
+    where
+        T: Sum,
+    {
+        self.iter_dfs().map(|node| node.value).sum()
+    }
+
+    /// Returns the mean of the values at each depth, starting with the root at depth 0.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::tree;
+    ///
+    /// let tree = tree! { 1 => [2 => [4, 5, 6], 3 => [7]] };
+    /// assert_eq!(tree.mean_by_depth(), vec![1.0, 2.5, 5.5]);
+    /// ```
+    pub fn mean_by_depth(&self) -> Vec<f64>
+    where
+        T: Into<f64>,
+    {
+        let mut totals: Vec<(f64, usize)> = Vec::new();
+        for (depth, node) in self.traverse().order(BreadthFirst).with_depth().build() {
+            if depth == totals.len() {
+                totals.push((0.0, 0));
+            }
+            totals[depth].0 += node.value.into();
+            totals[depth].1 += 1;
+        }
+        totals
+            .into_iter()
+            .map(|(total, count)| total / count as f64)
+            .collect()
+    }
+
+    /// Returns the largest sum of values along any path between two nodes of the tree.
+    ///
+    /// A path goes up from one node to a common ancestor and down to another, visiting
+    /// each node at most once, and has at least one node. Subpaths with a negative sum are
+    /// left out, with `T::default()` taken as zero. The tree is folded iteratively, so it
+    /// may be of any depth.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::tree;
+    ///
+    /// // The best path is 4 -> 2 -> 1 -> 3
+    /// let tree = tree! { 1 => [2 => [4, -5], 3 => [-1]] };
+    /// assert_eq!(tree.max_path_sum(), 10);
+    ///
+    /// assert_eq!(tree! { -3 => [-1, -2] }.max_path_sum(), -1);
+    /// ```
+    pub fn max_path_sum(&self) -> T
+    where
+        T: Add<Output = T> + PartialOrd + Default,
+    {
+        let max = |a: T, b: T| if b > a { b } else { a };
+        let zero = T::default();
+        // For each subtree: the best path going down from its root, and the best path anywhere
+        let (_, best) = self.fold(|node, children: Vec<(T, T)>| {
+            let (mut first, mut second) = (zero, zero);
+            let mut best = None;
+            for &(down, child_best) in &children {
+                if down > first {
+                    second = first;
+                    first = down;
+                } else if down > second {
+                    second = down;
+                }
+                best = Some(best.map_or(child_best, |best| max(best, child_best)));
+            }
+            let through = node.value + first + second;
+            let best = best.map_or(through, |best| max(best, through));
+            (node.value + first, best)
+        });
+        best
+    }
+
+    /// Returns the sum of each value multiplied by the depth of its node, with the root at
+    /// depth 0.
+    ///
+    /// With values as the weights or probabilities of the nodes, this is the total or
+    /// expected cost of reaching them from the root.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::tree;
+    ///
+    /// let tree = tree! { 0.0 => [0.5, 0.0 => [0.25, 0.25]] };
+    /// assert_eq!(tree.weighted_depth(), 1.5);
+    /// ```
+    pub fn weighted_depth(&self) -> f64
+    where
+        T: Into<f64>,
+    {
+        self.traverse()
+            .with_depth()
+            .build()
+            .map(|(depth, node)| node.value.into() * depth as f64)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tree::Node;
+
+    #[test]
+    fn test_deep_stats() {
+        let mut deep = Node::new(1);
+        for i in 0..100_000 {
+            deep = Node::with_children(if i % 2 == 0 { -1 } else { 2 }, [deep]);
+        }
+        assert_eq!(deep.sum(), 50_001);
+        assert_eq!(deep.max_path_sum(), 50_001);
+        assert_eq!(deep.mean_by_depth().len(), 100_001);
+        let expected: f64 = (0..100_000)
+            .map(|i| (if i % 2 == 0 { -1.0 } else { 2.0 }) * (99_999 - i) as f64)
+            .sum();
+        assert_eq!(deep.weighted_depth(), expected + 100_000.0);
+    }
+}
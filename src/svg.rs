@@ -0,0 +1,365 @@
+use std::fmt::{self, Write};
+
+use crate::{
+    iter::TreeNode,
+    layout::{Point, TidyLayout},
+};
+
+/// Attributes of an SVG element, as `(name, value)` pairs.
+type Attributes = Vec<(String, String)>;
+/// Gives the attributes of a node.
+type NodeAttributesFn<'a, N> = Box<dyn FnMut(&N) -> Attributes + 'a>;
+/// Gives the attributes of the edge from a parent to a child.
+type EdgeAttributesFn<'a, N> = Box<dyn FnMut(&N, &N) -> Attributes + 'a>;
+
+/// Renders a tree as an SVG image, with `label` giving the label of each node.
+///
+/// See [`SvgWriter`] for more options.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::svg::to_svg;
+/// use tree_iter::tree;
+///
+/// let tree = tree! { 1 => [2, 3] };
+/// let svg = to_svg(&tree, |node| node.value.to_string());
+/// assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+/// assert!(svg.contains(">3</text>"));
+/// ```
+pub fn to_svg<N: TreeNode>(root: &N, label: impl FnMut(&N) -> String) -> String {
+    SvgWriter::new(label).render(root)
+}
+
+/// Configurable SVG drawing of any [`TreeNode`], laid out with [`TidyLayout`].
+///
+/// Each node is drawn as a circle with its label centered on it, and each edge as a line
+/// from the parent to the child, drawn behind the nodes. The elements have the classes
+/// `node`, `label` and `edge`, so they can be styled with a stylesheet, and the attributes
+/// of each node and edge can be set with [`SvgWriter::node_attributes`] and
+/// [`SvgWriter::edge_attributes`]; these replace the attributes the element would
+/// otherwise have with the same name, such as its `fill`, `stroke`, `class` or position,
+/// and the last of several with the same name wins. Labels and attribute values are
+/// escaped. Attribute names must be XML names: unlike DOT, XML has no way to quote a name,
+/// so rendering panics on any other name rather than write a broken document. The tree is
+/// walked iteratively, so trees of any depth can be rendered.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::svg::SvgWriter;
+/// use tree_iter::tree;
+/// use tree_iter::tree::Node;
+///
+/// let tree = tree! { "root" => ["ok", "failed"] };
+/// let svg = SvgWriter::new(|node: &Node<&str>| node.value.to_string())
+///     .radius(25.0)
+///     .style(".label { font-family: monospace; }")
+///     .node_attributes(|node| {
+///         if node.value == "failed" {
+///             vec![("fill".to_string(), "red".to_string())]
+///         } else {
+///             vec![]
+///         }
+///     })
+///     .edge_attributes(|_, _| vec![("stroke-dasharray".to_string(), "4".to_string())])
+///     .render(&tree);
+/// assert!(svg.contains("<style>.label { font-family: monospace; }</style>"));
+/// assert!(svg.contains("r=\"25\" stroke=\"black\" fill=\"red\"/>"));
+/// assert!(svg.contains("stroke=\"black\" stroke-dasharray=\"4\"/>"));
+/// ```
+pub struct SvgWriter<'a, N> {
+    /// Gives the label of a node.
+    label: Box<dyn FnMut(&N) -> String + 'a>,
+    /// Gives extra attributes of a node's circle.
+    node_attributes: Option<NodeAttributesFn<'a, N>>,
+    /// Gives extra attributes of the line from a parent to a child.
+    edge_attributes: Option<EdgeAttributesFn<'a, N>>,
+    /// A stylesheet embedded in the image.
+    style: Option<String>,
+    /// The positions of the nodes, before the margin is added.
+    layout: TidyLayout,
+    /// The radius of the circle of each node.
+    radius: f64,
+    /// The space around the drawing.
+    margin: f64,
+}
+
+impl<'a, N: TreeNode> SvgWriter<'a, N> {
+    /// Creates a writer with nodes 60 units apart, both across and down, with `label`
+    /// giving the label of each node.
+    pub fn new(label: impl FnMut(&N) -> String + 'a) -> Self {
+        Self {
+            label: Box::new(label),
+            node_attributes: None,
+            edge_attributes: None,
+            style: None,
+            layout: TidyLayout::new()
+                .sibling_distance(60.0)
+                .level_distance(60.0),
+            radius: 20.0,
+            margin: 10.0,
+        }
+    }
+
+    /// Sets the layout that places the nodes.
+    pub fn layout(mut self, layout: TidyLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Sets the radius of the circle of each node.
+    pub fn radius(mut self, radius: f64) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    /// Sets the space around the drawing.
+    pub fn margin(mut self, margin: f64) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    /// Embeds a stylesheet in the image, as the contents of a `<style>` element.
+    pub fn style(mut self, css: impl Into<String>) -> Self {
+        self.style = Some(css.into());
+        self
+    }
+
+    /// Sets a function giving extra attributes of the circle of each node.
+    ///
+    /// The names must be XML names, such as `fill` or `stroke-width`; rendering panics on
+    /// any other name, which could not be written without breaking the document. An
+    /// attribute replaces the one the circle would otherwise have with the same name.
+    pub fn node_attributes(mut self, attributes: impl FnMut(&N) -> Attributes + 'a) -> Self {
+        self.node_attributes = Some(Box::new(attributes));
+        self
+    }
+
+    /// Sets a function giving extra attributes of the line of each edge, from the parent
+    /// and the child.
+    ///
+    /// The names must be XML names, as for [`SvgWriter::node_attributes`].
+    pub fn edge_attributes(mut self, attributes: impl FnMut(&N, &N) -> Attributes + 'a) -> Self {
+        self.edge_attributes = Some(Box::new(attributes));
+        self
+    }
+
+    /// Renders the tree to a string.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the name of a node or edge attribute is not an XML name.
+    pub fn render(&mut self, root: &N) -> String {
+        let mut out = String::new();
+        self.write(root, &mut out)
+            .expect("writing to a String cannot fail");
+        out
+    }
+
+    /// Writes the tree to `out`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the name of a node or edge attribute is not an XML name.
+    pub fn write(&mut self, root: &N, out: &mut impl Write) -> fmt::Result {
+        let inset = self.margin + self.radius;
+        let points: Vec<Point> = self
+            .layout
+            .layout(root)
+            .into_iter()
+            .map(|point| Point {
+                x: point.x + inset,
+                y: point.y + inset,
+            })
+            .collect();
+        let width = points.iter().map(|point| point.x).fold(0.0, f64::max) + inset;
+        let height = points.iter().map(|point| point.y).fold(0.0, f64::max) + inset;
+
+        writeln!(
+            out,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">"
+        )?;
+        if let Some(style) = &self.style {
+            writeln!(out, "  <style>{}</style>", escape(style))?;
+        }
+
+        // Edges first, so that the nodes are drawn over them
+        let mut next_id = 0;
+        let mut stack = vec![(root, None)];
+        while let Some((node, parent)) = stack.pop() {
+            let id = next_id;
+            next_id += 1;
+            if let Some((parent, parent_id)) = parent {
+                let (from, to): (Point, Point) = (points[parent_id], points[id]);
+                let attributes = match &mut self.edge_attributes {
+                    Some(attributes) => attributes(parent, node),
+                    None => Vec::new(),
+                };
+                let fixed = [
+                    ("class", "edge".to_string()),
+                    ("x1", from.x.to_string()),
+                    ("y1", from.y.to_string()),
+                    ("x2", to.x.to_string()),
+                    ("y2", to.y.to_string()),
+                    ("stroke", "black".to_string()),
+                ];
+                write!(out, "  <line")?;
+                write_attributes(out, &fixed, &attributes)?;
+                writeln!(out, "/>")?;
+            }
+            stack.extend(node.children().rev().map(|child| (child, Some((node, id)))));
+        }
+
+        let mut points = points.into_iter();
+        let mut stack = vec![root];
+        while let Some(node) = stack.pop() {
+            let point = points.next().unwrap();
+            let attributes = match &mut self.node_attributes {
+                Some(attributes) => attributes(node),
+                None => Vec::new(),
+            };
+            let fixed = [
+                ("class", "node".to_string()),
+                ("cx", point.x.to_string()),
+                ("cy", point.y.to_string()),
+                ("r", self.radius.to_string()),
+                ("fill", "white".to_string()),
+                ("stroke", "black".to_string()),
+            ];
+            write!(out, "  <circle")?;
+            write_attributes(out, &fixed, &attributes)?;
+            writeln!(out, "/>")?;
+            writeln!(
+                out,
+                "  <text class=\"label\" x=\"{}\" y=\"{}\" text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>",
+                point.x,
+                point.y,
+                escape(&(self.label)(node))
+            )?;
+            stack.extend(node.children().rev());
+        }
+        writeln!(out, "</svg>")
+    }
+}
+
+impl<N> fmt::Debug for SvgWriter<'_, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SvgWriter")
+            .field("style", &self.style)
+            .field("layout", &self.layout)
+            .field("radius", &self.radius)
+            .field("margin", &self.margin)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Writes ` name="value"` pairs: the fixed ones that are not overridden, then the last
+/// custom one of each name, so that no name is written twice.
+fn write_attributes(
+    out: &mut impl Write,
+    fixed: &[(&str, String)],
+    custom: &[(String, String)],
+) -> fmt::Result {
+    for (name, value) in fixed {
+        if !custom.iter().any(|(custom, _)| custom == name) {
+            write!(out, " {}=\"{}\"", name, escape(value))?;
+        }
+    }
+    for (i, (name, value)) in custom.iter().enumerate() {
+        assert!(is_name(name), "invalid SVG attribute name {name:?}");
+        if !custom[i + 1..].iter().any(|(later, _)| later == name) {
+            write!(out, " {}=\"{}\"", name, escape(value))?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns whether `name` is an XML name, which can be written as an attribute name as is.
+fn is_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_' || c == ':')
+        && chars.all(|c| c.is_alphanumeric() || matches!(c, '_' | ':' | '-' | '.'))
+}
+
+/// Escapes a string for use in XML text or a quoted attribute value.
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::Node;
+
+    #[test]
+    fn test_small_tree() {
+        let tree = crate::tree! { "a<b" => ["c & d"] };
+        let svg = SvgWriter::new(|node: &Node<&str>| node.value.to_string())
+            .node_attributes(|_| vec![("stroke".to_string(), "\"blue\"".to_string())])
+            .render(&tree);
+        assert_eq!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"60\" height=\"120\" viewBox=\"0 0 60 120\">\n\
+             \x20 <line class=\"edge\" x1=\"30\" y1=\"30\" x2=\"30\" y2=\"90\" stroke=\"black\"/>\n\
+             \x20 <circle class=\"node\" cx=\"30\" cy=\"30\" r=\"20\" fill=\"white\" stroke=\"&quot;blue&quot;\"/>\n\
+             \x20 <text class=\"label\" x=\"30\" y=\"30\" text-anchor=\"middle\" dominant-baseline=\"central\">a&lt;b</text>\n\
+             \x20 <circle class=\"node\" cx=\"30\" cy=\"90\" r=\"20\" fill=\"white\" stroke=\"&quot;blue&quot;\"/>\n\
+             \x20 <text class=\"label\" x=\"30\" y=\"90\" text-anchor=\"middle\" dominant-baseline=\"central\">c &amp; d</text>\n\
+             </svg>\n"
+        );
+    }
+
+    #[test]
+    fn test_attribute_names() {
+        assert!(is_name("stroke-width"));
+        assert!(is_name("xlink:href"));
+        assert!(is_name("_x.1"));
+        for name in ["", "1x", "a b", "a=\"b\"", "x>", "-x", "a\"", "a/"] {
+            assert!(!is_name(name), "{name}");
+        }
+
+        let tree = crate::tree! { 1 };
+        let result = std::panic::catch_unwind(|| {
+            SvgWriter::new(|node: &Node<i32>| node.value.to_string())
+                .node_attributes(|_| vec![("x\"/><script".to_string(), String::new())])
+                .render(&tree)
+        });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_attributes_are_written_once() {
+        let tree = crate::tree! { 1 => [2] };
+        let svg = SvgWriter::new(|node: &Node<i32>| node.value.to_string())
+            .node_attributes(|_| {
+                vec![
+                    ("class".to_string(), "leaf".to_string()),
+                    ("r".to_string(), "5".to_string()),
+                    ("opacity".to_string(), "0.5".to_string()),
+                    ("opacity".to_string(), "1".to_string()),
+                ]
+            })
+            .edge_attributes(|_, _| vec![("y1".to_string(), "0".to_string())])
+            .render(&tree);
+        assert!(svg.contains(
+            "<line class=\"edge\" x1=\"30\" x2=\"30\" y2=\"90\" stroke=\"black\" y1=\"0\"/>"
+        ));
+        assert!(svg.contains(
+            "<circle cx=\"30\" cy=\"30\" fill=\"white\" stroke=\"black\" class=\"leaf\" r=\"5\" opacity=\"1\"/>"
+        ));
+    }
+}
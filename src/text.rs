@@ -0,0 +1,171 @@
+use crate::{flat::Flat, traversal_order::DepthFirst, tree::Node};
+
+/// Helpers for trees labeled with text, such as `Node<String>` and `Node<&str>`.
+impl<T: AsRef<str>> Node<T> {
+    /// Returns the paths of the nodes whose value contains `needle`, ignoring case, in
+    /// depth-first order.
+    ///
+    /// Case is compared by lowercasing both sides, so it covers all of Unicode.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::tree;
+    ///
+    /// let tree = tree! { "Docs" => ["README.md", "guide" => ["Readme.txt"]] };
+    /// assert_eq!(tree.search_ignore_case("readme"), vec![vec![0], vec![1, 0]]);
+    /// ```
+    pub fn search_ignore_case(&self, needle: &str) -> Vec<Vec<usize>> {
+        let needle = needle.to_lowercase();
+        let flat = Flat::new(self);
+        (0..flat.len())
+            .filter(|&id| {
+                flat.nodes[id]
+                    .value
+                    .as_ref()
+                    .to_lowercase()
+                    .contains(&needle)
+            })
+            .map(|id| flat.path(id))
+            .collect()
+    }
+
+    /// Keeps the nodes whose value starts with `prefix`, along with their ancestors.
+    ///
+    /// Every other node is removed with its subtree. Returns `None` if no node matches.
+    /// The tree is filtered iteratively, so it may be of any depth.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::tree;
+    ///
+    /// let tree = tree! { "src" => ["test_a", "util" => ["test_b", "io"], "main"] };
+    /// assert_eq!(
+    ///     tree.retain_prefix("test_"),
+    ///     Some(tree! { "src" => ["test_a", "util" => ["test_b"]] })
+    /// );
+    /// assert_eq!(tree! { "src" => ["main"] }.retain_prefix("test_"), None);
+    /// ```
+    pub fn retain_prefix(self, prefix: &str) -> Option<Node<T>> {
+        // Find the nodes to keep by their pre-order ids, bottom-up
+        let flat = Flat::new(&self);
+        let mut keep: Vec<bool> = flat
+            .nodes
+            .iter()
+            .map(|node| node.value.as_ref().starts_with(prefix))
+            .collect();
+        for id in (1..flat.len()).rev() {
+            if let Some(parent) = flat.parent[id] {
+                keep[parent] |= keep[id];
+            }
+        }
+        if !keep[0] {
+            return None;
+        }
+        let subtree_end = flat.subtree_end;
+
+        Some(Node::unfold::<DepthFirst, _>((self, 0), |(node, id)| {
+            let (value, children) = node.into_parts();
+            let mut child_id = id + 1;
+            let mut kept = Vec::new();
+            for child in children {
+                if keep[child_id] {
+                    kept.push((child, child_id));
+                }
+                child_id = subtree_end[child_id];
+            }
+            (value, kept)
+        }))
+    }
+
+    /// Returns the values from the root to the node at `path`, joined by `separator`, or
+    /// `None` if there is no node at `path`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::tree;
+    ///
+    /// let tree = tree! { "usr" => ["bin", "lib" => ["libc.so"]] };
+    /// assert_eq!(tree.joined_path(&[1, 0], "/"), Some("usr/lib/libc.so".to_string()));
+    /// assert_eq!(tree.joined_path(&[2], "/"), None);
+    /// ```
+    pub fn joined_path(&self, path: &[usize], separator: &str) -> Option<String> {
+        let mut joined = self.value.as_ref().to_string();
+        let mut node = self;
+        for &index in path {
+            node = node.children.get(index)?;
+            joined.push_str(separator);
+            joined.push_str(node.value.as_ref());
+        }
+        Some(joined)
+    }
+
+    /// Returns the joined path of every node, as given by [`Node::joined_path`], in
+    /// depth-first order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::tree;
+    ///
+    /// let tree = tree! { "usr" => ["bin", "lib" => ["libc.so"]] };
+    /// assert_eq!(
+    ///     tree.joined_paths("/"),
+    ///     vec!["usr", "usr/bin", "usr/lib", "usr/lib/libc.so"]
+    /// );
+    /// ```
+    pub fn joined_paths(&self, separator: &str) -> Vec<String> {
+        let mut paths = Vec::new();
+        let mut stack = vec![(self, self.value.as_ref().to_string())];
+        while let Some((node, joined)) = stack.pop() {
+            stack.extend(node.children.iter().rev().map(|child| {
+                (
+                    child,
+                    format!("{joined}{separator}{}", child.value.as_ref()),
+                )
+            }));
+            paths.push(joined);
+        }
+        paths
+    }
+
+    /// Sorts the children of every node alphabetically, ignoring case.
+    ///
+    /// Values that differ only in case keep their order, as the sort is stable.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::tree;
+    ///
+    /// let mut tree = tree! { "root" => ["b", "C" => ["y", "X"], "a"] };
+    /// tree.sort_children_ignore_case();
+    /// assert_eq!(tree, tree! { "root" => ["a", "b", "C" => ["X", "y"]] });
+    /// ```
+    pub fn sort_children_ignore_case(&mut self) {
+        self.sort_children_by_key(|value| value.as_ref().to_lowercase());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tree::Node;
+
+    #[test]
+    fn test_deep_text_tree() {
+        let mut deep = Node::new("x".to_string());
+        for i in 0..100_000 {
+            deep = Node::with_children(if i == 0 { "Target" } else { "x" }.to_string(), [deep]);
+        }
+        let path = deep.search_ignore_case("TARGET").pop().unwrap();
+        assert_eq!(path.len(), 99_999);
+
+        let joined = deep.joined_path(&path, "").unwrap();
+        assert_eq!(joined.len(), 99_999 + "Target".len());
+
+        let kept = deep.retain_prefix("T").unwrap();
+        assert_eq!(kept.search_ignore_case("target"), vec![path]);
+    }
+}
@@ -0,0 +1,97 @@
+use std::fmt;
+
+/// The child-index path of a queued node, kept only when traversal decisions are logged.
+///
+/// A traversal keeps paths only if trace logging is enabled for the crate when it starts,
+/// so that with the `log` feature on but tracing off, traversals pay nothing for them.
+#[cfg(feature = "log")]
+#[derive(Debug, Clone)]
+pub(crate) struct Trail(Option<Vec<usize>>);
+
+/// The child-index path of a queued node, kept only when traversal decisions are logged.
+///
+/// Without the `log` feature this is empty, so the traversals pay nothing for it.
+#[cfg(not(feature = "log"))]
+#[derive(Debug, Clone)]
+pub(crate) struct Trail;
+
+impl Trail {
+    /// Returns the trail of the node a traversal starts from.
+    #[cfg(feature = "log")]
+    pub(crate) fn root() -> Self {
+        Self(log::log_enabled!(target: "tree_iter", log::Level::Trace).then(Vec::new))
+    }
+
+    /// Returns the trail of the node a traversal starts from.
+    #[cfg(not(feature = "log"))]
+    pub(crate) fn root() -> Self {
+        Self
+    }
+
+    /// Returns the trail of the child with the given index.
+    #[cfg(feature = "log")]
+    pub(crate) fn child(&self, index: usize) -> Self {
+        Self(self.0.as_ref().map(|path| {
+            let mut child = Vec::with_capacity(path.len() + 1);
+            child.extend_from_slice(path);
+            child.push(index);
+            child
+        }))
+    }
+
+    /// Returns the trail of the child with the given index.
+    #[cfg(not(feature = "log"))]
+    pub(crate) fn child(&self, index: usize) -> Self {
+        let _ = index;
+        Self
+    }
+
+    /// Returns whether the path is kept, so that child indices are worth counting.
+    #[cfg(feature = "log")]
+    pub(crate) fn is_kept(&self) -> bool {
+        self.0.is_some()
+    }
+
+    /// Returns whether the path is kept, so that child indices are worth counting.
+    #[cfg(not(feature = "log"))]
+    pub(crate) fn is_kept(&self) -> bool {
+        false
+    }
+}
+
+impl fmt::Display for Trail {
+    #[cfg(feature = "log")]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Some(path) => write!(f, "{path:?}"),
+            None => f.write_str("?"),
+        }
+    }
+
+    #[cfg(not(feature = "log"))]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("?")
+    }
+}
+
+/// Logs a traversal decision at trace level, with the `log` feature.
+#[cfg(feature = "log")]
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        log::trace!(target: "tree_iter", $($arg)*)
+    };
+}
+
+/// Logs a traversal decision at trace level, with the `log` feature.
+///
+/// Without it, the arguments are still type-checked but never evaluated.
+#[cfg(not(feature = "log"))]
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    };
+}
+
+pub(crate) use trace;
@@ -1,3 +1,9 @@
+use std::collections::VecDeque;
+
+use crate::iter::TreeNode;
+
+pub(crate) use seal::Kind as OrderKind;
+
 /// Marker struct representing breadth-first traversal order.
 ///
 /// Breadth-first traversal visits all nodes at the same depth level before moving to the next level.
@@ -16,16 +22,86 @@ pub struct DepthFirst;
 ///
 /// This prevents external crates from implementing the `TraversalOrder` trait,
 /// allowing us to maintain control over the possible traversal orders.
+///
+/// It also lets code inside the crate that is generic over the order tell the orders apart.
 mod seal {
-    pub trait Sealed {}
-    impl Sealed for super::DepthFirst {}
-    impl Sealed for super::BreadthFirst {}
+    /// The traversal orders, as a value.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Kind {
+        BreadthFirst,
+        DepthFirst,
+    }
+
+    pub trait Sealed {
+        const KIND: Kind;
+    }
+    impl Sealed for super::DepthFirst {
+        const KIND: Kind = Kind::DepthFirst;
+    }
+    impl Sealed for super::BreadthFirst {
+        const KIND: Kind = Kind::BreadthFirst;
+    }
 }
 
 /// Trait for tree traversal order strategies.
 ///
 /// This trait is sealed (cannot be implemented outside this crate) and is used as
 /// a marker for different traversal strategies that can be used with tree iterators.
-pub trait TraversalOrder: seal::Sealed {}
+pub trait TraversalOrder: seal::Sealed {
+    /// Returns the ids of the nodes of `shape` in the order this traversal visits them.
+    ///
+    /// This is the specification of the order, computed from `children()` alone and
+    /// independently of the crate's iterators, so it can be used to check them. See
+    /// [`check_conformance`](crate::conformance::check_conformance) for a full check of a
+    /// custom [`TreeNode`] implementation.
+    ///
+    /// # Parameters
+    ///
+    /// * `shape` - The root of the tree.
+    /// * `id` - Gives the id of a node.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::prelude::*;
+    /// use tree_iter::tree;
+    ///
+    /// let shape = tree! { 1 => [2 => [4, 5], 3] };
+    /// assert_eq!(DepthFirst::expected_sequence(&shape, |node| node.value), vec![1, 2, 4, 5, 3]);
+    /// assert_eq!(BreadthFirst::expected_sequence(&shape, |node| node.value), vec![1, 2, 3, 4, 5]);
+    /// ```
+    fn expected_sequence<N: TreeNode, K>(shape: &N, mut id: impl FnMut(&N) -> K) -> Vec<K>
+    where
+        Self: Sized,
+    {
+        let mut sequence = Vec::new();
+        match Self::KIND {
+            OrderKind::BreadthFirst => {
+                let mut queue = VecDeque::from([shape]);
+                while let Some(node) = queue.pop_front() {
+                    sequence.push(id(node));
+                    queue.extend(node.children());
+                }
+            }
+            OrderKind::DepthFirst => {
+                // Each entry holds the children of a node that are still to be visited
+                sequence.push(id(shape));
+                let mut stack = vec![shape.children()];
+                while let Some(children) = stack.last_mut() {
+                    match children.next() {
+                        Some(child) => {
+                            sequence.push(id(child));
+                            stack.push(child.children());
+                        }
+                        None => {
+                            stack.pop();
+                        }
+                    }
+                }
+            }
+        }
+        sequence
+    }
+}
 impl TraversalOrder for BreadthFirst {}
 impl TraversalOrder for DepthFirst {}
@@ -0,0 +1,554 @@
+use std::{
+    collections::VecDeque,
+    fmt,
+    marker::PhantomData,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    iter::TreeNode,
+    trace::{Trail, trace},
+    traversal_order::{DepthFirst, OrderKind, TraversalOrder},
+};
+
+/// Marker for traversals that yield nodes.
+#[derive(Debug)]
+pub struct Nodes;
+
+/// Marker for traversals that yield nodes together with their depth.
+#[derive(Debug)]
+pub struct WithDepth;
+
+/// A predicate deciding which subtrees are left out of a traversal.
+type Prune<'a, N> = Box<dyn FnMut(&N) -> bool + 'a>;
+
+/// A builder for traversals with optional pruning, depth limits, node budgets and time
+/// budgets per subtree.
+///
+/// Created by [`TreeNode::traverse`]. Every option is off by default, in which case the
+/// traversal visits the same nodes in the same order as [`TreeNode::iter`].
+///
+/// # Type Parameters
+///
+/// * `'a` - The lifetime of the tree nodes being traversed.
+/// * `N` - The type of tree node.
+/// * `O` - The traversal order strategy (e.g., `DepthFirst` or `BreadthFirst`).
+/// * `D` - What the traversal yields: [`Nodes`] or, after [`with_depth`](Self::with_depth), [`WithDepth`].
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::prelude::*;
+/// use tree_iter::tree;
+///
+/// let tree = tree! { 1 => [2 => [4 => [8], 5], 3 => [6, 7]] };
+/// let visited: Vec<(usize, i32)> = tree
+///     .traverse()
+///     .order(BreadthFirst)
+///     .max_depth(2)
+///     .prune(|node| node.value == 3)
+///     .with_depth()
+///     .build()
+///     .map(|(depth, node)| (depth, node.value))
+///     .collect();
+/// assert_eq!(visited, vec![(0, 1), (1, 2), (2, 4), (2, 5)]);
+/// ```
+pub struct IterBuilder<'a, N, O = DepthFirst, D = Nodes> {
+    /// The node to start from.
+    root: &'a N,
+    /// The deepest level that is visited, if limited.
+    max_depth: Option<usize>,
+    /// The maximum number of nodes yielded, if limited.
+    max_nodes: Option<usize>,
+    /// Skips the subtrees of nodes for which it returns `true`.
+    prune: Option<Prune<'a, N>>,
+    /// The time each subtree of the root may take, if limited.
+    subtree_budget: Option<Duration>,
+    /// Phantom data to track the traversal order and the item type.
+    _marker: PhantomData<(O, D)>,
+}
+
+impl<'a, N: TreeNode, O: TraversalOrder, D> IterBuilder<'a, N, O, D> {
+    /// Creates a builder for a traversal starting from `root`, with every option off.
+    pub fn new(root: &'a N) -> Self {
+        Self {
+            root,
+            max_depth: None,
+            max_nodes: None,
+            prune: None,
+            subtree_budget: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Sets the traversal order.
+    ///
+    /// # Parameters
+    ///
+    /// * `order` - The traversal order marker, e.g. `DepthFirst` or `BreadthFirst`.
+    pub fn order<O2: TraversalOrder>(self, order: O2) -> IterBuilder<'a, N, O2, D> {
+        let _ = order;
+        IterBuilder {
+            root: self.root,
+            max_depth: self.max_depth,
+            max_nodes: self.max_nodes,
+            prune: self.prune,
+            subtree_budget: self.subtree_budget,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Limits the traversal to nodes at most `depth` levels below the root.
+    ///
+    /// The root is at depth 0, so a limit of 0 visits only the root.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Stops the traversal after `count` nodes have been yielded.
+    pub fn max_nodes(mut self, count: usize) -> Self {
+        self.max_nodes = Some(count);
+        self
+    }
+
+    /// Leaves out every node for which `predicate` returns `true`, along with its subtree.
+    ///
+    /// The predicate is called once per node reached, in traversal order, and never on
+    /// the descendants of a pruned node. Calling this again replaces the predicate.
+    pub fn prune(mut self, predicate: impl FnMut(&N) -> bool + 'a) -> Self {
+        self.prune = Some(Box::new(predicate));
+        self
+    }
+
+    /// Gives each subtree of the root its own time budget, after which the rest of that
+    /// subtree is left out while the other subtrees carry on.
+    ///
+    /// The time from taking a node off the queue, through the prune predicate and the
+    /// walk over its children, to the next call to `next` is charged to the subtree of the
+    /// root the node belongs to. The budget so covers slow predicates and slow `children`
+    /// as well as the caller's work on the nodes. The subtrees that were cut short are
+    /// reported by [`Traversal::truncated`]. The root itself is never charged or left out.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::{thread, time::Duration};
+    ///
+    /// use tree_iter::prelude::*;
+    /// use tree_iter::tree;
+    ///
+    /// let tree = tree! { 0 => [1 => [10, 11, 12], 2 => [20, 21]] };
+    /// let mut traversal = tree.traverse().subtree_budget(Duration::from_millis(20)).build();
+    /// let mut visited = Vec::new();
+    /// for node in &mut traversal {
+    ///     if node.value == 10 {
+    ///         // A pathological node uses up the budget of its subtree
+    ///         thread::sleep(Duration::from_millis(30));
+    ///     }
+    ///     visited.push(node.value);
+    /// }
+    /// assert_eq!(visited, vec![0, 1, 10, 2, 20, 21]);
+    /// assert_eq!(traversal.truncated(), vec![0]);
+    /// ```
+    pub fn subtree_budget(mut self, budget: Duration) -> Self {
+        self.subtree_budget = Some(budget);
+        self
+    }
+
+    /// Makes the traversal yield the depth of each node along with it, as `(depth, node)`.
+    pub fn with_depth(self) -> IterBuilder<'a, N, O, WithDepth> {
+        IterBuilder {
+            root: self.root,
+            max_depth: self.max_depth,
+            max_nodes: self.max_nodes,
+            prune: self.prune,
+            subtree_budget: self.subtree_budget,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Builds the configured traversal.
+    pub fn build(self) -> Traversal<'a, N, O, D> {
+        Traversal {
+            nodes: VecDeque::from([Queued {
+                depth: 0,
+                node: self.root,
+                subtree: 0,
+                trail: Trail::root(),
+            }]),
+            max_depth: self.max_depth,
+            remaining: self.max_nodes,
+            prune: self.prune,
+            subtree_budget: self.subtree_budget,
+            subtrees: Vec::new(),
+            last: None,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<N: fmt::Debug, O, D> fmt::Debug for IterBuilder<'_, N, O, D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IterBuilder")
+            .field("root", &self.root)
+            .field("max_depth", &self.max_depth)
+            .field("max_nodes", &self.max_nodes)
+            .field("prune", &self.prune.is_some())
+            .field("subtree_budget", &self.subtree_budget)
+            .finish()
+    }
+}
+
+/// A traversal configured with an [`IterBuilder`].
+///
+/// It yields `&N`, or `(usize, &N)` with the depth of each node if the builder was
+/// configured with [`IterBuilder::with_depth`].
+pub struct Traversal<'a, N, O, D> {
+    /// Queue of nodes to be visited.
+    nodes: VecDeque<Queued<'a, N>>,
+    /// The deepest level that is visited, if limited.
+    max_depth: Option<usize>,
+    /// The number of nodes that may still be yielded, if limited.
+    remaining: Option<usize>,
+    /// Skips the subtrees of nodes for which it returns `true`.
+    prune: Option<Prune<'a, N>>,
+    /// The time each subtree of the root may take, if limited.
+    subtree_budget: Option<Duration>,
+    /// The time charged to each subtree of the root so far, and whether it was cut short.
+    subtrees: Vec<(Duration, bool)>,
+    /// The subtree of the node yielded last, and when the traversal took it off the
+    /// queue, if it is charged.
+    last: Option<(usize, Instant)>,
+    /// Phantom data to track the traversal order and the item type.
+    _marker: PhantomData<(O, D)>,
+}
+
+impl<N, O, D> Traversal<'_, N, O, D> {
+    /// Returns the indices of the subtrees of the root that ran out of their
+    /// [time budget](IterBuilder::subtree_budget) and had nodes left out, in order.
+    pub fn truncated(&self) -> Vec<usize> {
+        (0..self.subtrees.len())
+            .filter(|&i| self.subtrees[i].1)
+            .collect()
+    }
+}
+
+impl<'a, N: TreeNode, O: TraversalOrder, D> Traversal<'a, N, O, D> {
+    /// Returns the next node that is not pruned, with its depth, and queues its children.
+    fn next_visit(&mut self) -> Option<(usize, &'a N)> {
+        if self.remaining == Some(0) {
+            return None;
+        }
+        if let Some((subtree, since)) = self.last.take() {
+            self.subtrees[subtree].0 += since.elapsed();
+        }
+        // The time spent on a node, from pruning it on, is charged to its subtree
+        let (visit, started) = loop {
+            let visit = self.nodes.pop_front()?;
+            let charged = visit.depth > 0 && self.subtree_budget.is_some();
+            if charged
+                && let Some(budget) = self.subtree_budget
+                && self.subtrees[visit.subtree].0 >= budget
+            {
+                trace!(
+                    "leaving out {}: subtree {} of the root is out of time",
+                    visit.trail, visit.subtree
+                );
+                self.subtrees[visit.subtree].1 = true;
+                continue;
+            }
+            let started = charged.then(Instant::now);
+            if self.prune.as_mut().is_some_and(|prune| prune(visit.node)) {
+                trace!("pruned {} with its subtree", visit.trail);
+                if let Some(started) = started {
+                    self.subtrees[visit.subtree].0 += started.elapsed();
+                }
+                continue;
+            }
+            break (visit, started);
+        };
+
+        match self.max_depth {
+            Some(max) if visit.depth >= max => {
+                if visit.node.children().next().is_some() {
+                    trace!("not expanding {}: depth limit {max} reached", visit.trail);
+                }
+            }
+            _ => self.queue(&visit),
+        }
+        if let Some(remaining) = &mut self.remaining {
+            *remaining -= 1;
+            if *remaining == 0 && !self.nodes.is_empty() {
+                trace!(
+                    "node budget used up at {}, leaving out {} queued nodes",
+                    visit.trail,
+                    self.nodes.len()
+                );
+            }
+        }
+        self.last = started.map(|started| (visit.subtree, started));
+        Some((visit.depth, visit.node))
+    }
+
+    /// Queues the children of a node just visited, in order.
+    fn queue(&mut self, parent: &Queued<'a, N>) {
+        if parent.depth == 0 && self.subtree_budget.is_some() {
+            self.subtrees = vec![(Duration::ZERO, false); parent.node.children().count()];
+        }
+        // Each child of the root starts a subtree of its own
+        let child = |index, node| Queued {
+            depth: parent.depth + 1,
+            node,
+            subtree: if parent.depth == 0 {
+                index
+            } else {
+                parent.subtree
+            },
+            trail: parent.trail.child(index),
+        };
+        match O::KIND {
+            OrderKind::BreadthFirst => self.nodes.extend(
+                parent
+                    .node
+                    .children()
+                    .enumerate()
+                    .map(|(i, node)| child(i, node)),
+            ),
+            OrderKind::DepthFirst => {
+                // Indices are only needed for the subtrees of the root and for logging
+                let needs_index = parent.depth == 0 || parent.trail.is_kept();
+                let mut index = if needs_index {
+                    parent.node.children().count()
+                } else {
+                    0
+                };
+                for node in parent.node.children().rev() {
+                    index = index.saturating_sub(1);
+                    self.nodes.push_front(child(index, node));
+                }
+            }
+        }
+    }
+}
+
+/// A node queued in a [`Traversal`].
+#[derive(Debug)]
+struct Queued<'a, N> {
+    /// The depth of the node.
+    depth: usize,
+    /// The node itself.
+    node: &'a N,
+    /// The index of the subtree of the root the node belongs to.
+    subtree: usize,
+    /// The path of the node, for logging.
+    trail: Trail,
+}
+
+impl<'a, N: TreeNode, O: TraversalOrder> Iterator for Traversal<'a, N, O, Nodes> {
+    type Item = &'a N;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_visit().map(|(_, node)| node)
+    }
+}
+
+impl<'a, N: TreeNode, O: TraversalOrder> Iterator for Traversal<'a, N, O, WithDepth> {
+    type Item = (usize, &'a N);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_visit()
+    }
+}
+
+impl<N: fmt::Debug, O, D> fmt::Debug for Traversal<'_, N, O, D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Traversal")
+            .field("nodes", &self.nodes)
+            .field("max_depth", &self.max_depth)
+            .field("remaining", &self.remaining)
+            .field("prune", &self.prune.is_some())
+            .field("subtree_budget", &self.subtree_budget)
+            .field("truncated", &self.truncated())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_default_matches_iter() {
+        let tree = crate::tree! { 1 => [2 => [4, 5], 3 => [6]] };
+        let built: Vec<i32> = tree.traverse().build().map(|node| node.value).collect();
+        let plain: Vec<i32> = tree.iter::<DepthFirst>().map(|node| node.value).collect();
+        assert_eq!(built, plain);
+    }
+
+    #[test]
+    fn test_options_compose() {
+        let tree = crate::tree! { 1 => [2 => [4 => [8], 5], 3 => [6, 7]] };
+
+        let visited: Vec<(usize, i32)> = tree
+            .traverse()
+            .max_depth(1)
+            .with_depth()
+            .build()
+            .map(|(depth, node)| (depth, node.value))
+            .collect();
+        assert_eq!(visited, vec![(0, 1), (1, 2), (1, 3)]);
+
+        let mut calls = 0;
+        let visited: Vec<i32> = tree
+            .traverse()
+            .prune(|node| {
+                calls += 1;
+                node.value == 2
+            })
+            .max_nodes(3)
+            .build()
+            .map(|node| node.value)
+            .collect();
+        assert_eq!(visited, vec![1, 3, 6]);
+        assert_eq!(calls, 4);
+    }
+
+    #[test]
+    fn test_subtree_budget() {
+        use std::time::Duration;
+
+        let tree = crate::tree! { 1 => [2 => [4, 5], 3 => [6]] };
+        let mut traversal = tree
+            .traverse()
+            .order(BreadthFirst)
+            .subtree_budget(Duration::from_secs(3600))
+            .build();
+        let visited: Vec<i32> = traversal.by_ref().map(|node| node.value).collect();
+        assert_eq!(visited, vec![1, 2, 3, 4, 5, 6]);
+        assert!(traversal.truncated().is_empty());
+
+        // An empty budget leaves out every subtree of the root
+        let mut traversal = tree.traverse().subtree_budget(Duration::ZERO).build();
+        assert_eq!(
+            traversal
+                .by_ref()
+                .map(|node| node.value)
+                .collect::<Vec<_>>(),
+            vec![1]
+        );
+        assert_eq!(traversal.truncated(), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_slow_prune_uses_subtree_budget() {
+        use std::{thread, time::Duration};
+
+        let tree = crate::tree! { 0 => [1 => [10, 11, 12], 2 => [20, 21]] };
+        for pruned in [false, true] {
+            let mut traversal = tree
+                .traverse()
+                .prune(|node| {
+                    if node.value == 10 {
+                        // A pathological prune check uses up the budget of its subtree
+                        thread::sleep(Duration::from_millis(30));
+                        return pruned;
+                    }
+                    false
+                })
+                .subtree_budget(Duration::from_millis(20))
+                .build();
+            let visited: Vec<i32> = traversal.by_ref().map(|node| node.value).collect();
+            let expected = if pruned {
+                vec![0, 1, 2, 20, 21]
+            } else {
+                vec![0, 1, 10, 2, 20, 21]
+            };
+            assert_eq!(visited, expected);
+            assert_eq!(traversal.truncated(), vec![0]);
+        }
+    }
+
+    #[cfg(feature = "log")]
+    #[test]
+    fn test_logged_decisions() {
+        use std::sync::Mutex;
+
+        use crate::{
+            graph::{GraphIter, RevisitPolicy},
+            trace::Trail,
+        };
+
+        /// Keeps every message logged by the crate.
+        struct Collect(Mutex<Vec<String>>);
+
+        impl log::Log for Collect {
+            fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+                metadata.target() == "tree_iter"
+            }
+
+            fn log(&self, record: &log::Record<'_>) {
+                if self.enabled(record.metadata()) {
+                    self.0.lock().unwrap().push(record.args().to_string());
+                }
+            }
+
+            fn flush(&self) {}
+        }
+
+        static LOGGER: Collect = Collect(Mutex::new(Vec::new()));
+        log::set_logger(&LOGGER).unwrap();
+        log::set_max_level(log::LevelFilter::Trace);
+
+        let tree = crate::tree! { 1 => [2 => [4 => [8], 5], 3 => [6, 7]] };
+        let visited = tree
+            .traverse()
+            .max_depth(2)
+            .prune(|node| node.value == 3)
+            .max_nodes(3)
+            .build()
+            .count();
+        assert_eq!(visited, 3);
+        let visited = tree
+            .traverse()
+            .prune(|node| node.value == 3)
+            .build()
+            .count();
+        assert_eq!(visited, 5);
+        let visited = tree
+            .traverse()
+            .subtree_budget(std::time::Duration::ZERO)
+            .build()
+            .count();
+        assert_eq!(visited, 1);
+
+        // A node reached again through another parent
+        let shared = crate::tree! { 1 => [2 => [4], 3 => [2 => [4]]] };
+        let visited = GraphIter::new(&shared, |node| node.value).count();
+        assert_eq!(visited, 4);
+        let strict = GraphIter::new(&shared, |node| node.value).policy(RevisitPolicy::Error);
+        assert!(strict.last().unwrap().is_err());
+
+        let messages = LOGGER.0.lock().unwrap().clone();
+        for expected in [
+            "not expanding [0, 0]: depth limit 2 reached",
+            "node budget used up at [0, 0], leaving out 2 queued nodes",
+            "pruned [1] with its subtree",
+            "leaving out [0]: subtree 0 of the root is out of time",
+            "leaving out [1]: subtree 1 of the root is out of time",
+            "skipping [1, 0] with its subtree: the node was reached before",
+            "stopping at [1, 0]: the node was reached before",
+        ] {
+            assert!(
+                messages.iter().any(|message| message == expected),
+                "{expected}"
+            );
+        }
+
+        // Paths are not kept while tracing is off
+        log::set_max_level(log::LevelFilter::Debug);
+        assert!(!Trail::root().is_kept());
+        log::set_max_level(log::LevelFilter::Trace);
+        assert!(Trail::root().child(2).is_kept());
+    }
+}
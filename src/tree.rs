@@ -1,56 +1,1379 @@
-use crate::{iter::TreeNode, prelude::TreeNodeMut};
+use std::{
+    cmp::Ordering,
+    collections::VecDeque,
+    error::Error,
+    fmt,
+    hash::{Hash, Hasher},
+    mem::{self, ManuallyDrop},
+    ops::{Index, IndexMut},
+    ptr,
+};
+
+use crate::{
+    diff::{Conflict, Edit, PatchError},
+    events::{self, Event, EventError},
+    iter::{TreeIter, TreeNode},
+    iter_mut::{TreeIterMut, TreeNodeMut},
+    path::TreePath,
+    traversal_order::{DepthFirst, OrderKind, TraversalOrder},
+};
 
 /// A generic tree node implementation.
 ///
-/// This struct provides a simple implementation of a tree node with a value and a vector of children.
-/// It implements both `TreeNode` and `TreeNodeMut` traits, allowing it to be used with both
-/// immutable and mutable iterators.
+/// This struct provides a simple implementation of a tree node with a value and a vector of children.
+/// It implements both `TreeNode` and `TreeNodeMut` traits, allowing it to be used with both
+/// immutable and mutable iterators.
+///
+/// The standard traits are implemented whenever `T` supports them. Equality and hashing are
+/// structural: two trees are equal when they have the same shape and the same values at
+/// the same positions. `Debug` writes the tree as a tree rather than as nested structs.
+/// `Clone`, `PartialEq`, `Hash`, `Debug` and dropping walk the tree iteratively, so they
+/// work on trees of any depth.
+///
+/// With the `serde` feature, a node is serialized as a struct with a `value` and a list of
+/// `children`; `children` may be left out for leaves when deserializing. Deserializing
+/// keeps the nodes being read on an explicit stack, but formats still recurse once per
+/// level of nesting and may limit the depth they accept: `serde_json` stops at 128 levels
+/// by default, and reads deeper trees with its recursion limit disabled and a growing
+/// stack, as from `serde_stacker`. Serializing recurses once per level.
+///
+/// With the `rkyv` feature, a node can be archived with `rkyv`, and the archived form,
+/// `ArchivedNode<T>`, implements [`TreeNode`], so a memory-mapped archive can be traversed
+/// without deserializing it. Archiving and unarchiving recurse once per level.
+///
+/// # Type Parameters
+///
+/// * `T` - The type of value stored in each node.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::tree::Node;
+/// use tree_iter::prelude::*;
+///
+/// // Create a simple tree
+/// let tree = Node {
+///     value: 1,
+///     children: vec![Node::new(2), Node::new(3)],
+/// };
+///
+/// // Traverse in depth-first order
+/// let values: Vec<i32> = tree.iter::<DepthFirst>()
+///                           .map(|node| node.value)
+///                           .collect();
+/// assert_eq!(values, vec![1, 2, 3]);
+/// ```
+#[derive(Default)]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    rkyv(
+        serialize_bounds(
+            __S: rkyv::ser::Writer + rkyv::ser::Allocator,
+            __S::Error: rkyv::rancor::Source,
+        ),
+        deserialize_bounds(__D::Error: rkyv::rancor::Source),
+        bytecheck(bounds(
+            __C: rkyv::validation::ArchiveContext,
+            __C::Error: rkyv::rancor::Source,
+        )),
+    )
+)]
+pub struct Node<T> {
+    /// The value stored in this node.
+    pub value: T,
+    /// The children of this node.
+    #[cfg_attr(feature = "rkyv", rkyv(omit_bounds))]
+    pub children: Vec<Node<T>>,
+}
+
+impl<T> Node<T> {
+    /// Creates a new node with the given value and no children.
+    ///
+    /// # Parameters
+    ///
+    /// * `value` - The value to store in the node.
+    ///
+    /// # Returns
+    ///
+    /// A new `Node` with the given value and an empty children vector.
+    pub fn new(value: T) -> Self {
+        Self {
+            value,
+            children: Vec::new(),
+        }
+    }
+
+    /// Creates a new node with the given value and children.
+    ///
+    /// # Parameters
+    ///
+    /// * `value` - The value to store in the node.
+    /// * `children` - The children of the node.
+    pub fn with_children(value: T, children: impl IntoIterator<Item = Node<T>>) -> Self {
+        Self {
+            value,
+            children: children.into_iter().collect(),
+        }
+    }
+
+    /// Builds a tree from a stream of [`Event`]s, such as those of
+    /// [`events`](crate::events::events), a SAX-style parser or a network feed.
+    ///
+    /// This is [`build`](crate::events::build) as a constructor; see
+    /// [`TreeBuilder`](crate::events::TreeBuilder) to feed events one at a time. Only the
+    /// open nodes are kept while building, so trees of any depth can be built.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stream is empty, closes a node when none is open, starts a
+    /// second root, or leaves nodes open.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::events::{Event, EventError, events};
+    /// use tree_iter::tree;
+    /// use tree_iter::tree::Node;
+    ///
+    /// let tree = tree! { 1 => [2 => [3], 4] };
+    /// assert_eq!(Node::from_events(events(&tree, |node| node.value)), Ok(tree));
+    ///
+    /// let unclosed = [Event::Open(1), Event::Open(2), Event::Close];
+    /// assert_eq!(Node::from_events(unclosed), Err(EventError::Unclosed(1)));
+    /// ```
+    pub fn from_events(events: impl IntoIterator<Item = Event<T>>) -> Result<Self, EventError> {
+        events::build(events)
+    }
+
+    /// Splits the node into its value and its children.
+    ///
+    /// Since `Node` implements `Drop`, its fields cannot be moved out by destructuring;
+    /// this is the way to take ownership of both.
+    pub fn into_parts(self) -> (T, Vec<Node<T>>) {
+        let this = ManuallyDrop::new(self);
+        // SAFETY: `this` is never used or dropped again, so each field is moved out exactly once.
+        unsafe { (ptr::read(&this.value), ptr::read(&this.children)) }
+    }
+
+    /// Removes every subtree whose root value fails the predicate.
+    ///
+    /// The tree is filtered top-down, so the predicate is never called on the descendants
+    /// of a removed node. Returns `None` if the root itself fails the predicate.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::tree;
+    ///
+    /// let tree = tree! { 1 => [2 => [4, 5], 3 => [6]] };
+    /// let pruned = tree.filter_prune(|value| *value != 3 && *value != 5);
+    /// assert_eq!(pruned, Some(tree! { 1 => [2 => [4]] }));
+    ///
+    /// assert_eq!(tree! { 1 => [2] }.filter_prune(|value| *value > 1), None);
+    /// ```
+    pub fn filter_prune(mut self, mut predicate: impl FnMut(&T) -> bool) -> Option<Node<T>> {
+        if !predicate(&self.value) {
+            return None;
+        }
+        let mut stack = vec![&mut self];
+        while let Some(node) = stack.pop() {
+            node.children.retain(|child| predicate(&child.value));
+            stack.extend(node.children.iter_mut());
+        }
+        Some(self)
+    }
+
+    /// Removes every descendant whose value matches the predicate, splicing its children
+    /// into its parent's children at its position.
+    ///
+    /// This is the opposite of [`Node::filter_prune`]: the matching node goes away but its
+    /// subtree stays. Spliced children are tested as well, so nested matches are all
+    /// collapsed. The root is never removed, and the tree is walked iteratively.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::tree;
+    ///
+    /// let mut tree = tree! { "block" => ["group" => ["a", "group" => ["b"]], "c"] };
+    /// tree.collapse_where(|value| *value == "group");
+    /// assert_eq!(tree, tree! { "block" => ["a", "b", "c"] });
+    /// ```
+    pub fn collapse_where(&mut self, mut predicate: impl FnMut(&T) -> bool) {
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            let mut pending = mem::take(&mut node.children);
+            pending.reverse();
+            while let Some(child) = pending.pop() {
+                if predicate(&child.value) {
+                    let (_, grandchildren) = child.into_parts();
+                    pending.extend(grandchildren.into_iter().rev());
+                } else {
+                    node.children.push(child);
+                }
+            }
+            stack.extend(node.children.iter_mut());
+        }
+    }
+
+    /// Returns the descendant at the given child-index path, or `None` if there is no such node.
+    ///
+    /// The empty path refers to the node itself.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::tree;
+    ///
+    /// let tree = tree! { 1 => [2 => [4, 5], 3] };
+    /// assert_eq!(tree.get(&[0, 1]).map(|node| node.value), Some(5));
+    /// assert_eq!(tree.get(&[1, 0]), None);
+    /// ```
+    pub fn get(&self, path: &[usize]) -> Option<&Node<T>> {
+        path.iter().try_fold(self, |node, &i| node.children.get(i))
+    }
+
+    /// Returns the descendant at the given child-index path mutably, or `None` if there is no such node.
+    ///
+    /// The empty path refers to the node itself.
+    pub fn get_mut(&mut self, path: &[usize]) -> Option<&mut Node<T>> {
+        path.iter()
+            .try_fold(self, |node, &i| node.children.get_mut(i))
+    }
+
+    /// Returns the ancestor `k` levels above the descendant at the given child-index path,
+    /// or `None` if there is no node at the path or it is less than `k` levels deep.
+    ///
+    /// The ancestor with `k = 0` is the node at the path itself. This walks the path from
+    /// this node; see [`AncestorTable`](crate::ancestors::AncestorTable) for repeated
+    /// queries by node id.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::tree;
+    ///
+    /// let tree = tree! { 1 => [2 => [4, 5], 3] };
+    /// assert_eq!(tree.kth_ancestor(&[0, 1], 1).map(|node| node.value), Some(2));
+    /// assert_eq!(tree.kth_ancestor(&[0, 1], 2).map(|node| node.value), Some(1));
+    /// assert_eq!(tree.kth_ancestor(&[0, 1], 3), None);
+    /// assert_eq!(tree.kth_ancestor(&[0, 2], 1), None);
+    /// ```
+    pub fn kth_ancestor(&self, path: &[usize], k: usize) -> Option<&Node<T>> {
+        let depth = path.len().checked_sub(k)?;
+        let mut node = self;
+        let mut ancestor = None;
+        for (i, &index) in path.iter().enumerate() {
+            if i == depth {
+                ancestor = Some(node);
+            }
+            node = node.children.get(index)?;
+        }
+        Some(ancestor.unwrap_or(node))
+    }
+
+    /// Appends a child to the end of this node's children.
+    pub fn push_child(&mut self, child: Node<T>) {
+        self.children.push(child);
+    }
+
+    /// Appends several children to the end of this node's children, in order.
+    ///
+    /// Like [`Vec::extend`], this reserves space up front from the iterator's size hint, so
+    /// adding a known number of children reallocates at most once.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::tree::Node;
+    ///
+    /// let mut tree = Node::new(0);
+    /// tree.extend_children((1..=3).map(Node::new));
+    /// assert_eq!(tree, Node::with_children(0, [Node::new(1), Node::new(2), Node::new(3)]));
+    /// ```
+    pub fn extend_children(&mut self, children: impl IntoIterator<Item = Node<T>>) {
+        self.children.extend(children);
+    }
+
+    /// Inserts a child at position `index`, shifting all children after it to the right.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > self.children.len()`.
+    pub fn insert_child(&mut self, index: usize, child: Node<T>) {
+        self.children.insert(index, child);
+    }
+
+    /// Removes and returns the child at position `index`, shifting all children after it to the left.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove_child(&mut self, index: usize) -> Node<T> {
+        self.children.remove(index)
+    }
+
+    /// Swaps the children at positions `a` and `b`, along with their subtrees.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `a` or `b` are out of bounds.
+    pub fn swap_children(&mut self, a: usize, b: usize) {
+        self.children.swap(a, b);
+    }
+
+    /// Inserts `subtree` so that it ends up at the given child-index path.
+    ///
+    /// The last index of `path` is the position among the children of the node at the rest
+    /// of the path; children from that position on are shifted to the right. If there is no
+    /// such parent, the index is past the end of its children, or `path` is empty, the
+    /// subtree is handed back as the error.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::tree;
+    ///
+    /// let mut tree = tree! { 1 => [2 => [4], 3] };
+    /// assert!(tree.graft_at(&[0, 0], tree! { 5 => [6] }).is_ok());
+    /// assert_eq!(tree, tree! { 1 => [2 => [5 => [6], 4], 3] });
+    ///
+    /// assert_eq!(tree.graft_at(&[3, 0], tree! { 7 }), Err(tree! { 7 }));
+    /// ```
+    pub fn graft_at(&mut self, path: &[usize], subtree: Node<T>) -> Result<(), Node<T>> {
+        let Some((&index, parent)) = path.split_last() else {
+            return Err(subtree);
+        };
+        match self.get_mut(parent) {
+            Some(parent) if index <= parent.children.len() => {
+                parent.children.insert(index, subtree);
+                Ok(())
+            }
+            _ => Err(subtree),
+        }
+    }
+
+    /// Detaches the subtree at the given child-index path and returns it.
+    ///
+    /// The rest of the tree is left intact; later siblings of the subtree shift to the left.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `path` is empty, since the node cannot split itself off, or if there is
+    /// no node at `path`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::tree;
+    ///
+    /// let mut tree = tree! { 1 => [2 => [4, 5], 3] };
+    /// let branch = tree.split_off(&[0, 1]);
+    /// assert_eq!(branch, tree! { 5 });
+    /// assert_eq!(tree, tree! { 1 => [2 => [4], 3] });
+    /// ```
+    pub fn split_off(&mut self, path: &[usize]) -> Node<T> {
+        let (&index, parent) = path
+            .split_last()
+            .expect("cannot split off the root of the tree");
+        match self.get_mut(parent) {
+            Some(parent) if index < parent.children.len() => parent.children.remove(index),
+            _ => panic!("no node at path {path:?}"),
+        }
+    }
+
+    /// Merges `other` into this tree, matching nodes by position.
+    ///
+    /// The roots are matched with each other, and the children of matched nodes are matched
+    /// by index. For every matched pair, `resolve` is called with this tree's value and the
+    /// value from `other`, in depth-first order. Children of `other` without a counterpart
+    /// are appended with their subtrees; children of this tree without a counterpart are
+    /// kept as they are. The trees are walked iteratively, so they may be of any depth.
+    ///
+    /// # Parameters
+    ///
+    /// * `other` - The tree to merge in.
+    /// * `resolve` - Combines a value of this tree with the matching value from `other`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::tree;
+    ///
+    /// // Layer overrides on top of defaults
+    /// let mut config = tree! { ("root", 0) => [("width", 80), ("height", 24)] };
+    /// let overrides = tree! { ("root", 0) => [("width", 120), ("height", 0), ("color", 1)] };
+    /// config.merge(overrides, |current, (_, value)| {
+    ///     if value != 0 {
+    ///         current.1 = value;
+    ///     }
+    /// });
+    /// assert_eq!(
+    ///     config,
+    ///     tree! { ("root", 0) => [("width", 120), ("height", 24), ("color", 1)] }
+    /// );
+    /// ```
+    pub fn merge(&mut self, other: Node<T>, mut resolve: impl FnMut(&mut T, T)) {
+        let mut stack = vec![(self, other)];
+        while let Some((node, other)) = stack.pop() {
+            let (value, mut children) = other.into_parts();
+            resolve(&mut node.value, value);
+
+            let matched = node.children.len().min(children.len());
+            node.children.extend(children.split_off(matched));
+            let pairs = node.children[..matched].iter_mut().zip(children);
+            stack.extend(pairs.rev());
+        }
+    }
+
+    /// Sorts the children of every node in the tree with a comparator function.
+    ///
+    /// Each node's children are reordered along with their subtrees. The sort is stable, and
+    /// the tree is walked iteratively, so trees of any depth can be sorted.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::tree;
+    ///
+    /// let mut tree = tree! { 0 => [1 => [4, 3], 2 => [6, 5]] };
+    /// tree.sort_children_by(|a, b| b.cmp(a));
+    /// assert_eq!(tree, tree! { 0 => [2 => [6, 5], 1 => [4, 3]] });
+    /// ```
+    pub fn sort_children_by(&mut self, mut compare: impl FnMut(&T, &T) -> Ordering) {
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            node.children.sort_by(|a, b| compare(&a.value, &b.value));
+            stack.extend(node.children.iter_mut());
+        }
+    }
+
+    /// Sorts the children of every node in the tree with a key extraction function.
+    ///
+    /// See [`Node::sort_children_by`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::tree;
+    ///
+    /// let mut tree = tree! { "root" => ["b" => ["z", "y"], "a"] };
+    /// tree.sort_children_by_key(|name| *name);
+    /// assert_eq!(tree, tree! { "root" => ["a", "b" => ["y", "z"]] });
+    /// ```
+    pub fn sort_children_by_key<K: Ord>(&mut self, mut key: impl FnMut(&T) -> K) {
+        self.sort_children_by(|a, b| key(a).cmp(&key(b)));
+    }
+
+    /// Builds a tree by repeatedly expanding seeds.
+    ///
+    /// Starting from `seed`, `f` turns each seed into the value of a node and the seeds of its
+    /// children. Seeds are expanded in the order `O`, which decides the order in which `f` is
+    /// called; the shape of the resulting tree is the same either way. The tree is built
+    /// iteratively, so `f` may produce trees of any depth.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `O` - The order in which seeds are expanded (e.g., `DepthFirst` or `BreadthFirst`).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::prelude::*;
+    /// use tree_iter::tree;
+    /// use tree_iter::tree::Node;
+    ///
+    /// // The binary representations of numbers below 8, built level by level
+    /// let tree = Node::unfold::<BreadthFirst, _>(1u32, |n| {
+    ///     let children = if n < 4 { vec![2 * n, 2 * n + 1] } else { vec![] };
+    ///     (format!("{n:b}"), children)
+    /// });
+    /// let labels: Vec<&str> = tree.iter::<BreadthFirst>().map(|n| n.value.as_str()).collect();
+    /// assert_eq!(labels, vec!["1", "10", "11", "100", "101", "110", "111"]);
+    /// ```
+    pub fn unfold<O: TraversalOrder, S>(seed: S, mut f: impl FnMut(S) -> (T, Vec<S>)) -> Node<T> {
+        // Expand the seeds into a flat list of values with the ids of their children
+        let mut expanded: Vec<(T, Vec<usize>)> = Vec::new();
+        let mut pending: VecDeque<(S, Option<usize>)> = VecDeque::from([(seed, None)]);
+        loop {
+            let next = match O::KIND {
+                OrderKind::BreadthFirst => pending.pop_front(),
+                OrderKind::DepthFirst => pending.pop_back(),
+            };
+            let Some((seed, parent)) = next else {
+                break;
+            };
+            let id = expanded.len();
+            let (value, seeds) = f(seed);
+            expanded.push((value, Vec::new()));
+            if let Some(parent) = parent {
+                expanded[parent].1.push(id);
+            }
+            let seeds = seeds.into_iter().map(|seed| (seed, Some(id)));
+            match O::KIND {
+                OrderKind::BreadthFirst => pending.extend(seeds),
+                OrderKind::DepthFirst => {
+                    pending.extend(seeds.collect::<Vec<_>>().into_iter().rev())
+                }
+            }
+        }
+
+        // Children are always expanded after their parents, so build the nodes backwards
+        let mut built: Vec<Option<Node<T>>> = (0..expanded.len()).map(|_| None).collect();
+        while let Some((value, children)) = expanded.pop() {
+            let children = children
+                .into_iter()
+                .map(|child| built[child].take().unwrap())
+                .collect();
+            built[expanded.len()] = Some(Node { value, children });
+        }
+        built[0].take().unwrap()
+    }
+
+    /// Combines two trees position by position.
+    ///
+    /// The value at each position of the result is `f` applied to the values at the same
+    /// position in `self` and `other`. When the trees have different shapes, `policy` decides
+    /// whether the extra children of the larger tree are dropped or an error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::tree;
+    /// use tree_iter::tree::ZipPolicy;
+    ///
+    /// let expected = tree! { 1 => [2, 3] };
+    /// let actual = tree! { 1 => [5, 3 => [4]] };
+    ///
+    /// let matches = expected
+    ///     .clone()
+    ///     .zip_with(actual.clone(), ZipPolicy::Truncate, |a, b| a == b)
+    ///     .unwrap();
+    /// assert_eq!(matches, tree! { true => [false, true] });
+    ///
+    /// let error = expected.zip_with(actual, ZipPolicy::Error, |a, b| a == b).unwrap_err();
+    /// assert_eq!(error.path, vec![1]);
+    /// ```
+    pub fn zip_with<U, R>(
+        self,
+        other: Node<U>,
+        policy: ZipPolicy,
+        mut f: impl FnMut(T, U) -> R,
+    ) -> Result<Node<R>, ShapeMismatch> {
+        if policy == ZipPolicy::Error {
+            let mut stack = vec![(&self, &other, Vec::new())];
+            while let Some((left, right, path)) = stack.pop() {
+                if left.children.len() != right.children.len() {
+                    return Err(ShapeMismatch {
+                        path,
+                        left: left.children.len(),
+                        right: right.children.len(),
+                    });
+                }
+                let pairs = left.children.iter().zip(&right.children).enumerate();
+                for (i, (left, right)) in pairs.rev() {
+                    let mut child = path.clone();
+                    child.push(i);
+                    stack.push((left, right, child));
+                }
+            }
+        }
+        Ok(Node::unfold::<DepthFirst, _>(
+            (self, other),
+            |(left, right)| {
+                let (left, left_children) = left.into_parts();
+                let (right, right_children) = right.into_parts();
+                let children = left_children.into_iter().zip(right_children).collect();
+                (f(left, right), children)
+            },
+        ))
+    }
+
+    /// Compares two trees position by position with `eq`, and reports the first position,
+    /// in depth-first order, where they differ.
+    ///
+    /// This is equality for trees whose values cannot be compared exactly, such as
+    /// floating-point transforms, with `eq` deciding whether two values are close enough.
+    /// At each position the values are compared before the numbers of children. The trees
+    /// are walked iteratively, so they may be of any depth.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first [`Mismatch`] found, either of values or of shapes.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::tree;
+    /// use tree_iter::tree::Mismatch;
+    ///
+    /// let close = |a: &f64, b: &f64| (a - b).abs() < 1e-9;
+    /// let expected = tree! { 1.0 => [0.3, 0.5 => [2.0]] };
+    ///
+    /// assert_eq!(expected.approx_eq(&tree! { 1.0 => [0.1 + 0.2, 0.5 => [2.0]] }, close), Ok(()));
+    /// assert_eq!(
+    ///     expected.approx_eq(&tree! { 1.0 => [0.3, 0.5 => [2.1]] }, close),
+    ///     Err(Mismatch::Value(vec![1, 0]))
+    /// );
+    /// let error = expected.approx_eq(&tree! { 1.0 => [0.3, 0.5] }, close).unwrap_err();
+    /// assert_eq!(error.path(), &[1]);
+    /// ```
+    pub fn approx_eq<U>(
+        &self,
+        other: &Node<U>,
+        mut eq: impl FnMut(&T, &U) -> bool,
+    ) -> Result<(), Mismatch> {
+        let mut path = Vec::new();
+        // Each pair of nodes with its depth and its index among its siblings
+        let mut stack = vec![(self, other, 0, 0)];
+        while let Some((left, right, depth, index)) = stack.pop() {
+            if depth > 0 {
+                path.truncate(depth - 1);
+                path.push(index);
+            }
+            if !eq(&left.value, &right.value) {
+                return Err(Mismatch::Value(path));
+            }
+            if left.children.len() != right.children.len() {
+                return Err(Mismatch::Shape(ShapeMismatch {
+                    path,
+                    left: left.children.len(),
+                    right: right.children.len(),
+                }));
+            }
+            let pairs = left.children.iter().zip(&right.children).enumerate();
+            for (i, (left, right)) in pairs.rev() {
+                stack.push((left, right, depth + 1, i));
+            }
+        }
+        Ok(())
+    }
+
+    /// Creates a builder for a node with the given value.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::tree::Node;
+    ///
+    /// let tree = Node::builder(1)
+    ///     .child(Node::builder(2).child(4).child(5))
+    ///     .children([3])
+    ///     .build();
+    ///
+    /// assert_eq!(
+    ///     tree,
+    ///     Node::with_children(
+    ///         1,
+    ///         [Node::with_children(2, [Node::new(4), Node::new(5)]), Node::new(3)],
+    ///     )
+    /// );
+    /// ```
+    pub fn builder(value: T) -> NodeBuilder<T> {
+        NodeBuilder::new(value)
+    }
+}
+
+impl<T: Clone> Node<T> {
+    /// Clones the subtree at `path` into a separate tree, or returns `None` if there is no
+    /// node at `path`.
+    ///
+    /// The subtree is copied iteratively, so unlike `clone` it may be of any depth.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::tree;
+    ///
+    /// let tree = tree! { 1 => [2 => [4, 5], 3] };
+    /// assert_eq!(tree.clone_subtree(&[0]), Some(tree! { 2 => [4, 5] }));
+    /// assert_eq!(tree.clone_subtree(&[2]), None);
+    /// ```
+    pub fn clone_subtree(&self, path: &[usize]) -> Option<Node<T>> {
+        let subtree = self.get(path)?;
+        Some(subtree.materialize::<DepthFirst, _>(|node| node.value.clone()))
+    }
+
+    /// Clones the values of the tree into an owned list, in the order `O`, along with the
+    /// depth and path of each node.
+    ///
+    /// The list borrows nothing from the tree, so unlike the iterators it can be sent to
+    /// other threads whenever `T: Send`. It is built in a single pass.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `O` - The traversal order strategy to use (e.g., `DepthFirst` or `BreadthFirst`).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::prelude::*;
+    /// use tree_iter::tree;
+    ///
+    /// let tree = tree! { 1 => [2 => [4], 3] };
+    /// let visits = tree.to_owned_traversal::<BreadthFirst>();
+    ///
+    /// let handle = std::thread::spawn(move || {
+    ///     visits
+    ///         .into_iter()
+    ///         .map(|visit| (visit.value, visit.depth, visit.path))
+    ///         .collect::<Vec<_>>()
+    /// });
+    /// assert_eq!(
+    ///     handle.join().unwrap(),
+    ///     vec![(1, 0, vec![]), (2, 1, vec![0]), (3, 1, vec![1]), (4, 2, vec![0, 0])]
+    /// );
+    /// ```
+    pub fn to_owned_traversal<O: TraversalOrder>(&self) -> Vec<OwnedVisit<T>> {
+        let mut visits = Vec::new();
+        let mut pending = VecDeque::from([(self, Vec::new())]);
+        while let Some((node, path)) = pending.pop_front() {
+            let children = node.children.iter().enumerate().map(|(i, child)| {
+                let mut child_path = path.clone();
+                child_path.push(i);
+                (child, child_path)
+            });
+            match O::KIND {
+                OrderKind::BreadthFirst => pending.extend(children),
+                OrderKind::DepthFirst => {
+                    for child in children.rev() {
+                        pending.push_front(child);
+                    }
+                }
+            }
+            visits.push(OwnedVisit {
+                value: node.value.clone(),
+                depth: path.len(),
+                path,
+            });
+        }
+        visits
+    }
+}
+
+impl<T: Clone + PartialEq> Node<T> {
+    /// Applies an edit script, such as one from [`edit_script`](crate::diff::edit_script),
+    /// edit by edit.
+    ///
+    /// Each edit is checked against the tree first: a change must find the value it
+    /// replaces, a removal the subtree it removes, and an insertion a parent with room for
+    /// the new child. If an edit does not apply, the edits already applied are undone, so
+    /// the tree is either fully patched or left as it was.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PatchError`] naming the first edit that does not apply and why.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::diff::{Conflict, edit_script};
+    /// use tree_iter::tree;
+    ///
+    /// let base = tree! { "doc" => ["title", "body"] };
+    /// let script = edit_script(&base, &tree! { "doc" => ["title", "text", "footer"] });
+    ///
+    /// // Someone else edited the body in the meantime
+    /// let mut theirs = tree! { "doc" => ["title", "draft"] };
+    /// let error = theirs.apply(&script).unwrap_err();
+    /// assert_eq!((error.edit, error.path, error.conflict), (0, vec![1], Conflict::Value));
+    /// assert_eq!(theirs, tree! { "doc" => ["title", "draft"] });
+    ///
+    /// let mut ours = base.clone();
+    /// ours.apply(&script).unwrap();
+    /// assert_eq!(ours, tree! { "doc" => ["title", "text", "footer"] });
+    /// ```
+    pub fn apply(&mut self, script: &[Edit<T>]) -> Result<(), PatchError> {
+        // How to undo each edit applied so far
+        let mut undo = Vec::new();
+        for (i, edit) in script.iter().enumerate() {
+            match self.apply_edit(edit) {
+                Ok(step) => undo.push(step),
+                Err(conflict) => {
+                    for step in undo.into_iter().rev() {
+                        self.undo_edit(step);
+                    }
+                    return Err(PatchError {
+                        edit: i,
+                        path: edit.path().to_vec(),
+                        conflict,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies one edit if it matches the tree, returning how to undo it.
+    fn apply_edit(&mut self, edit: &Edit<T>) -> Result<Undo<T>, Conflict> {
+        match edit {
+            Edit::Change { path, from, to } => {
+                let node = self.get_mut(path).ok_or(Conflict::Missing)?;
+                if node.value != *from {
+                    return Err(Conflict::Value);
+                }
+                let from = mem::replace(&mut node.value, to.clone());
+                Ok(Undo::Change(path.clone(), from))
+            }
+            Edit::Remove { path, subtree } => {
+                let (&index, parent) = path.split_last().ok_or(Conflict::Missing)?;
+                let parent = self.get_mut(parent).ok_or(Conflict::Missing)?;
+                let node = parent.children.get(index).ok_or(Conflict::Missing)?;
+                if node.approx_eq(subtree, |a, b| a == b).is_err() {
+                    return Err(Conflict::Subtree);
+                }
+                Ok(Undo::Remove(path.clone(), parent.remove_child(index)))
+            }
+            Edit::Insert { path, subtree } => {
+                let (&index, parent) = path.split_last().ok_or(Conflict::Missing)?;
+                let parent = self.get_mut(parent).ok_or(Conflict::Missing)?;
+                if index > parent.children.len() {
+                    return Err(Conflict::Missing);
+                }
+                let subtree = subtree.clone_subtree(&[]).expect("the root exists");
+                parent.insert_child(index, subtree);
+                Ok(Undo::Insert(path.clone()))
+            }
+        }
+    }
+
+    /// Undoes an edit applied by [`Node::apply_edit`], with every edit after it undone.
+    fn undo_edit(&mut self, step: Undo<T>) {
+        const APPLIED: &str = "an applied edit left its position in place";
+        match step {
+            Undo::Change(path, value) => self.get_mut(&path).expect(APPLIED).value = value,
+            Undo::Remove(path, subtree) => {
+                let (&index, parent) = path.split_last().expect(APPLIED);
+                self.get_mut(parent)
+                    .expect(APPLIED)
+                    .insert_child(index, subtree);
+            }
+            Undo::Insert(path) => {
+                let (&index, parent) = path.split_last().expect(APPLIED);
+                self.get_mut(parent).expect(APPLIED).remove_child(index);
+            }
+        }
+    }
+}
+
+/// How to undo an edit applied by [`Node::apply`].
+enum Undo<T> {
+    /// Restore the value at the path.
+    Change(Vec<usize>, T),
+    /// Reinsert the removed subtree at the path.
+    Remove(Vec<usize>, Node<T>),
+    /// Remove the subtree inserted at the path.
+    Insert(Vec<usize>),
+}
+
+/// A node visited by [`Node::to_owned_traversal`], with an owned copy of its value.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OwnedVisit<T> {
+    /// A clone of the node's value.
+    pub value: T,
+    /// The depth of the node; the root is at depth 0.
+    pub depth: usize,
+    /// The child-index path from the root to the node.
+    pub path: Vec<usize>,
+}
+
+/// What [`Node::zip_with`] does when the two trees have different shapes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ZipPolicy {
+    /// Keep only the positions present in both trees.
+    #[default]
+    Truncate,
+    /// Fail with a [`ShapeMismatch`].
+    Error,
+}
+
+/// The error returned when two trees that must have the same shape do not.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ShapeMismatch {
+    /// The path of the first node, in depth-first order, whose child counts differ.
+    pub path: Vec<usize>,
+    /// The number of children of the node in the left tree.
+    pub left: usize,
+    /// The number of children of the node in the right tree.
+    pub right: usize,
+}
+
+impl fmt::Display for ShapeMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "node at {:?} has {} children on the left and {} on the right",
+            self.path, self.left, self.right
+        )
+    }
+}
+
+impl Error for ShapeMismatch {}
+
+/// The first difference between two trees found by [`Node::approx_eq`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Mismatch {
+    /// The values at the given child-index path do not compare equal.
+    Value(Vec<usize>),
+    /// The nodes at a path have different numbers of children.
+    Shape(ShapeMismatch),
+}
+
+impl Mismatch {
+    /// Returns the child-index path of the differing nodes.
+    pub fn path(&self) -> &[usize] {
+        match self {
+            Self::Value(path) => path,
+            Self::Shape(mismatch) => &mismatch.path,
+        }
+    }
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Value(path) => write!(f, "values at {path:?} differ"),
+            Self::Shape(mismatch) => mismatch.fmt(f),
+        }
+    }
+}
+
+impl Error for Mismatch {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Value(_) => None,
+            Self::Shape(mismatch) => Some(mismatch),
+        }
+    }
+}
+
+/// A builder for constructing trees of [`Node`]s fluently.
+///
+/// Anything that converts into a `NodeBuilder` can be added as a child, which includes
+/// plain values, finished nodes, and other builders.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Hash)]
+pub struct NodeBuilder<T> {
+    /// The node under construction.
+    node: Node<T>,
+}
+
+impl<T> NodeBuilder<T> {
+    /// Creates a builder for a node with the given value and no children.
+    pub fn new(value: T) -> Self {
+        Self {
+            node: Node::new(value),
+        }
+    }
+
+    /// Appends a child to the node.
+    pub fn child(mut self, child: impl Into<NodeBuilder<T>>) -> Self {
+        self.node.children.push(child.into().build());
+        self
+    }
+
+    /// Appends several children to the node, in order.
+    pub fn children<C: Into<NodeBuilder<T>>>(
+        mut self,
+        children: impl IntoIterator<Item = C>,
+    ) -> Self {
+        self.node
+            .children
+            .extend(children.into_iter().map(|child| child.into().build()));
+        self
+    }
+
+    /// Finishes the node.
+    pub fn build(self) -> Node<T> {
+        self.node
+    }
+}
+
+impl<T> From<T> for NodeBuilder<T> {
+    /// Creates a builder for a leaf node with the given value.
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T> From<Node<T>> for NodeBuilder<T> {
+    /// Creates a builder that continues from an existing node.
+    fn from(node: Node<T>) -> Self {
+        Self { node }
+    }
+}
+
+impl<T> From<T> for Node<T> {
+    /// Creates a leaf node with the given value.
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T> From<(T, Vec<Node<T>>)> for Node<T> {
+    /// Creates a node from its value and children, the inverse of [`Node::into_parts`].
+    fn from((value, children): (T, Vec<Node<T>>)) -> Self {
+        Self { value, children }
+    }
+}
+
+impl<T> Extend<Node<T>> for Node<T> {
+    /// Appends the nodes to this node's children, like [`Node::extend_children`].
+    fn extend<I: IntoIterator<Item = Node<T>>>(&mut self, children: I) {
+        self.extend_children(children);
+    }
+}
+
+impl<T> Node<T> {
+    /// Builds a tree from `(path, value)` pairs, given in any order.
+    ///
+    /// Each value is placed at its child-index path, the empty path being the root. The
+    /// pairs are sorted by path, so that parents come before their children, and a path
+    /// given more than once keeps its last value.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PathsError`] if there is no value for the root, or for the parent or an
+    /// earlier sibling of a node that has one, as no placeholders are made up.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::path::TreePath;
+    /// use tree_iter::tree;
+    /// use tree_iter::tree::{Node, PathsError};
+    ///
+    /// let tree = Node::try_from_paths([(vec![0, 1], 5), (vec![], 1), (vec![1], 3), (vec![0], 2), (vec![0, 0], 4)]);
+    /// assert_eq!(tree, Ok(tree! { 1 => [2 => [4, 5], 3] }));
+    ///
+    /// let gap: Vec<(TreePath, i32)> = vec![(TreePath::root(), 1), (TreePath::from(vec![2]), 3)];
+    /// assert_eq!(Node::try_from_paths(gap), Err(PathsError::Gap(vec![2])));
+    /// assert_eq!(Node::try_from_paths([(vec![0], 2)]), Err(PathsError::MissingRoot));
+    /// ```
+    pub fn try_from_paths<P: Into<TreePath>>(
+        entries: impl IntoIterator<Item = (P, T)>,
+    ) -> Result<Self, PathsError> {
+        let mut entries: Vec<(TreePath, T)> = entries
+            .into_iter()
+            .map(|(path, value)| (path.into(), value))
+            .collect();
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        // The nodes along the path of the previous entry, each with its earlier children
+        let mut stack: Vec<Node<T>> = Vec::new();
+        let mut previous: Option<TreePath> = None;
+        for (path, value) in entries {
+            let Some(previous) = previous.replace(path.clone()) else {
+                if !path.is_root() {
+                    return Err(PathsError::MissingRoot);
+                }
+                stack.push(Node::new(value));
+                continue;
+            };
+            if path == previous {
+                stack.last_mut().unwrap().value = value;
+                continue;
+            }
+            let (&index, parent) = path.split_last().unwrap();
+            while stack.len() > path.len() {
+                let node = stack.pop().unwrap();
+                stack.last_mut().unwrap().children.push(node);
+            }
+            let siblings = &stack.last().unwrap().children;
+            if stack.len() < path.len()
+                || previous[..parent.len()] != *parent
+                || index != siblings.len()
+            {
+                return Err(PathsError::Gap(path.into_vec()));
+            }
+            stack.push(Node::new(value));
+        }
+        while stack.len() > 1 {
+            let node = stack.pop().unwrap();
+            stack.last_mut().unwrap().children.push(node);
+        }
+        stack.pop().ok_or(PathsError::MissingRoot)
+    }
+}
+
+/// The error returned when `(path, value)` pairs do not describe a tree, by
+/// [`Node::try_from_paths`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PathsError {
+    /// There is no value for the root.
+    MissingRoot,
+    /// The node at the given path has a value, but its parent or an earlier sibling has
+    /// none.
+    Gap(Vec<usize>),
+}
+
+impl fmt::Display for PathsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingRoot => f.write_str("no value for the root"),
+            Self::Gap(path) => write!(
+                f,
+                "no value for the parent or an earlier sibling of the node at {path:?}"
+            ),
+        }
+    }
+}
+
+impl Error for PathsError {}
+
+/// Builds a tree from `(path, value)` pairs, as [`Node::try_from_paths`] does.
+///
+/// # Panics
+///
+/// Panics if there is no value for the root, or for the parent or an earlier sibling of a
+/// node that has one.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::path::TreePath;
+/// use tree_iter::tree;
+/// use tree_iter::tree::Node;
+///
+/// let tree: Node<i32> = ["", "0.1", "1", "0", "0.0"]
+///     .into_iter()
+///     .zip([1, 5, 3, 2, 4])
+///     .map(|(path, value)| (path.parse::<TreePath>().unwrap(), value))
+///     .collect();
+/// assert_eq!(tree, tree! { 1 => [2 => [4, 5], 3] });
+/// ```
+impl<T> FromIterator<(TreePath, T)> for Node<T> {
+    fn from_iter<I: IntoIterator<Item = (TreePath, T)>>(entries: I) -> Self {
+        Node::try_from_paths(entries).unwrap_or_else(|e| panic!("{e}"))
+    }
+}
+
+/// Builds a tree from `(path, value)` pairs of raw child-index paths, as
+/// [`Node::try_from_paths`] does.
+///
+/// # Panics
+///
+/// Panics if there is no value for the root, or for the parent or an earlier sibling of a
+/// node that has one.
+impl<T> FromIterator<(Vec<usize>, T)> for Node<T> {
+    fn from_iter<I: IntoIterator<Item = (Vec<usize>, T)>>(entries: I) -> Self {
+        Node::try_from_paths(entries).unwrap_or_else(|e| panic!("{e}"))
+    }
+}
+
+impl<T: Default> Node<T> {
+    /// Drains the values out of the tree, leaving its structure intact.
+    ///
+    /// Values are yielded in depth-first (pre-order) order and each one is
+    /// replaced with `T::default()`, so the tree can be reused as a template
+    /// afterwards. Values that are not consumed from the iterator are left in place.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tree_iter::tree::Node;
+    ///
+    /// let mut tree = Node {
+    ///     value: 1,
+    ///     children: vec![Node::new(2), Node::new(3)],
+    /// };
+    ///
+    /// let values: Vec<i32> = tree.drain_values().collect();
+    /// assert_eq!(values, vec![1, 2, 3]);
+    /// assert_eq!(tree.value, 0);
+    /// assert_eq!(tree.children.len(), 2);
+    /// ```
+    pub fn drain_values(&mut self) -> DrainValues<'_, T> {
+        DrainValues { stack: vec![self] }
+    }
+}
+
+/// An iterator that takes the values out of a tree in depth-first order.
+///
+/// This struct is created by [`Node::drain_values`].
+#[derive(Debug)]
+pub struct DrainValues<'a, T> {
+    /// Stack of nodes whose values have not been taken yet.
+    stack: Vec<&'a mut Node<T>>,
+}
+
+impl<T: Default> Iterator for DrainValues<'_, T> {
+    type Item = T;
+
+    /// Takes the value of the next node in depth-first order.
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.stack.extend(node.children.iter_mut().rev());
+        Some(std::mem::take(&mut node.value))
+    }
+}
+
+/// Orders trees by their values in pre-order, then by their shapes.
+///
+/// The values of the two trees are compared in depth-first pre-order, as sequences, so
+/// the first differing value decides and a tree whose values run out first is less. Trees
+/// with the same value sequence are ordered by the numbers of children of their nodes, in
+/// the same order. This agrees with `==`, so trees can be sorted and used as `BTreeMap`
+/// keys. The comparison walks both trees iteratively, so they may be of any depth.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::tree;
+///
+/// let mut trees = vec![
+///     tree! { 1 => [3] },
+///     tree! { 1 => [2 => [3]] },
+///     tree! { 1 => [2, 3] },
+///     tree! { 0 => [9] },
+/// ];
+/// trees.sort();
+/// assert_eq!(
+///     trees,
+///     vec![tree! { 0 => [9] }, tree! { 1 => [2 => [3]] }, tree! { 1 => [2, 3] }, tree! { 1 => [3] }]
+/// );
+/// ```
+impl<T: Ord> Ord for Node<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // The first difference in shape, used only if all the values are equal
+        let mut shape = Ordering::Equal;
+        let (mut left, mut right) = (self.iter_dfs(), other.iter_dfs());
+        loop {
+            match (left.next(), right.next()) {
+                (Some(a), Some(b)) => {
+                    let order = a.value.cmp(&b.value);
+                    if order != Ordering::Equal {
+                        return order;
+                    }
+                    shape = shape.then(a.children.len().cmp(&b.children.len()));
+                }
+                (None, None) => return shape,
+                (None, Some(_)) => return Ordering::Less,
+                (Some(_), None) => return Ordering::Greater,
+            }
+        }
+    }
+}
+
+impl<T: Ord> PartialOrd for Node<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Drops the tree iteratively.
+///
+/// The default drop glue would recurse once per level, overflowing the stack on very deep
+/// trees. Instead, descendants are moved onto an explicit work list, so each node is dropped
+/// with no children left.
+impl<T: Clone> Clone for Node<T> {
+    fn clone(&self) -> Self {
+        self.fold(|node, children| Node {
+            value: node.value.clone(),
+            children,
+        })
+    }
+}
+
+impl<T: PartialEq> PartialEq for Node<T> {
+    fn eq(&self, other: &Self) -> bool {
+        let mut stack = vec![(self, other)];
+        while let Some((left, right)) = stack.pop() {
+            if left.value != right.value || left.children.len() != right.children.len() {
+                return false;
+            }
+            stack.extend(left.children.iter().zip(&right.children));
+        }
+        true
+    }
+}
+
+impl<T: Eq> Eq for Node<T> {}
+
+impl<T: Hash> Hash for Node<T> {
+    /// Hashes the values in depth-first order, each with its number of children, which
+    /// together determine the tree.
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for node in self.iter::<DepthFirst>() {
+            node.value.hash(state);
+            node.children.len().hash(state);
+        }
+    }
+}
+
+impl<T> Drop for Node<T> {
+    fn drop(&mut self) {
+        let mut stack = std::mem::take(&mut self.children);
+        while let Some(mut node) = stack.pop() {
+            stack.append(&mut node.children);
+        }
+    }
+}
+
+/// Indexes the children of a node.
+///
+/// # Panics
+///
+/// Panics if `index` is out of bounds.
+impl<T> Index<usize> for Node<T> {
+    type Output = Node<T>;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.children[index]
+    }
+}
+
+impl<T> IndexMut<usize> for Node<T> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.children[index]
+    }
+}
+
+/// Indexes the descendants of a node by their child-index paths.
+///
+/// The empty path refers to the node itself.
 ///
-/// # Type Parameters
+/// # Panics
 ///
-/// * `T` - The type of value stored in each node.
+/// Panics if any index along the path is out of bounds.
 ///
 /// # Examples
 ///
 /// ```rust
-/// use tree_iter::tree::Node;
-/// use tree_iter::prelude::*;
+/// use tree_iter::tree;
 ///
-/// // Create a simple tree
-/// let tree = Node {
-///     value: 1,
-///     children: vec![Node::new(2), Node::new(3)],
-/// };
+/// let mut tree = tree! { 1 => [2 => [4, 5], 3] };
+/// assert_eq!(tree[&[0, 1]].value, 5);
+/// assert_eq!(tree[1].value, 3);
 ///
-/// // Traverse in depth-first order
-/// let values: Vec<i32> = tree.iter::<DepthFirst>()
-///                           .map(|node| node.value)
-///                           .collect();
-/// assert_eq!(values, vec![1, 2, 3]);
+/// tree[&[0, 0]].value = 40;
+/// assert_eq!(tree[0][0].value, 40);
 /// ```
-#[derive(Debug, Clone, PartialEq, Eq, Default, Hash)]
-pub struct Node<T> {
-    /// The value stored in this node.
-    pub value: T,
-    /// The children of this node.
-    pub children: Vec<Node<T>>,
+impl<T> Index<&[usize]> for Node<T> {
+    type Output = Node<T>;
+
+    fn index(&self, path: &[usize]) -> &Self::Output {
+        path.iter().fold(self, |node, &i| &node.children[i])
+    }
 }
 
-impl<T> Node<T> {
-    /// Creates a new node with the given value and no children.
-    ///
-    /// # Parameters
-    ///
-    /// * `value` - The value to store in the node.
-    ///
-    /// # Returns
-    ///
-    /// A new `Node` with the given value and an empty children vector.
-    pub fn new(value: T) -> Self {
-        Self {
-            value,
-            children: Vec::new(),
-        }
+impl<T> IndexMut<&[usize]> for Node<T> {
+    fn index_mut(&mut self, path: &[usize]) -> &mut Self::Output {
+        path.iter().fold(self, |node, &i| &mut node.children[i])
+    }
+}
+
+impl<T, const N: usize> Index<&[usize; N]> for Node<T> {
+    type Output = Node<T>;
+
+    fn index(&self, path: &[usize; N]) -> &Self::Output {
+        &self[&path[..]]
+    }
+}
+
+impl<T, const N: usize> IndexMut<&[usize; N]> for Node<T> {
+    fn index_mut(&mut self, path: &[usize; N]) -> &mut Self::Output {
+        &mut self[&path[..]]
     }
 }
 
@@ -62,6 +1385,11 @@ impl<T> TreeNode for Node<T> {
     fn children(&self) -> impl DoubleEndedIterator<Item = &Self> {
         self.children.iter()
     }
+
+    /// Returns the children of this node, which are stored contiguously.
+    fn children_slice(&self) -> Option<&[Self]> {
+        Some(&self.children)
+    }
 }
 
 /// Implementation of `TreeNodeMut` for `Node<T>`.
@@ -74,6 +1402,97 @@ impl<T> TreeNodeMut for Node<T> {
     }
 }
 
+/// A tree that may be empty.
+///
+/// This wraps an optional root [`Node`], so that "no tree at all" can be represented and
+/// traversed like any other tree.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::prelude::*;
+/// use tree_iter::tree::{Node, Tree};
+///
+/// let mut tree = Tree::new();
+/// assert_eq!(tree.iter::<DepthFirst>().count(), 0);
+///
+/// tree.insert_root(Node::with_children(1, [Node::new(2)]));
+/// let values: Vec<i32> = tree.iter::<DepthFirst>().map(|node| node.value).collect();
+/// assert_eq!(values, vec![1, 2]);
+///
+/// assert_eq!(tree.take_root().map(|root| root.value), Some(1));
+/// assert!(tree.is_empty());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default, Hash)]
+pub struct Tree<T> {
+    /// The root node, or `None` if the tree is empty.
+    root: Option<Node<T>>,
+}
+
+impl<T> Tree<T> {
+    /// Creates an empty tree.
+    pub fn new() -> Self {
+        Self { root: None }
+    }
+
+    /// Returns `true` if the tree has no root.
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Returns the root node, if any.
+    pub fn root(&self) -> Option<&Node<T>> {
+        self.root.as_ref()
+    }
+
+    /// Returns the root node mutably, if any.
+    pub fn root_mut(&mut self) -> Option<&mut Node<T>> {
+        self.root.as_mut()
+    }
+
+    /// Sets the root node, returning the previous root if there was one.
+    pub fn insert_root(&mut self, root: Node<T>) -> Option<Node<T>> {
+        self.root.replace(root)
+    }
+
+    /// Removes the root node, leaving the tree empty.
+    pub fn take_root(&mut self) -> Option<Node<T>> {
+        self.root.take()
+    }
+
+    /// Creates an iterator that traverses the tree, yielding nothing if it is empty.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `O` - The traversal order strategy to use (e.g., `DepthFirst` or `BreadthFirst`).
+    pub fn iter<O: TraversalOrder>(&self) -> TreeIter<'_, Node<T>, O> {
+        TreeIter::new(self.root.iter())
+    }
+
+    /// Creates a mutable iterator that traverses the tree, yielding nothing if it is empty.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `O` - The traversal order strategy to use (e.g., `DepthFirst` or `BreadthFirst`).
+    pub fn iter_mut<O: TraversalOrder>(&mut self) -> TreeIterMut<'_, Node<T>, O> {
+        TreeIterMut::new(self.root.iter_mut())
+    }
+}
+
+impl<T> From<Node<T>> for Tree<T> {
+    /// Creates a tree with the given root.
+    fn from(root: Node<T>) -> Self {
+        Self { root: Some(root) }
+    }
+}
+
+impl<T> From<Option<Node<T>>> for Tree<T> {
+    /// Creates a tree with the given root, or an empty tree.
+    fn from(root: Option<Node<T>>) -> Self {
+        Self { root }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -129,6 +1548,31 @@ mod tests {
         assert_eq!(values, vec![1, 2, 3, 4, 5, 6]);
     }
 
+    #[test]
+    fn test_order_shorthands() {
+        let mut tree = crate::tree! { 1 => [2 => [4, 5], 3 => [6]] };
+
+        let values: Vec<i32> = tree.iter_dfs().map(|n| n.value).collect();
+        assert_eq!(values, vec![1, 2, 4, 5, 3, 6]);
+        let values: Vec<i32> = tree.iter_bfs().map(|n| n.value).collect();
+        assert_eq!(values, vec![1, 2, 3, 4, 5, 6]);
+
+        let mut order = Vec::new();
+        let mut iter = tree.iter_mut_dfs();
+        while let Some(mut node) = iter.next() {
+            order.push(node.value);
+            node.value *= 10;
+        }
+        assert_eq!(order, vec![1, 2, 4, 5, 3, 6]);
+
+        let mut order = Vec::new();
+        let mut iter = tree.iter_mut_bfs();
+        while let Some(node) = iter.next() {
+            order.push(node.value);
+        }
+        assert_eq!(order, vec![10, 20, 30, 40, 50, 60]);
+    }
+
     #[test]
     fn test_mutable_depth_first_traversal() {
         let mut tree = Node {
@@ -174,6 +1618,30 @@ mod tests {
         assert_eq!(values, vec![11, 12, 13, 14, 20]);
     }
 
+    #[test]
+    fn test_skip_children() {
+        let mut tree = crate::tree! { 1 => [2 => [4, 5], 3 => [6]] };
+
+        let mut iter = tree.iter_mut::<DepthFirst>();
+        while let Some(mut node) = iter.next() {
+            if node.value == 2 {
+                node.skip_children();
+            }
+            node.value *= 10;
+        }
+        assert_eq!(tree, crate::tree! { 10 => [20 => [4, 5], 30 => [60]] });
+
+        let mut visited = Vec::new();
+        let mut iter = tree.iter::<BreadthFirst>();
+        while let Some(mut node) = iter.next_guard() {
+            if node.value == 30 {
+                node.skip_children();
+            }
+            visited.push(node.value);
+        }
+        assert_eq!(visited, vec![10, 20, 30, 4, 5]);
+    }
+
     #[test]
     fn test_tree_modification() {
         // Create a tree manually with specific structure
@@ -219,6 +1687,424 @@ mod tests {
         assert_eq!(value, vec![11, 13, 12, 14]);
     }
 
+    #[test]
+    fn test_child_management() {
+        let mut tree = Node::new(1);
+        tree.push_child(Node::new(3));
+        tree.insert_child(0, Node::new(2));
+        tree.push_child(Node::with_children(4, [Node::new(5)]));
+        assert_eq!(tree, crate::tree! { 1 => [2, 3, 4 => [5]] });
+
+        tree.swap_children(0, 2);
+        assert_eq!(tree, crate::tree! { 1 => [4 => [5], 3, 2] });
+
+        let removed = tree.remove_child(0);
+        assert_eq!(removed, crate::tree! { 4 => [5] });
+        assert_eq!(tree, crate::tree! { 1 => [3, 2] });
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_index_invalid_path() {
+        let tree = crate::tree! { 1 => [2 => [4], 3] };
+        let _ = &tree[&[1, 0]];
+    }
+
+    #[test]
+    #[should_panic(expected = "no node at path [0, 1]")]
+    fn test_split_off_invalid_path() {
+        let mut tree = crate::tree! { 1 => [2 => [4], 3] };
+        tree.split_off(&[0, 1]);
+    }
+
+    #[test]
+    fn test_diameter() {
+        let values = |tree: &Node<i32>| -> Vec<i32> {
+            tree.diameter().iter().map(|node| node.value).collect()
+        };
+        assert_eq!(values(&Node::new(1)), vec![1]);
+        assert_eq!(values(&crate::tree! { 1 => [2 => [3]] }), vec![1, 2, 3]);
+        assert_eq!(
+            values(&crate::tree! { 1 => [2, 3 => [4 => [5, 6 => [7]], 8 => [9 => [10]]]] }),
+            vec![7, 6, 4, 3, 8, 9, 10]
+        );
+
+        let mut deep = Node::new(0);
+        for i in 1..100_000 {
+            deep = Node::with_children(i, [deep, Node::new(-i)]);
+        }
+        let path = deep.diameter();
+        assert_eq!(path.len(), 100_001);
+        assert_eq!((path[0].value, path[100_000].value), (0, -99_999));
+    }
+
+    #[test]
+    fn test_clone_subtree_and_materialize() {
+        let mut deep = Node::new(0);
+        for i in 1..100_000 {
+            deep = Node::with_children(i, [deep]);
+        }
+        let copy = deep.clone_subtree(&[0; 10]).unwrap();
+        assert_eq!(copy.value, 99_989);
+        assert_eq!(copy.height(), 99_989);
+
+        let tree = crate::tree! { 1 => [2 => [4], 3] };
+        let mut calls = Vec::new();
+        let copy = tree.materialize::<BreadthFirst, _>(|node| {
+            calls.push(node.value);
+            node.value * 10
+        });
+        assert_eq!(calls, vec![1, 2, 3, 4]);
+        assert_eq!(copy, crate::tree! { 10 => [20 => [40], 30] });
+    }
+
+    #[test]
+    fn test_optional_root_tree() {
+        let mut tree: Tree<i32> = Tree::new();
+        let mut iter = tree.iter_mut::<BreadthFirst>();
+        assert!(iter.next().is_none());
+
+        assert_eq!(tree.insert_root(crate::tree! { 1 => [2, 3] }), None);
+        let mut iter = tree.iter_mut::<BreadthFirst>();
+        while let Some(mut node) = iter.next() {
+            node.value *= 10;
+        }
+        let old = tree.insert_root(Node::new(4));
+        assert_eq!(old, Some(crate::tree! { 10 => [20, 30] }));
+        assert_eq!(Tree::from(Node::new(4)), tree);
+    }
+
+    #[test]
+    fn test_metrics() {
+        let leaf = Node::new(1);
+        assert_eq!(leaf.height(), 0);
+        assert_eq!(leaf.count(), 1);
+        assert_eq!(leaf.max_degree(), 0);
+        assert_eq!(leaf.width_per_level(), vec![1]);
+
+        // A deep chain must not overflow the stack
+        let mut chain = Node::new(0);
+        for i in 1..100_000 {
+            chain = Node::with_children(i, [chain]);
+        }
+        assert_eq!(chain.height(), 99_999);
+        assert_eq!(chain.count(), 100_000);
+        assert_eq!(chain.max_degree(), 1);
+    }
+
+    #[test]
+    fn test_deep_standard_traits() {
+        use std::hash::BuildHasher;
+
+        let mut chain = Node::new(0);
+        for i in 1..100_000 {
+            chain = Node::with_children(i, [chain]);
+        }
+        let copy = chain.clone();
+        assert!(copy == chain);
+        let hasher = std::hash::RandomState::new();
+        assert_eq!(hasher.hash_one(&copy), hasher.hash_one(&chain));
+
+        let mut other = chain.clone();
+        let mut node = &mut other;
+        while !node.children.is_empty() {
+            node = &mut node.children[0];
+        }
+        node.value = -1;
+        assert!(other != chain);
+
+        // Equal values in different shapes differ, and hash differently
+        let wide = crate::tree! { 1 => [2, 3] };
+        let deep = crate::tree! { 1 => [2 => [3]] };
+        assert_ne!(wide, deep);
+        assert_ne!(hasher.hash_one(&wide), hasher.hash_one(&deep));
+        assert_eq!(wide.clone(), wide);
+    }
+
+    #[test]
+    fn test_fold() {
+        // Children's results come in order, after the children themselves are folded
+        let tree = crate::tree! { 1 => [2 => [4, 5], 3] };
+        let folded = tree.fold(|node, children: Vec<String>| {
+            if children.is_empty() {
+                node.value.to_string()
+            } else {
+                format!("{}({})", node.value, children.join(","))
+            }
+        });
+        assert_eq!(folded, "1(2(4,5),3)");
+        assert_eq!(
+            tree.fold(|node, children: Vec<i32>| node.value + children.iter().sum::<i32>()),
+            15
+        );
+
+        // A deep chain must not overflow the stack
+        let mut chain = Node::new(0);
+        for i in 1..100_000 {
+            chain = Node::with_children(i, [chain]);
+        }
+        assert_eq!(chain.fold(|_, children: Vec<usize>| children.len() + 1), 2);
+        assert_eq!(
+            chain.fold(|_, children: Vec<usize>| children.first().map_or(0, |h| h + 1)),
+            99_999
+        );
+    }
+
+    #[test]
+    fn test_unfold_orders() {
+        let mut calls = Vec::new();
+        let expand = |calls: &mut Vec<u32>, depth: u32| {
+            calls.push(depth);
+            (
+                depth,
+                if depth < 2 {
+                    vec![depth + 1; 2]
+                } else {
+                    vec![]
+                },
+            )
+        };
+        let df = Node::unfold::<DepthFirst, _>(0, |d| expand(&mut calls, d));
+        assert_eq!(calls, vec![0, 1, 2, 2, 1, 2, 2]);
+
+        calls.clear();
+        let bf = Node::unfold::<BreadthFirst, _>(0, |d| expand(&mut calls, d));
+        assert_eq!(calls, vec![0, 1, 1, 2, 2, 2, 2]);
+        assert_eq!(df, bf);
+        assert_eq!(df, crate::tree! { 0 => [1 => [2, 2], 1 => [2, 2]] });
+
+        // Deep trees are built without recursion
+        let chain = Node::unfold::<DepthFirst, _>(0, |n| {
+            (n, if n < 100_000 { vec![n + 1] } else { vec![] })
+        });
+        assert_eq!(chain.height(), 100_000);
+    }
+
+    #[test]
+    fn test_drop_deep_tree() {
+        let mut tree = Node::new(0);
+        for i in 1..1_000_000 {
+            tree = Node::with_children(i, [tree]);
+        }
+        drop(tree);
+    }
+
+    #[test]
+    fn test_graft_and_merge() {
+        let mut tree = crate::tree! { 1 => [2] };
+        assert_eq!(tree.graft_at(&[], Node::new(9)), Err(Node::new(9)));
+        assert_eq!(tree.graft_at(&[0, 1], Node::new(9)), Err(Node::new(9)));
+        assert!(tree.graft_at(&[1], Node::new(3)).is_ok());
+        assert_eq!(tree, crate::tree! { 1 => [2, 3] });
+
+        // Conflicts are resolved in depth-first order
+        let mut calls = Vec::new();
+        tree.merge(crate::tree! { 10 => [20 => [40], 30, 50] }, |a, b| {
+            calls.push((*a, b));
+            *a += b;
+        });
+        assert_eq!(calls, vec![(1, 10), (2, 20), (3, 30)]);
+        assert_eq!(tree, crate::tree! { 11 => [22 => [40], 33, 50] });
+
+        // Deep trees are merged without recursion
+        let mut left = Node::new(0);
+        let mut right = Node::new(0);
+        for i in 1..100_000 {
+            left = Node::with_children(i, [left]);
+            right = Node::with_children(i, [right]);
+        }
+        left.merge(right, |a, b| *a += b);
+        assert_eq!(left.value, 2 * 99_999);
+    }
+
+    #[test]
+    fn test_sort_children() {
+        // Stable: children with equal keys keep their relative order
+        let mut tree =
+            crate::tree! { (0, ' ') => [(2, 'a') => [(9, 'x'), (1, 'y')], (1, 'b'), (2, 'c')] };
+        tree.sort_children_by_key(|(key, _)| *key);
+        assert_eq!(
+            tree,
+            crate::tree! { (0, ' ') => [(1, 'b'), (2, 'a') => [(1, 'y'), (9, 'x')], (2, 'c')] }
+        );
+
+        // Deep trees are sorted without recursion
+        let mut chain = Node::new(0);
+        for i in 1..100_000 {
+            chain = Node::with_children(i, [Node::new(-1), chain]);
+        }
+        chain.sort_children_by_key(|value| *value);
+        assert_eq!(chain.get(&[1, 1, 0]).map(|node| node.value), Some(-1));
+        assert_eq!(chain.get(&[1; 20]).map(|node| node.value), Some(99_979));
+    }
+
+    #[test]
+    fn test_into_parts() {
+        let tree = crate::tree! { String::from("a") => [String::from("b"), String::from("c")] };
+        let (value, children) = tree.into_parts();
+        assert_eq!(value, "a");
+        assert_eq!(children, vec![Node::new("b".into()), Node::new("c".into())]);
+    }
+
+    #[test]
+    fn test_owned_traversal_matches_iter() {
+        let tree = crate::tree! { 1 => [2 => [4, 5], 3 => [6]] };
+        let owned: Vec<i32> = tree
+            .to_owned_traversal::<DepthFirst>()
+            .into_iter()
+            .map(|visit| visit.value)
+            .collect();
+        let borrowed: Vec<i32> = tree.iter::<DepthFirst>().map(|n| n.value).collect();
+        assert_eq!(owned, borrowed);
+
+        for visit in tree.to_owned_traversal::<BreadthFirst>() {
+            assert_eq!(tree[visit.path.as_slice()].value, visit.value);
+        }
+    }
+
+    #[test]
+    fn test_std_conversions() {
+        let (value, children) = crate::tree! { 1 => [2, 3] }.into_parts();
+        let mut tree = Node::from((value, children));
+        assert_eq!(tree, crate::tree! { 1 => [2, 3] });
+
+        tree.extend([4, 5].map(Node::from));
+        assert_eq!(tree, crate::tree! { 1 => [2, 3, 4, 5] });
+        assert_eq!(Node::<i32>::default(), Node::from(0));
+    }
+
+    #[test]
+    fn test_structural_equality() {
+        use std::collections::HashSet;
+
+        let tree = crate::tree! { 1 => [2 => [4], 3] };
+        assert_eq!(tree, tree.clone());
+
+        // Same values in a different shape
+        let reshaped = crate::tree! { 1 => [2, 4, 3] };
+        assert_ne!(tree, reshaped);
+
+        let trees = HashSet::from([tree.clone(), reshaped, tree.clone()]);
+        assert_eq!(trees.len(), 2);
+        assert_eq!(Node::<i32>::default(), Node::new(0));
+    }
+
+    #[test]
+    fn test_approx_eq() {
+        let mut left = Node::new(0.0);
+        let mut right = Node::new(0.0f32);
+        for i in 1..100_000 {
+            left = Node::with_children(f64::from(i) / 3.0, [left]);
+            right = Node::with_children(i as f32 / 3.0, [right]);
+        }
+        let close = |a: &f64, b: &f32| (a - f64::from(*b)).abs() < 1e-2;
+        assert_eq!(left.approx_eq(&right, close), Ok(()));
+
+        right
+            .get_mut(&[0; 50_000])
+            .unwrap()
+            .push_child(Node::new(1.0));
+        let error = left.approx_eq(&right, close).unwrap_err();
+        assert_eq!(error.path().len(), 50_000);
+        assert_eq!(
+            error,
+            Mismatch::Shape(ShapeMismatch {
+                path: vec![0; 50_000],
+                left: 1,
+                right: 2,
+            })
+        );
+    }
+
+    #[test]
+    fn test_tree_macro() {
+        let tree: Node<i32> = crate::tree! { 1 => [2 => [4 => [7], 5], 3 => [6 => [8, 9],],] };
+        let values: Vec<i32> = tree.iter::<DepthFirst>().map(|n| n.value).collect();
+        assert_eq!(values, vec![1, 2, 4, 7, 5, 3, 6, 8, 9]);
+
+        let leaf = crate::tree!("leaf".to_string());
+        assert_eq!(leaf, Node::new("leaf".to_string()));
+
+        let empty_children: Node<i32> = crate::tree! { 1 => [] };
+        assert_eq!(empty_children, Node::new(1));
+    }
+
+    #[test]
+    fn test_wide_tree_macro() {
+        // Far more children than the default recursion limit of 128
+        let tree: Node<i32> = crate::tree! { 0 => [
+            1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24,
+            25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45,
+            46, 47, 48, 49, 50 => [500], 51, 52, 53, 54, 55, 56, 57, 58, 59, 60, 61, 62, 63, 64,
+            65, 66, 67, 68, 69, 70, 71, 72, 73, 74, 75, 76, 77, 78, 79, 80, 81, 82, 83, 84, 85,
+            86, 87, 88, 89, 90, 91, 92, 93, 94, 95, 96, 97, 98, 99, 100 => [1000], 101, 102, 103,
+            104, 105, 106, 107, 108, 109, 110, 111, 112, 113, 114, 115, 116, 117, 118, 119, 120,
+            121, 122, 123, 124, 125, 126, 127, 128, 129, 130, 131, 132, 133, 134, 135, 136, 137,
+            138, 139, 140, 141, 142, 143, 144, 145, 146, 147, 148, 149, 150 => [1500], 151, 152,
+            153, 154, 155, 156, 157, 158, 159, 160, 161, 162, 163, 164, 165, 166, 167, 168, 169,
+            170, 171, 172, 173, 174, 175, 176, 177, 178, 179, 180, 181, 182, 183, 184, 185, 186,
+            187, 188, 189, 190, 191, 192, 193, 194, 195, 196, 197, 198, 199, 200 => [2000], 201,
+            202, 203, 204, 205, 206, 207, 208, 209, 210
+        ] };
+        assert_eq!(tree.children.len(), 210);
+        assert!(
+            tree.children
+                .iter()
+                .zip(1..)
+                .all(|(child, i)| child.value == i)
+        );
+        assert_eq!(tree.children[49].children, vec![Node::new(500)]);
+        assert_eq!(tree.count(), 215);
+    }
+
+    #[test]
+    fn test_for_each_mut_catch() {
+        let mut tree = crate::tree! { 1 => [2 => [3 => [4, 5], 6], 7] };
+        let caught = tree.for_each_mut_catch(|node| {
+            node.value += 100;
+            match node.value {
+                102 => panic!("static message"),
+                103 => std::panic::panic_any(3u32),
+                105 => panic!("formatted {}", node.value),
+                _ => {}
+            }
+        });
+
+        // The children and later siblings of a panicking node are still visited
+        assert_eq!(
+            tree,
+            crate::tree! { 101 => [102 => [103 => [104, 105], 106], 107] }
+        );
+        let paths: Vec<&[usize]> = caught.iter().map(|panic| panic.path.as_slice()).collect();
+        assert_eq!(paths, vec![&[0][..], &[0, 0], &[0, 0, 1]]);
+        assert_eq!(caught[0].message(), Some("static message"));
+        assert_eq!(caught[1].message(), None);
+        assert_eq!(caught[1].payload.downcast_ref::<u32>(), Some(&3));
+        assert_eq!(caught[2].message(), Some("formatted 105"));
+    }
+
+    #[test]
+    fn test_drain_values() {
+        let mut tree = Node {
+            value: 1,
+            children: vec![
+                Node {
+                    value: 2,
+                    children: vec![Node::new(4)],
+                },
+                Node::new(3),
+            ],
+        };
+
+        let values: Vec<i32> = tree.drain_values().collect();
+        assert_eq!(values, vec![1, 2, 4, 3]);
+
+        // The shape is kept, with default values in place
+        let values: Vec<i32> = tree.iter::<DepthFirst>().map(|n| n.value).collect();
+        assert_eq!(values, vec![0, 0, 0, 0]);
+        assert_eq!(tree.children[0].children.len(), 1);
+    }
+
     #[test]
     fn test_complex_tree_traversal() {
         // Create a more complex tree
@@ -269,4 +2155,89 @@ mod tests {
         }
         assert_eq!(bf_values_mut, vec![1, 2, 3, 4, 5, 6, 7, 8, 9]);
     }
+
+    #[test]
+    fn test_ord_agrees_with_eq() {
+        // Small trees of every shape up to four nodes, with values 0 and 1
+        let shapes = [
+            crate::tree! { 0 },
+            crate::tree! { 0 => [0] },
+            crate::tree! { 0 => [0, 0] },
+            crate::tree! { 0 => [0 => [0]] },
+            crate::tree! { 0 => [0, 0, 0] },
+            crate::tree! { 0 => [0 => [0], 0] },
+            crate::tree! { 0 => [0, 0 => [0]] },
+            crate::tree! { 0 => [0 => [0, 0]] },
+            crate::tree! { 0 => [0 => [0 => [0]]] },
+        ];
+        let mut trees = Vec::new();
+        for shape in &shapes {
+            let size = shape.iter_dfs().count();
+            for bits in 0..1u32 << size {
+                let mut tree = shape.clone();
+                let mut iter = tree.iter_mut::<DepthFirst>();
+                let mut i = 0;
+                while let Some(mut node) = iter.next() {
+                    node.value = bits >> i & 1;
+                    i += 1;
+                }
+                trees.push(tree);
+            }
+        }
+        for a in &trees {
+            for b in &trees {
+                assert_eq!(a.cmp(b) == Ordering::Equal, a == b);
+                assert_eq!(a.cmp(b), b.cmp(a).reverse());
+            }
+        }
+        let keys: std::collections::BTreeSet<Node<u32>> = trees.iter().cloned().collect();
+        assert_eq!(keys.len(), trees.len());
+        assert!(keys.iter().zip(keys.iter().skip(1)).all(|(a, b)| a < b));
+    }
+
+    #[test]
+    fn test_from_paths() {
+        let tree = crate::tree! { 1 => [2 => [4, 5 => [7]], 3 => [6]] };
+        let ids = crate::preorder::PreorderIds::new(&tree);
+        let mut pairs: Vec<(TreePath, i32)> = tree
+            .iter_dfs()
+            .enumerate()
+            .map(|(id, node)| (ids.path(id).into(), node.value))
+            .collect();
+        pairs.reverse();
+        assert_eq!(pairs.iter().cloned().collect::<Node<i32>>(), tree);
+
+        // The last value given for a path wins
+        pairs.push((TreePath::from(vec![1]), 8));
+        assert_eq!(Node::try_from_paths(pairs).unwrap().children[1].value, 8);
+
+        for (paths, error) in [
+            (vec![vec![0]], PathsError::MissingRoot),
+            (vec![vec![], vec![1]], PathsError::Gap(vec![1])),
+            (vec![vec![], vec![0, 0]], PathsError::Gap(vec![0, 0])),
+            (
+                vec![vec![], vec![0], vec![1, 0]],
+                PathsError::Gap(vec![1, 0]),
+            ),
+            (
+                vec![vec![], vec![0], vec![0, 1]],
+                PathsError::Gap(vec![0, 1]),
+            ),
+            (
+                vec![vec![], vec![usize::MAX]],
+                PathsError::Gap(vec![usize::MAX]),
+            ),
+        ] {
+            let pairs = paths.into_iter().map(|path| (path, 0));
+            assert_eq!(Node::try_from_paths(pairs), Err(error));
+        }
+        assert!(Node::<i32>::try_from_paths(Vec::<(Vec<usize>, i32)>::new()).is_err());
+
+        let result = std::panic::catch_unwind(|| {
+            [(vec![], 0), (vec![usize::MAX], 1)]
+                .into_iter()
+                .collect::<Node<i32>>()
+        });
+        assert!(result.is_err());
+    }
 }
@@ -0,0 +1,288 @@
+use std::{
+    collections::VecDeque,
+    fmt,
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+};
+
+use crate::traversal_order::{OrderKind, TraversalOrder};
+
+/// Trait for mutable tree traversal where reaching the children of a node can fail.
+///
+/// This is the fallible counterpart of [`TreeNodeMut`](crate::iter_mut::TreeNodeMut), for
+/// trees whose children are produced on demand, such as trees that load a subtree on first
+/// write. An error stops the traversal cleanly and is reported by the iterator.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::prelude::*;
+/// use tree_iter::try_iter_mut::TryTreeNodeMut;
+///
+/// // A directory whose entries are loaded on first access
+/// struct Dir {
+///     name: String,
+///     readable: bool,
+///     entries: Option<Vec<Dir>>,
+/// }
+///
+/// impl TryTreeNodeMut for Dir {
+///     type Error = String;
+///
+///     fn try_children_mut(
+///         &mut self,
+///     ) -> Result<impl DoubleEndedIterator<Item = &mut Self>, Self::Error> {
+///         if self.entries.is_none() {
+///             if !self.readable {
+///                 return Err(format!("cannot read {}", self.name));
+///             }
+///             self.entries = Some(Vec::new());
+///         }
+///         Ok(self.entries.iter_mut().flatten())
+///     }
+/// }
+///
+/// let mut root = Dir {
+///     name: "root".into(),
+///     readable: true,
+///     entries: Some(vec![
+///         Dir { name: "locked".into(), readable: false, entries: None },
+///         Dir { name: "open".into(), readable: true, entries: None },
+///     ]),
+/// };
+///
+/// let mut names = Vec::new();
+/// let mut iter = root.try_iter_mut::<DepthFirst>();
+/// while let Some(node) = iter.next() {
+///     match node {
+///         Ok(mut dir) => {
+///             dir.name.make_ascii_uppercase();
+///             names.push(dir.name.clone());
+///         }
+///         Err(e) => names.push(e),
+///     }
+/// }
+/// // The error stops the traversal before "open" is reached
+/// assert_eq!(names, vec!["ROOT", "LOCKED", "cannot read LOCKED"]);
+/// ```
+pub trait TryTreeNodeMut {
+    /// The error returned when the children of a node cannot be reached.
+    type Error;
+
+    /// Returns a mutable iterator over the children of this node, or an error if they cannot be reached.
+    ///
+    /// This method must be implemented by all types implementing `TryTreeNodeMut`.
+    fn try_children_mut(
+        &mut self,
+    ) -> Result<impl DoubleEndedIterator<Item = &mut Self>, Self::Error>;
+
+    /// Creates a fallible mutable iterator that traverses the tree starting from this node.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `T` - The traversal order strategy to use (e.g., `DepthFirst` or `BreadthFirst`).
+    fn try_iter_mut<T: TraversalOrder>(&mut self) -> TryTreeIterMut<'_, Self, T>
+    where
+        Self: Sized,
+    {
+        TryTreeIterMut::new([self])
+    }
+}
+
+/// A fallible mutable iterator over tree nodes in a specified traversal order.
+///
+/// Like [`TreeIterMut`](crate::iter_mut::TreeIterMut), it hands out guards, and the
+/// children of a node are reached when its guard is dropped. If that fails, the remaining
+/// nodes are discarded and the error is returned by the next call to `next`, after which
+/// the iteration is over.
+///
+/// # Type Parameters
+///
+/// * `'a` - The lifetime of the tree nodes being traversed.
+/// * `N` - The type of tree node.
+/// * `T` - The traversal order strategy (e.g., `DepthFirst` or `BreadthFirst`).
+#[derive(Debug)]
+pub struct TryTreeIterMut<'a, N: TryTreeNodeMut, T> {
+    /// Queue of nodes to be visited.
+    nodes: VecDeque<&'a mut N>,
+    /// The error that stopped the traversal, until it is returned.
+    error: Option<N::Error>,
+    /// Phantom data to track the traversal order type.
+    _order: PhantomData<T>,
+}
+
+impl<'a, N: TryTreeNodeMut, T: TraversalOrder> TryTreeIterMut<'a, N, T> {
+    /// Creates a new fallible mutable tree iterator from a collection of root nodes.
+    ///
+    /// # Parameters
+    ///
+    /// * `roots` - An iterator yielding mutable references to the root nodes to start traversal from.
+    pub fn new(roots: impl IntoIterator<Item = &'a mut N>) -> Self {
+        Self {
+            nodes: roots.into_iter().collect(),
+            error: None,
+            _order: PhantomData,
+        }
+    }
+
+    /// Returns the next node in the traversal order, or the error that stopped the traversal.
+    ///
+    /// This method returns a guard that implements `DerefMut` to provide
+    /// mutable access to the node. When the guard is dropped, the node's children
+    /// are added to the traversal queue.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<Result<TryRefMutGuard<'a, '_, N, T>, N::Error>> {
+        if let Some(error) = self.error.take() {
+            return Some(Err(error));
+        }
+        self.nodes.pop_front().map(|node| {
+            Ok(TryRefMutGuard {
+                iter: self,
+                node: Some(node),
+                skip_children: false,
+            })
+        })
+    }
+}
+
+/// A guard for mutable node references in fallible traversal.
+///
+/// When the guard is dropped, the node's children are added to the traversal queue, or the
+/// traversal is stopped if they cannot be reached.
+///
+/// The guard implements `Deref` and `DerefMut` to allow direct access to the underlying node.
+pub struct TryRefMutGuard<'a: 'b, 'b, N: TryTreeNodeMut, T: TraversalOrder> {
+    /// Reference to the tree iterator.
+    iter: &'b mut TryTreeIterMut<'a, N, T>,
+    /// The current node being visited (wrapped in Option to allow taking ownership in drop).
+    node: Option<&'a mut N>,
+    /// Whether the children of the node are left out of the traversal.
+    skip_children: bool,
+}
+
+impl<N: TryTreeNodeMut, T: TraversalOrder> TryRefMutGuard<'_, '_, N, T> {
+    /// Leaves the children of this node, and their subtrees, out of the traversal.
+    ///
+    /// The children are then never requested, so this cannot fail.
+    pub fn skip_children(&mut self) {
+        self.skip_children = true;
+    }
+}
+
+impl<N: TryTreeNodeMut + fmt::Debug, T: TraversalOrder> fmt::Debug
+    for TryRefMutGuard<'_, '_, N, T>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TryRefMutGuard")
+            .field("node", &self.node)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<N: TryTreeNodeMut, T: TraversalOrder> Drop for TryRefMutGuard<'_, '_, N, T> {
+    /// When the guard is dropped, add the node's children to the traversal queue.
+    ///
+    /// If the children cannot be reached, the queue is cleared and the error is kept
+    /// for the iterator to return.
+    fn drop(&mut self) {
+        let node = self.node.take().unwrap();
+        if self.skip_children {
+            return;
+        }
+        match node.try_children_mut() {
+            Ok(children) => match T::KIND {
+                OrderKind::BreadthFirst => self.iter.nodes.extend(children),
+                OrderKind::DepthFirst => {
+                    for child in children.rev() {
+                        self.iter.nodes.push_front(child);
+                    }
+                }
+            },
+            Err(error) => {
+                self.iter.nodes.clear();
+                self.iter.error = Some(error);
+            }
+        }
+    }
+}
+
+impl<N: TryTreeNodeMut, T: TraversalOrder> Deref for TryRefMutGuard<'_, '_, N, T> {
+    type Target = N;
+
+    /// Provides immutable access to the wrapped node.
+    fn deref(&self) -> &Self::Target {
+        self.node.as_ref().unwrap()
+    }
+}
+
+impl<N: TryTreeNodeMut, T: TraversalOrder> DerefMut for TryRefMutGuard<'_, '_, N, T> {
+    /// Provides mutable access to the wrapped node.
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.node.as_mut().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traversal_order::BreadthFirst;
+
+    /// A node whose children fail to load when its value is negative.
+    struct Lazy {
+        value: i32,
+        children: Vec<Lazy>,
+    }
+
+    impl TryTreeNodeMut for Lazy {
+        type Error = i32;
+
+        fn try_children_mut(
+            &mut self,
+        ) -> Result<impl DoubleEndedIterator<Item = &mut Self>, Self::Error> {
+            if self.value < 0 {
+                return Err(self.value);
+            }
+            Ok(self.children.iter_mut())
+        }
+    }
+
+    fn leaf(value: i32) -> Lazy {
+        Lazy {
+            value,
+            children: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_breadth_first_stops_at_error() {
+        let mut root = Lazy {
+            value: 1,
+            children: vec![
+                Lazy {
+                    value: 2,
+                    children: vec![leaf(4)],
+                },
+                Lazy {
+                    value: -3,
+                    children: vec![leaf(5)],
+                },
+                leaf(6),
+            ],
+        };
+
+        let mut visited = Vec::new();
+        let mut iter = root.try_iter_mut::<BreadthFirst>();
+        while let Some(node) = iter.next() {
+            match node {
+                Ok(mut node) => {
+                    node.value *= 10;
+                    visited.push(Ok(node.value));
+                }
+                Err(e) => visited.push(Err(e)),
+            }
+        }
+        assert_eq!(visited, vec![Ok(10), Ok(20), Ok(-30), Err(-30)]);
+        assert!(iter.next().is_none());
+        assert_eq!(root.children[2].value, 6);
+    }
+}
@@ -0,0 +1,281 @@
+use std::collections::HashSet;
+
+use crate::iter::TreeNode;
+
+/// A row of an expandable tree view.
+///
+/// Rows are produced in display order, which is depth-first pre-order with
+/// the children of collapsed nodes left out.
+///
+/// # Type Parameters
+///
+/// * `'a` - The lifetime of the tree nodes being displayed.
+/// * `N` - The type of tree node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VisibleRow<'a, N> {
+    /// The node displayed on this row.
+    pub node: &'a N,
+    /// The depth of the node, with the root at depth 0.
+    pub depth: usize,
+    /// The child-index path from the root to the node.
+    pub path: Vec<usize>,
+}
+
+/// The set of expanded nodes of an interactive tree view.
+///
+/// Nodes are identified by their child-index paths from the root, which keeps the expansion
+/// state decoupled from the data tree. The children of a node are only shown when the node is
+/// expanded, and a node is only shown when all of its ancestors are expanded.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::tree::Node;
+/// use tree_iter::view::ExpansionState;
+///
+/// let tree = Node {
+///     value: 1,
+///     children: vec![
+///         Node {
+///             value: 2,
+///             children: vec![Node::new(4), Node::new(5)],
+///         },
+///         Node::new(3),
+///     ],
+/// };
+///
+/// let mut state = ExpansionState::new();
+/// let values: Vec<i32> = state.visible(&tree).map(|row| row.node.value).collect();
+/// assert_eq!(values, vec![1]);
+///
+/// // Reveal node 5, expanding the root and node 2 along the way
+/// state.expand_to_path(&[0, 1]);
+/// let values: Vec<i32> = state.visible(&tree).map(|row| row.node.value).collect();
+/// assert_eq!(values, vec![1, 2, 4, 5, 3]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExpansionState {
+    /// Paths of the expanded nodes.
+    expanded: HashSet<Vec<usize>>,
+}
+
+impl ExpansionState {
+    /// Creates an expansion state in which every node is collapsed.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `true` if the node at `path` is expanded.
+    pub fn is_expanded(&self, path: &[usize]) -> bool {
+        self.expanded.contains(path)
+    }
+
+    /// Expands the node at `path`.
+    ///
+    /// Returns `true` if the node was previously collapsed.
+    pub fn expand(&mut self, path: &[usize]) -> bool {
+        self.expanded.insert(path.to_vec())
+    }
+
+    /// Collapses the node at `path`.
+    ///
+    /// The expansion state of its descendants is kept, so they reappear as they were
+    /// when the node is expanded again. Returns `true` if the node was previously expanded.
+    pub fn collapse(&mut self, path: &[usize]) -> bool {
+        self.expanded.remove(path)
+    }
+
+    /// Toggles the node at `path` between expanded and collapsed.
+    ///
+    /// Returns `true` if the node is expanded afterwards.
+    pub fn toggle(&mut self, path: &[usize]) -> bool {
+        if self.collapse(path) {
+            false
+        } else {
+            self.expand(path)
+        }
+    }
+
+    /// Expands every ancestor of the node at `path`, making the node visible.
+    ///
+    /// The node itself is left as it was.
+    pub fn expand_to_path(&mut self, path: &[usize]) {
+        for len in 0..path.len() {
+            self.expand(&path[..len]);
+        }
+    }
+
+    /// Collapses every node.
+    pub fn collapse_all(&mut self) {
+        self.expanded.clear();
+    }
+
+    /// Returns an iterator over the expanded paths, in no particular order.
+    pub fn expanded_paths(&self) -> impl Iterator<Item = &[usize]> {
+        self.expanded.iter().map(Vec::as_slice)
+    }
+
+    /// Creates an iterator over the visible rows of the tree rooted at `root`.
+    ///
+    /// Rows are yielded in display order, and the children of collapsed nodes are never walked.
+    pub fn visible<'a, 's, N: TreeNode>(&'s self, root: &'a N) -> VisibleRows<'a, 's, N> {
+        VisibleRows {
+            stack: vec![(root, Vec::new())],
+            state: self,
+        }
+    }
+}
+
+impl FromIterator<Vec<usize>> for ExpansionState {
+    /// Creates an expansion state in which the nodes at the given paths are expanded.
+    fn from_iter<I: IntoIterator<Item = Vec<usize>>>(iter: I) -> Self {
+        Self {
+            expanded: iter.into_iter().collect(),
+        }
+    }
+}
+
+/// An iterator over the visible rows of an expandable tree.
+///
+/// This struct is created by [`ExpansionState::visible`].
+///
+/// # Type Parameters
+///
+/// * `'a` - The lifetime of the tree nodes being displayed.
+/// * `'s` - The lifetime of the expansion state.
+/// * `N` - The type of tree node.
+#[derive(Debug)]
+pub struct VisibleRows<'a, 's, N> {
+    /// Stack of nodes to be displayed, along with their paths.
+    stack: Vec<(&'a N, Vec<usize>)>,
+    /// The expansion state deciding which children are shown.
+    state: &'s ExpansionState,
+}
+
+impl<'a, N: TreeNode> Iterator for VisibleRows<'a, '_, N> {
+    type Item = VisibleRow<'a, N>;
+
+    /// Returns the next visible row in display order.
+    fn next(&mut self) -> Option<Self::Item> {
+        let (node, path) = self.stack.pop()?;
+        if self.state.is_expanded(&path) {
+            let children: Vec<_> = node.children().enumerate().collect();
+            for (i, child) in children.into_iter().rev() {
+                let mut child_path = path.clone();
+                child_path.push(i);
+                self.stack.push((child, child_path));
+            }
+        }
+        Some(VisibleRow {
+            node,
+            depth: path.len(),
+            path,
+        })
+    }
+}
+
+/// Computes the rows of an expandable tree that fall within a viewport.
+///
+/// The rows are numbered in display order starting from the root at row 0, and the rows
+/// `scroll_offset..scroll_offset + viewport_len` are returned.
+///
+/// Collapsed subtrees are never walked, and the walk stops as soon as the viewport is filled,
+/// so the cost is proportional to the number of visible rows up to the end of the viewport.
+///
+/// # Parameters
+///
+/// * `root` - The root node of the tree.
+/// * `scroll_offset` - The index of the first row to return.
+/// * `viewport_len` - The maximum number of rows to return.
+/// * `expanded` - The expansion state of the tree.
+///
+/// # Examples
+///
+/// ```rust
+/// use tree_iter::tree::Node;
+/// use tree_iter::view::{ExpansionState, visible_slice};
+///
+/// let tree = Node {
+///     value: 1,
+///     children: vec![
+///         Node {
+///             value: 2,
+///             children: vec![Node::new(4), Node::new(5)],
+///         },
+///         Node::new(3),
+///     ],
+/// };
+///
+/// // Only the root is expanded, so node 2 hides its children
+/// let expanded = ExpansionState::from_iter([vec![]]);
+/// let rows = visible_slice(&tree, 1, 10, &expanded);
+/// let values: Vec<i32> = rows.iter().map(|row| row.node.value).collect();
+/// assert_eq!(values, vec![2, 3]);
+/// ```
+pub fn visible_slice<'a, N: TreeNode>(
+    root: &'a N,
+    scroll_offset: usize,
+    viewport_len: usize,
+    expanded: &ExpansionState,
+) -> Vec<VisibleRow<'a, N>> {
+    expanded
+        .visible(root)
+        .skip(scroll_offset)
+        .take(viewport_len)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::Node;
+
+    fn sample_tree() -> Node<i32> {
+        Node {
+            value: 1,
+            children: vec![
+                Node {
+                    value: 2,
+                    children: vec![Node::new(4), Node::new(5)],
+                },
+                Node {
+                    value: 3,
+                    children: vec![Node::new(6)],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_toggle_keeps_descendant_state() {
+        let tree = sample_tree();
+        let mut state = ExpansionState::from_iter([vec![], vec![0]]);
+        let values: Vec<i32> = state.visible(&tree).map(|row| row.node.value).collect();
+        assert_eq!(values, vec![1, 2, 4, 5, 3]);
+
+        // Collapsing the root hides everything, but node 2 stays expanded
+        assert!(!state.toggle(&[]));
+        let values: Vec<i32> = state.visible(&tree).map(|row| row.node.value).collect();
+        assert_eq!(values, vec![1]);
+        assert!(state.is_expanded(&[0]));
+
+        assert!(state.toggle(&[]));
+        let values: Vec<i32> = state.visible(&tree).map(|row| row.node.value).collect();
+        assert_eq!(values, vec![1, 2, 4, 5, 3]);
+    }
+
+    #[test]
+    fn test_visible_slice_window() {
+        let tree = sample_tree();
+        let mut state = ExpansionState::new();
+        state.expand_to_path(&[0, 0]);
+        state.expand_to_path(&[1, 0]);
+
+        let rows = visible_slice(&tree, 2, 3, &state);
+        let paths: Vec<Vec<usize>> = rows.iter().map(|row| row.path.clone()).collect();
+        assert_eq!(paths, vec![vec![0, 0], vec![0, 1], vec![1]]);
+        assert_eq!(rows[0].depth, 2);
+
+        assert!(visible_slice(&tree, 10, 3, &state).is_empty());
+    }
+}
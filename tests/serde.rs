@@ -0,0 +1,127 @@
+//! Serde round trips of trees through JSON and bincode.
+//!
+//! These live outside the library so that the `PartialEq` impls of `serde_json` do not
+//! change type inference in its unit tests.
+#![cfg(feature = "serde")]
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use tree_iter::prelude::*;
+use tree_iter::tree;
+use tree_iter::tree::{Node, Tree};
+
+#[test]
+fn test_json_round_trip() {
+    let tree = tree! { 1 => [2 => [4], 3] };
+    let json = serde_json::to_string(&tree).unwrap();
+    assert_eq!(
+        json,
+        r#"{"value":1,"children":[{"value":2,"children":[{"value":4,"children":[]}]},{"value":3,"children":[]}]}"#
+    );
+    assert_eq!(serde_json::from_str::<Node<i32>>(&json).unwrap(), tree);
+
+    // Leaves may leave out their children
+    let short = r#"{"value":1,"children":[{"value":2},{"value":3,"extra":true}]}"#;
+    assert_eq!(
+        serde_json::from_str::<Node<i32>>(short).unwrap(),
+        tree! { 1 => [2, 3] }
+    );
+    assert_eq!(
+        serde_json::from_str::<Node<i32>>("[1, [[2], [3, [[4]]]]]").unwrap(),
+        tree! { 1 => [2, 3 => [4]] }
+    );
+    for json in [
+        r#"{"children":[]}"#,
+        r#"{"value":1,"children":[{"children":[]}]}"#,
+        r#"{"value":1,"value":2}"#,
+        r#"{"value":1,"children":[],"children":[]}"#,
+        r#"{"value":1,"children":[{"value":"2"}]}"#,
+        "[]",
+    ] {
+        assert!(serde_json::from_str::<Node<i32>>(json).is_err(), "{json}");
+    }
+
+    let empty: Tree<i32> = serde_json::from_str("null").unwrap();
+    assert!(empty.is_empty());
+    let tree = Tree::from(tree);
+    let json = serde_json::to_string(&tree).unwrap();
+    assert_eq!(serde_json::from_str::<Tree<i32>>(&json).unwrap(), tree);
+}
+
+#[test]
+fn test_deep_nested_json() {
+    let depth = 20_000;
+    let mut json = String::new();
+    for i in 0..depth {
+        json.push_str(&format!(r#"{{"value":{i},"children":["#));
+    }
+    json.push_str(&"]}".repeat(depth));
+    // `serde_json` recurses once per level of nesting, so its stack has to grow
+    let mut deserializer = serde_json::Deserializer::from_str(&json);
+    deserializer.disable_recursion_limit();
+    let deserializer = serde_stacker::Deserializer::new(&mut deserializer);
+    let tree = Node::<usize>::deserialize(deserializer).unwrap();
+    assert_eq!(tree.height(), depth - 1);
+    assert_eq!(tree.iter::<DepthFirst>().last().unwrap().value, depth - 1);
+}
+
+/// A tree serialized as a flat list of records.
+#[derive(Debug, PartialEq)]
+struct Flat(Node<i32>);
+
+impl Serialize for Flat {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        tree_iter::adjacency::serialize(&self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Flat {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        tree_iter::adjacency::deserialize(deserializer).map(Flat)
+    }
+}
+
+#[test]
+fn test_length_prefixed_records() {
+    let tree = tree! { 1 => [2 => [4], 3] };
+    let bytes = bincode::serialize(&Flat(tree.clone())).unwrap();
+    // The length prefix, then each record as its id, an optional parent id and its value
+    assert_eq!(bytes.len(), 8 + 4 * (8 + 1 + 4) + 3 * 8);
+    assert_eq!(bincode::deserialize::<Flat>(&bytes).unwrap(), Flat(tree));
+}
+
+#[test]
+fn test_unordered_records() {
+    let json = r#"[
+        {"id": 30, "parent_id": 10, "value": 3},
+        {"id": 10, "value": 1},
+        {"id": 20, "parent_id": 10, "value": 2},
+        {"id": 40, "parent_id": 30, "value": 4}
+    ]"#;
+    let Flat(tree) = serde_json::from_str(json).unwrap();
+    assert_eq!(tree, tree! { 1 => [3 => [4], 2] });
+}
+
+#[test]
+fn test_invalid_records() {
+    for json in [
+        r#"[]"#,
+        r#"[{"id": 1, "value": 1}, {"id": 1, "parent_id": 1, "value": 2}]"#,
+        r#"[{"id": 1, "value": 1}, {"id": 2, "value": 2}]"#,
+        r#"[{"id": 1, "value": 1}, {"id": 2, "parent_id": 3, "value": 2}]"#,
+        r#"[{"id": 1, "value": 1}, {"id": 2, "parent_id": 3, "value": 2}, {"id": 3, "parent_id": 2, "value": 3}]"#,
+    ] {
+        assert!(serde_json::from_str::<Flat>(json).is_err(), "{json}");
+    }
+}
+
+#[test]
+fn test_deep_round_trip() {
+    let mut tree = Node::new(0);
+    for i in 1..100_000 {
+        tree = Node::with_children(i, [tree]);
+    }
+    let json = serde_json::to_string(&Flat(tree)).unwrap();
+    let Flat(tree) = serde_json::from_str(&json).unwrap();
+    assert_eq!(tree.height(), 99_999);
+    assert_eq!(tree.value, 99_999);
+}